@@ -19,12 +19,20 @@ impl<T: 'static + Clone + Send + Sync> UnboundedBroadcast<T> {
         rx
     }
 
-    pub fn send(&self, message: T) -> Result<(), crossbeam_channel::SendError<T>> {
-        for c in self.channels.iter() {
-            c.send(message.clone())?;
-        }
+    /// returns the number of subscribers the next message will be sent to, which
+    /// includes subscribers dropped since the last send
+    pub fn subscriber_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// sends the message to every subscriber, returning the number of subscribers it was
+    /// delivered to. delivery is attempted to every subscriber, even if the receivers of
+    /// earlier subscribers have been dropped, and subscribers whose receiver has been dropped
+    /// are removed
+    pub fn send(&mut self, message: T) -> usize {
+        self.channels.retain(|c| c.send(message.clone()).is_ok());
 
-        Ok(())
+        self.channels.len()
     }
 }
 
@@ -33,3 +41,42 @@ impl<T: 'static + Clone + Send + Sync> Default for UnboundedBroadcast<T> {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_send_prunes_dropped_subscribers() {
+        let mut broadcaster = UnboundedBroadcast::new();
+        assert_eq!(broadcaster.subscriber_count(), 0);
+        assert_eq!(broadcaster.send(true), 0);
+
+        let first = broadcaster.subscribe();
+        let second = broadcaster.subscribe();
+        assert_eq!(broadcaster.subscriber_count(), 2);
+        drop(first);
+        assert_eq!(broadcaster.send(true), 1);
+        assert_eq!(broadcaster.subscriber_count(), 1);
+        assert!(second.recv().unwrap());
+
+        drop(second);
+        assert_eq!(broadcaster.send(false), 0);
+        assert_eq!(broadcaster.subscriber_count(), 0);
+    }
+    #[test]
+    fn test_send_past_dropped_subscriber() {
+        let mut broadcaster = UnboundedBroadcast::new();
+        let first = broadcaster.subscribe();
+        let middle = broadcaster.subscribe();
+        let last = broadcaster.subscribe();
+        drop(middle);
+        // the dropped subscriber doesn't prevent delivery to the subscribers after it
+        assert_eq!(broadcaster.send(true), 2);
+        assert!(first.recv().unwrap());
+        assert!(last.recv().unwrap());
+        // and subsequent messages are still delivered to the remaining subscribers
+        assert_eq!(broadcaster.send(false), 2);
+        assert!(!first.recv().unwrap());
+        assert!(!last.recv().unwrap());
+    }
+}
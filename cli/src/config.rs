@@ -7,7 +7,7 @@ pub fn new_config(_matches: &clap::ArgMatches, config_file_path: String) -> Resu
 }
 
 pub fn export_as_json(_matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
-    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let config = Configuration::load_auto(config_file_path.as_str())?;
     let name_parts: Vec<&str> = config_file_path.split('.').collect();
     let mut name = String::new();
     name.push_str(name_parts[0]);
@@ -17,23 +17,35 @@ pub fn export_as_json(_matches: &clap::ArgMatches, config_file_path: String) ->
 }
 
 pub fn fix(config_file_path: String) -> Result<()> {
-    let mut config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut config = Configuration::load_auto(config_file_path.as_str())?;
     config.fix();
-    config.save(&config_file_path, false)?;
+    config.save(
+        &config_file_path,
+        config_file_path.to_lowercase().ends_with(".json"),
+    )?;
     Ok(())
 }
 
 pub fn seed_database(config_file_path: String) -> Result<()> {
-    let config = Configuration::load(config_file_path.as_str(), false)?;
-    let rpc_client = config.rpc_client();
-    let db = tulip_realms_sdk::Database::new(config.db_opts)?;
-    db.populate_database_with_mint_governance(
-        config.realm_info.realm_key(),
-        config.realm_info.council_mint_key(),
-        config.realm_info.community_mint_key(),
-        Utc::now(),
-        &rpc_client,
-    )
-    .unwrap();
+    let config = Configuration::load_auto(config_file_path.as_str())?;
+    let db = tulip_realms_sdk::Database::new(config.db_opts.clone())?;
+    // every account is keyed by its pubkey, so the realms share the database
+    for realm in config.realms.iter() {
+        let rpc_client = config.realm_rpc_client(realm);
+        let summary = db.populate_database_with_governances(
+            realm.realm_key(),
+            &realm.governance_keys(),
+            &realm.governing_token_mints(),
+            Utc::now(),
+            &rpc_client,
+        )?;
+        println!(
+            "realm {}: loaded {}/{} proposals ({} missing)",
+            realm.realm_key,
+            summary.loaded_proposals,
+            summary.expected_proposals,
+            summary.missing_indices.len()
+        );
+    }
     Ok(())
 }
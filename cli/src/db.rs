@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use config::Configuration;
+use serde_json::json;
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+
+fn open_database(config_file_path: &str) -> Result<tulip_realms_sdk::Database> {
+    let config = Configuration::load_auto(config_file_path)?;
+    Ok(tulip_realms_sdk::Database::new(config.db_opts)?)
+}
+
+/// returns the vote weight of the most voted option
+fn approval_votes(proposal: &tulip_realms_sdk::types::ProposalV2Wrapper) -> u64 {
+    proposal
+        .proposal
+        .options
+        .iter()
+        .map(|option| option.vote_weight)
+        .max()
+        .unwrap_or(0)
+}
+
+fn print_json(values: Vec<serde_json::Value>) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&values)?);
+    Ok(())
+}
+
+pub fn list_proposals(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let db = open_database(&config_file_path)?;
+    let proposals = db.list_proposals()?;
+    if matches.is_present("json") {
+        return print_json(
+            proposals
+                .iter()
+                .map(|proposal| {
+                    json!({
+                        "key": proposal.key.to_string(),
+                        "governance": proposal.proposal.governance.to_string(),
+                        "name": proposal.proposal.name,
+                        "state": format!("{:?}", proposal.proposal.state),
+                        "approval_votes": approval_votes(proposal),
+                        "deny_votes": proposal.proposal.deny_vote_weight.unwrap_or(0),
+                    })
+                })
+                .collect(),
+        );
+    }
+    for proposal in proposals.iter() {
+        println!(
+            "{} name: {}, state: {:?}, approval votes: {}, deny votes: {}",
+            proposal.key,
+            proposal.proposal.name,
+            proposal.proposal.state,
+            approval_votes(proposal),
+            proposal.proposal.deny_vote_weight.unwrap_or(0)
+        );
+    }
+    println!("{} proposals", proposals.len());
+    Ok(())
+}
+
+pub fn list_governances(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let db = open_database(&config_file_path)?;
+    let governances = db.list_governances()?;
+    if matches.is_present("json") {
+        return print_json(
+            governances
+                .iter()
+                .map(|governance| {
+                    json!({
+                        "key": governance.key.to_string(),
+                        "realm": governance.governance.realm.to_string(),
+                        "governed_account": governance.governance.governed_account.to_string(),
+                        "proposals_count": governance.governance.proposals_count,
+                    })
+                })
+                .collect(),
+        );
+    }
+    for governance in governances.iter() {
+        println!(
+            "{} realm: {}, governed account: {}, proposals: {}",
+            governance.key,
+            governance.governance.realm,
+            governance.governance.governed_account,
+            governance.governance.proposals_count
+        );
+    }
+    println!("{} governances", governances.len());
+    Ok(())
+}
+
+pub fn list_realms(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let db = open_database(&config_file_path)?;
+    let realms = db.list_realms()?;
+    if matches.is_present("json") {
+        return print_json(
+            realms
+                .iter()
+                .map(|realm| {
+                    json!({
+                        "key": realm.key.to_string(),
+                        "name": realm.display_name(),
+                        "community_mint": realm.realm.community_mint.to_string(),
+                        "council_mint": realm
+                            .realm
+                            .config
+                            .council_mint
+                            .map(|mint| mint.to_string()),
+                    })
+                })
+                .collect(),
+        );
+    }
+    for realm in realms.iter() {
+        println!(
+            "{} name: {}, community mint: {}",
+            realm.key,
+            realm.display_name(),
+            realm.realm.community_mint
+        );
+    }
+    println!("{} realms", realms.len());
+    Ok(())
+}
+
+pub fn stats(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let db = open_database(&config_file_path)?;
+    let stats = db.stats()?;
+    if matches.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    println!("realms: {}", stats.realms);
+    println!("governances: {}", stats.governances);
+    println!("proposals: {}", stats.proposals);
+    println!("notification caches: {}", stats.notif_caches);
+    println!("voting proposals: {}", stats.voting_proposals);
+    Ok(())
+}
+
+pub fn refresh_proposal(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let config = Configuration::load_auto(&config_file_path)?;
+    let db = tulip_realms_sdk::Database::new(config.db_opts.clone())?;
+    let proposal_key = matches.value_of("proposal").unwrap();
+    let proposal_key = Pubkey::from_str(proposal_key)
+        .with_context(|| format!("invalid proposal key {}", proposal_key))?;
+    let rpc_client = config.realm_rpc_client(config.proposal_realm(&db, proposal_key)?);
+    let (previous, proposal) = db
+        .refresh_proposal(proposal_key, &rpc_client)
+        .with_context(|| format!("failed to refresh proposal {}", proposal_key))?;
+    match previous {
+        Some(previous) => println!(
+            "before: state: {:?}, approval votes: {}, deny votes: {}",
+            previous.proposal.state,
+            approval_votes(&previous),
+            previous.proposal.deny_vote_weight.unwrap_or(0)
+        ),
+        None => println!("before: not cached"),
+    }
+    println!(
+        "after: state: {:?}, approval votes: {}, deny votes: {}",
+        proposal.proposal.state,
+        approval_votes(&proposal),
+        proposal.proposal.deny_vote_weight.unwrap_or(0)
+    );
+    Ok(())
+}
+
+pub fn compact(config_file_path: String) -> Result<()> {
+    let db = open_database(&config_file_path)?;
+    let size_before = db.size_on_disk()?;
+    db.maintenance()?;
+    println!(
+        "database size on disk {} -> {} bytes",
+        size_before,
+        db.size_on_disk()?
+    );
+    Ok(())
+}
+
+pub fn export(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let db = open_database(&config_file_path)?;
+    let export = db.export()?;
+    let out = matches.value_of("out").unwrap();
+    std::fs::write(out, serde_json::to_string_pretty(&export)?)
+        .with_context(|| format!("failed to write export to {}", out))?;
+    println!(
+        "exported {} realms, {} governances, {} proposals and {} notification caches to {}",
+        export.realms.len(),
+        export.governances.len(),
+        export.proposals.len(),
+        export.notif_caches.len(),
+        out
+    );
+    Ok(())
+}
+
+pub fn import(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let db = open_database(&config_file_path)?;
+    let input = matches.value_of("in").unwrap();
+    let export: tulip_realms_sdk::types::DatabaseExport = serde_json::from_str(
+        &std::fs::read_to_string(input)
+            .with_context(|| format!("failed to read export from {}", input))?,
+    )
+    .with_context(|| format!("failed to parse export {}", input))?;
+    let summary = db.import(&export, matches.is_present("overwrite"))?;
+    println!(
+        "imported {} entries from {} ({} already stored entries skipped)",
+        summary.imported, input, summary.skipped
+    );
+    Ok(())
+}
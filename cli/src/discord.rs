@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use log::error;
 use signal_hook::{
     consts::{SIGINT, SIGQUIT, SIGTERM},
@@ -6,8 +6,13 @@ use signal_hook::{
 };
 use std::sync::Arc;
 pub async fn start<'a>(_matches: &clap::ArgMatches<'a>, config_file_path: String) -> Result<()> {
-    let config = config::Configuration::load(&config_file_path, false)?;
-    config.init_log(false);
+    let config = config::Configuration::load_auto(&config_file_path)?;
+    // validate before initializing logging, so that an invalid log level is reported
+    // along with any other problems instead of failing logger setup
+    config
+        .validate()
+        .with_context(|| format!("invalid configuration {}", config_file_path))?;
+    config.init_log(config.file_log)?;
     let mut broadcaster = channels::broadcast::UnboundedBroadcast::new();
     let subscriber = broadcaster.subscribe();
     let mut signals =
@@ -17,8 +22,8 @@ pub async fn start<'a>(_matches: &clap::ArgMatches<'a>, config_file_path: String
             if let Some(sig) = signals.forever().next() {
                 error!("caught signal {:#?}", sig);
             }
-            if let Err(err) = broadcaster.send(true) {
-                error!("broadcaster failed to notify {:#?}", err);
+            if broadcaster.send(true) == 0 {
+                error!("broadcaster failed to notify, no subscribers remain");
             }
         });
     }
@@ -3,6 +3,7 @@ static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 use anyhow::{anyhow, Result};
 use clap::{App, Arg, SubCommand};
 mod config;
+mod db;
 mod discord;
 
 #[tokio::main]
@@ -27,11 +28,67 @@ async fn main() -> Result<()> {
                         .about("generates a new and empty configuration file"),
                     SubCommand::with_name("export-as-json")
                         .about("exports the yaml config file into a json file"),
-                    SubCommand::with_name("fix").about("fix bad or missing configurations"),
+                    SubCommand::with_name("fix")
+                        .about("fix bad or missing configurations, migrating deprecated fields"),
                     SubCommand::with_name("seed-database")
                         .about("seeds the sled database with realm information"),
                 ]),
         )
+        .subcommand(
+            SubCommand::with_name("db")
+                .about("inspect the sled database")
+                .subcommands(vec![
+                    SubCommand::with_name("list-proposals")
+                        .about("lists the cached proposals")
+                        .arg(json_arg()),
+                    SubCommand::with_name("list-governances")
+                        .about("lists the cached governances")
+                        .arg(json_arg()),
+                    SubCommand::with_name("list-realms")
+                        .about("lists the cached realms")
+                        .arg(json_arg()),
+                    SubCommand::with_name("stats")
+                        .about("counts the cached accounts and notification caches")
+                        .arg(json_arg()),
+                    SubCommand::with_name("refresh-proposal")
+                        .about("re-fetches a proposal from chain, updating the cached entry")
+                        .arg(
+                            Arg::with_name("proposal")
+                                .value_name("PUBKEY")
+                                .help("the proposal account to refresh")
+                                .required(true)
+                                .takes_value(true),
+                        ),
+                    SubCommand::with_name("compact")
+                        .about("flushes the database to disk, reporting its size"),
+                    SubCommand::with_name("export")
+                        .about("exports the cached accounts and notification caches to a json file")
+                        .arg(
+                            Arg::with_name("out")
+                                .long("out")
+                                .value_name("FILE")
+                                .help("the file to write the export to")
+                                .takes_value(true)
+                                .required(true),
+                        ),
+                    SubCommand::with_name("import")
+                        .about("imports a json file written by the export command")
+                        .arg(
+                            Arg::with_name("in")
+                                .long("in")
+                                .value_name("FILE")
+                                .help("the file to read the export from")
+                                .takes_value(true)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("overwrite")
+                                .long("overwrite")
+                                .help("replace entries which are already stored instead of skipping them")
+                                .takes_value(false),
+                        ),
+                ]),
+        )
         .subcommand(SubCommand::with_name("discord").about("run the discord bot"))
         .get_matches();
     let config_file_path = get_config_or_default(&matches);
@@ -39,6 +96,13 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+fn json_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("json")
+        .long("json")
+        .help("output as json instead of human readable text")
+        .takes_value(false)
+}
+
 // returns the value of the config file argument or the default
 fn get_config_or_default(matches: &clap::ArgMatches) -> String {
     matches
@@ -61,6 +125,23 @@ async fn process_matches<'a>(
             ("seed-database", Some(_)) => config::seed_database(config_file_path),
             _ => invalid_subcommand("config"),
         },
+        ("db", Some(db_command)) => match db_command.subcommand() {
+            ("list-proposals", Some(list_proposals)) => {
+                db::list_proposals(list_proposals, config_file_path)
+            }
+            ("list-governances", Some(list_governances)) => {
+                db::list_governances(list_governances, config_file_path)
+            }
+            ("list-realms", Some(list_realms)) => db::list_realms(list_realms, config_file_path),
+            ("stats", Some(stats)) => db::stats(stats, config_file_path),
+            ("refresh-proposal", Some(refresh_proposal)) => {
+                db::refresh_proposal(refresh_proposal, config_file_path)
+            }
+            ("compact", Some(_)) => db::compact(config_file_path),
+            ("export", Some(export)) => db::export(export, config_file_path),
+            ("import", Some(import)) => db::import(import, config_file_path),
+            _ => invalid_subcommand("db"),
+        },
         ("discord", Some(discord_args)) => discord::start(discord_args, config_file_path).await,
         _ => invalid_command(),
     }
@@ -0,0 +1,73 @@
+//! a logger which emits each record as a single line json object, for ingestion by log pipelines
+
+use crate::rotating_file::RotatingFile;
+use anyhow::{anyhow, Result};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct JsonLogger {
+    level: LevelFilter,
+    file: Option<Mutex<RotatingFile>>,
+}
+
+impl JsonLogger {
+    /// sets the json logger as the global logger, logging to stdout and optionally to a file
+    pub fn init(level: LevelFilter, file: Option<RotatingFile>) -> Result<()> {
+        let logger = Box::new(JsonLogger {
+            level,
+            file: file.map(Mutex::new),
+        });
+        log::set_logger(Box::leak(logger))
+            .map_err(|err| anyhow!("failed to set json logger {:#?}", err))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+    /// formats the record as a json object. messages which are themselves json objects, such
+    /// as those describing sent notifications, have their fields merged into the log line
+    pub fn format(record: &Record) -> String {
+        let mut entry = Map::new();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        entry.insert("timestamp".to_string(), timestamp.into());
+        entry.insert("level".to_string(), record.level().to_string().into());
+        entry.insert("target".to_string(), record.target().into());
+        let message = record.args().to_string();
+        match serde_json::from_str::<Value>(&message) {
+            Ok(Value::Object(fields)) => entry.extend(fields),
+            _ => {
+                entry.insert("message".to_string(), message.into());
+            }
+        }
+        Value::Object(entry).to_string()
+    }
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = JsonLogger::format(record);
+        println!("{}", line);
+        if let Some(file) = self.file.as_ref() {
+            if let Ok(mut file) = file.lock() {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+    fn flush(&self) {
+        if let Some(file) = self.file.as_ref() {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
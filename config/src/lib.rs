@@ -1,22 +1,74 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use simplelog::*;
 use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::pubkey::Pubkey;
 
 use std::fs;
-use std::fs::File;
 use std::str::FromStr;
+
+pub mod json_log;
+pub mod rotating_file;
 /// main configuration object
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Configuration {
     pub discord: Discord,
     pub db_opts: tulip_sled_util::config::DbOpts,
-    /// information for a particular realms configuration, only supporting mint based governance
-    pub realm_info: RealmsConfig,
+    /// when non-zero, a warning is logged once the database grows beyond this size in megabytes
+    #[serde(default)]
+    pub max_db_size_mb: u64,
+    /// the realms to track, and the governances of each realm to track. configurations
+    /// created before multiple realms were supported describe a single realm with `realm_info`,
+    /// which is still accepted
+    #[serde(alias = "realm_info", deserialize_with = "deserialize_realms")]
+    pub realms: Vec<RealmsConfig>,
+    /// when true, logs are written to `log_file` in addition to stdout
+    #[serde(default)]
+    pub file_log: bool,
     pub log_file: String,
+    /// the size in megabytes the log file may grow to before it is rotated. 0 disables rotation
+    #[serde(default = "default_log_max_size_mb")]
+    pub log_max_size_mb: u64,
+    /// the number of rotated log files to keep
+    #[serde(default = "default_log_keep_files")]
+    pub log_keep_files: usize,
+    /// when true, debug logs are enabled regardless of `log_level`
     pub debug_log: bool,
+    /// the level logs are emitted at, one of trace, debug, info, warn, or error
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// when true, log lines are emitted as json objects instead of human readable text
+    #[serde(default)]
+    pub json_logs: bool,
     pub rpc_url: String,
+    /// the commitment level of rpc requests, one of processed, confirmed, or finalized
+    #[serde(default = "default_rpc_commitment")]
+    pub rpc_commitment: String,
+    /// optional rpc url used for get_program_accounts scans (ie: fetching vote records), which
+    /// many public rpc nodes disable or rate limit. when unset the realm's rpc url is used
+    #[serde(default)]
+    pub gpa_rpc_url: Option<String>,
+    /// when set, prometheus metrics for the worker loop are served on this port at `/metrics`
+    #[serde(default)]
+    pub metrics_port: Option<u16>,
+    /// when set, liveness and readiness checks are served on this port at `/healthz` and
+    /// `/readyz`. readiness fails once the worker loop hasn't run for two iterations
+    #[serde(default)]
+    pub health_port: Option<u16>,
+    /// additional destinations new proposal and voting stats notifications are delivered to
+    #[serde(default)]
+    pub notification_sinks: Vec<NotificationSinkConfig>,
+}
+
+/// a destination for proposal notifications, other than the discord bot itself
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationSinkConfig {
+    /// posts each proposal event as json to the url
+    Webhook { url: String },
+    /// posts each proposal event as an embed to a discord webhook url
+    DiscordWebhook { url: String },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -24,7 +76,52 @@ pub struct RealmsConfig {
     pub realm_key: String,
     pub council_mint_key: String,
     pub community_mint_key: String,
+    /// the governance accounts of the realm to track proposals for
+    #[serde(default)]
+    pub governance_keys: Vec<String>,
+    /// deprecated: configurations created before multiple governances were supported track
+    /// a single governance with this field, which `migrate` moves into `governance_keys`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub governance_key: String,
+    /// optional rpc url used for this realm's rpc calls, overriding `Configuration.rpc_url`.
+    /// useful when monitoring realms that live on different clusters
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+    /// optional channel this realm's messages are posted to, in place of `Discord.status_channel`.
+    /// when set, the realm's notifications are also posted to it unless `proposal_channel` or
+    /// `stats_channel` are set, allowing a single bot to serve several daos in separate channels
+    #[serde(default)]
+    pub channel_id: Option<u64>,
+    /// optional channel this realm's new proposal notifications and results are posted to,
+    /// overriding `Discord.proposal_channel`
+    #[serde(default)]
+    pub proposal_channel: Option<u64>,
+    /// optional channel this realm's voting stats updates are posted to, overriding
+    /// `Discord.stats_channel`
+    #[serde(default)]
+    pub stats_channel: Option<u64>,
+    /// optional base url of this realm in a realms ui, overriding `Discord.ui_base_url`.
+    /// when tracking several realms, `fix` populates it with the public website's url
+    #[serde(default)]
+    pub ui_base_url: Option<String>,
+}
+
+/// a single realm, or a list of realms
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrManyRealms {
+    One(Box<RealmsConfig>),
+    Many(Vec<RealmsConfig>),
+}
+
+fn deserialize_realms<'de, D>(deserializer: D) -> std::result::Result<Vec<RealmsConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match OneOrManyRealms::deserialize(deserializer)? {
+        OneOrManyRealms::One(realm) => vec![*realm],
+        OneOrManyRealms::Many(realms) => realms,
+    })
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -32,16 +129,111 @@ pub struct Discord {
     /// the discord bot token
     pub bot_token: String,
     /// the base url for a realms ui, for example with the public website it would be
-    /// https://realms.today/dao
+    /// https://realms.today/dao/<realm-id>
+    ///
+    /// when empty, `fix` populates it with the public website's url for the configured realm
+    #[serde(default)]
     pub ui_base_url: String,
     /// the channel to post messages too
     pub status_channel: u64,
+    /// the channel to post new proposal notifications and results to, if 0 `status_channel` is used
+    #[serde(default)]
+    pub proposal_channel: u64,
+    /// the channel to post voting stats updates to, if 0 `status_channel` is used
+    #[serde(default)]
+    pub stats_channel: u64,
+    /// the role mentioned when a new proposal is detected, if 0 no role is mentioned
+    #[serde(default)]
+    pub notify_role_id: u64,
     /// how often the workloop should run
     /// which is responsible for things such as automated
     /// check ins, etc..
     pub worker_loop_frequency: u64,
+    /// when non-zero, each iteration of the worker loop sleeps for `worker_loop_frequency`
+    /// offset by a random amount of up to `worker_loop_jitter_secs` seconds in either direction,
+    /// so that instances sharing an rpc provider don't issue their requests in lockstep
+    #[serde(default)]
+    pub worker_loop_jitter_secs: u64,
     /// how often to notify about voting proposals in hours
+    #[serde(default = "default_notification_frequency")]
     pub notification_frequency: i64,
+    /// when non-zero, a reminder is posted once for each voting proposal when it enters
+    /// the final `final_reminder_hours` hours of voting
+    #[serde(default)]
+    pub final_reminder_hours: i64,
+    /// when true, a thread is created off the proposal channel for each new proposal
+    /// and voting stats updates for the proposal are posted into its thread
+    #[serde(default)]
+    pub use_threads: bool,
+    /// when true, a message is posted to the status channel once the worker loop starts
+    #[serde(default)]
+    pub announce_startup: bool,
+    /// when true, an alert is posted whenever a proposal enters the Executing
+    /// or ExecutingWithErrors state, as these typically need the attention of admins
+    #[serde(default = "default_execution_alerts")]
+    pub execution_alerts: bool,
+    /// ids of the roles permitted to use the read-only commands, such as ~status.
+    /// commands which modify state are always restricted to the bot owners.
+    ///
+    /// if empty, read-only commands may be used by anyone in the status channel
+    #[serde(default)]
+    pub command_roles: Vec<u64>,
+    /// number of attempts made to load the community mint when the worker loop starts,
+    /// backing off between attempts to ride out transient rpc failures
+    #[serde(default = "default_mint_load_attempts")]
+    pub mint_load_attempts: u32,
+    /// when true, the content linked to by a proposal's `description_link` is fetched and an
+    /// excerpt of it is included in new proposal notifications, instead of the raw link
+    #[serde(default)]
+    pub resolve_descriptions: bool,
+    /// the maximum number of characters of a proposal's description shown in notifications,
+    /// longer descriptions are truncated. at most `MAX_DESCRIPTION_CHARS`
+    #[serde(default = "default_description_max_chars")]
+    pub description_max_chars: usize,
+    /// when true, voting stats updates include the share of the community mint's supply
+    /// which has voted on the proposal
+    #[serde(default)]
+    pub show_participation_rate: bool,
+    /// when true, messages and notification sink events are logged instead of being posted,
+    /// allowing the worker loop to be run against a production realm while debugging
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn default_execution_alerts() -> bool {
+    true
+}
+
+fn default_notification_frequency() -> i64 {
+    6
+}
+
+fn default_mint_load_attempts() -> u32 {
+    5
+}
+
+fn default_description_max_chars() -> usize {
+    512
+}
+
+/// the maximum number of characters discord permits in an embed field's value, which
+/// descriptions are rendered in
+pub const MAX_DESCRIPTION_CHARS: usize = 1024;
+
+fn default_rpc_commitment() -> String {
+    "finalized".to_string()
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_log_max_size_mb() -> u64 {
+    100
+}
+
+fn default_log_keep_files() -> usize {
+    5
 }
 
 impl Configuration {
@@ -56,142 +248,931 @@ impl Configuration {
         } else {
             serde_yaml::to_string(&self)?
         };
-        fs::write(path, data).expect("failed to write to file");
+        fs::write(path, data).with_context(|| format!("failed to write config file {}", path))?;
         Ok(())
     }
+    /// loads the configuration, detecting the format from the file extension.
+    /// files ending in `.json` are parsed as json, and everything else as yaml
+    pub fn load_auto(path: &str) -> Result<Configuration> {
+        Configuration::load(path, path.to_lowercase().ends_with(".json"))
+    }
     pub fn load(path: &str, from_json: bool) -> Result<Configuration> {
-        let data = fs::read(path).expect("failed to read file");
+        let data =
+            fs::read(path).with_context(|| format!("failed to read config file {}", path))?;
+        // serde errors name the offending field, so include them alongside the path
         let config: Configuration = if from_json {
-            serde_json::from_slice(data.as_slice())?
+            serde_json::from_slice(data.as_slice())
+                .with_context(|| format!("failed to parse {} as json", path))?
         } else {
-            serde_yaml::from_slice(data.as_slice())?
+            serde_yaml::from_slice(data.as_slice())
+                .with_context(|| format!("failed to parse {} as yaml", path))?
         };
         Ok(config)
     }
     pub fn rpc_client(&self) -> RpcClient {
-        RpcClient::new(self.rpc_url.to_string())
+        self.new_rpc_client(&self.rpc_url)
+    }
+    /// returns an rpc client for the url using the configured commitment. invalid commitments
+    /// are rejected by `validate`, so fall back to the default commitment here
+    fn new_rpc_client(&self, url: &str) -> RpcClient {
+        RpcClient::new_with_commitment(
+            url.to_string(),
+            self.try_rpc_commitment().unwrap_or_default(),
+        )
+    }
+    /// parses `rpc_commitment`, returning an error if it isn't a known commitment level
+    pub fn try_rpc_commitment(&self) -> Result<CommitmentConfig> {
+        match self.rpc_commitment.as_str() {
+            "processed" => Ok(CommitmentConfig::processed()),
+            "confirmed" => Ok(CommitmentConfig::confirmed()),
+            "finalized" => Ok(CommitmentConfig::finalized()),
+            commitment => Err(anyhow!(
+                "rpc_commitment '{}' must be one of processed, confirmed, finalized",
+                commitment
+            )),
+        }
+    }
+    /// parses `log_level`, returning an error if it isn't a known level. `debug_log` takes
+    /// precedence, so configurations which only set it continue to log at the debug level
+    pub fn try_log_level(&self) -> Result<LevelFilter> {
+        if self.debug_log {
+            return Ok(LevelFilter::Debug);
+        }
+        match self.log_level.as_str() {
+            "trace" => Ok(LevelFilter::Trace),
+            "debug" => Ok(LevelFilter::Debug),
+            "info" => Ok(LevelFilter::Info),
+            "warn" => Ok(LevelFilter::Warn),
+            "error" => Ok(LevelFilter::Error),
+            level => Err(anyhow!(
+                "log_level '{}' must be one of trace, debug, info, warn, error",
+                level
+            )),
+        }
+    }
+    /// returns an rpc client for the given realm, using the realm's rpc_url override
+    /// if set, otherwise falling back to the global rpc_url
+    pub fn realm_rpc_client(&self, realm: &RealmsConfig) -> RpcClient {
+        match realm.rpc_url.as_ref() {
+            Some(rpc_url) if !rpc_url.is_empty() => self.new_rpc_client(rpc_url),
+            _ => self.rpc_client(),
+        }
+    }
+    /// returns the rpc client used for get_program_accounts scans, falling back
+    /// to the realm's rpc client when `gpa_rpc_url` is not set
+    pub fn gpa_rpc_client(&self, realm: &RealmsConfig) -> RpcClient {
+        match self.gpa_rpc_url.as_ref() {
+            Some(gpa_rpc_url) if !gpa_rpc_url.is_empty() => self.new_rpc_client(gpa_rpc_url),
+            _ => self.realm_rpc_client(realm),
+        }
+    }
+    /// returns a copy of the configuration which only tracks `realm`, with the realm's channel
+    /// and ui overrides applied to `discord`. each realm's worker loop runs against such a copy
+    pub fn for_realm(&self, realm: &RealmsConfig) -> Configuration {
+        let mut config = self.clone();
+        if let Some(channel_id) = realm.channel_id.filter(|channel| *channel != 0) {
+            // the global notification channels belong to the global status channel
+            config.discord.status_channel = channel_id;
+            config.discord.proposal_channel = 0;
+            config.discord.stats_channel = 0;
+        }
+        if let Some(proposal_channel) = realm.proposal_channel.filter(|channel| *channel != 0) {
+            config.discord.proposal_channel = proposal_channel;
+        }
+        if let Some(stats_channel) = realm.stats_channel.filter(|channel| *channel != 0) {
+            config.discord.stats_channel = stats_channel;
+        }
+        match realm.ui_base_url.as_ref() {
+            Some(ui_base_url) if !ui_base_url.is_empty() => {
+                config.discord.ui_base_url = ui_base_url.clone()
+            }
+            _ => (),
+        }
+        config.realms = vec![realm.clone()];
+        config
+    }
+    /// returns the channel the realm's messages are posted to, which is the realm's `channel_id`
+    /// if set, otherwise the global `status_channel`. returns an error if neither is set
+    pub fn realm_channel_id(&self, realm: &RealmsConfig) -> Result<u64> {
+        match realm.channel_id {
+            Some(channel_id) if channel_id != 0 => Ok(channel_id),
+            _ if self.discord.status_channel != 0 => Ok(self.discord.status_channel),
+            _ => Err(anyhow!(
+                "status_channel must be set when realm {} has no channel_id",
+                realm.realm_key
+            )),
+        }
+    }
+    /// returns the tracked realm the governance belongs to, falling back to the first
+    /// tracked realm for governances which aren't tracked
+    ///
+    /// panics if no realms are configured, which `validate` rejects
+    pub fn governance_realm(&self, governance_key: &Pubkey) -> &RealmsConfig {
+        self.realms
+            .iter()
+            .find(|realm| {
+                realm
+                    .try_governance_keys()
+                    .map_or(false, |keys| keys.contains(governance_key))
+            })
+            .unwrap_or(&self.realms[0])
+    }
+    /// returns the tracked realm the proposal belongs to, using the governance of the cached
+    /// proposal when available, and otherwise fetching the proposal with the global rpc client.
+    /// returns an error if no realms are configured
+    pub fn proposal_realm(
+        &self,
+        db: &tulip_realms_sdk::Database,
+        proposal_key: Pubkey,
+    ) -> Result<&RealmsConfig> {
+        if self.realms.is_empty() {
+            return Err(anyhow!("at least one realm must be configured"));
+        }
+        let governance_key = match db.get_proposal(proposal_key) {
+            Ok(cached) => cached.proposal.governance,
+            Err(_) => {
+                tulip_realms_sdk::utils::get_wrapper(
+                    &self.rpc_client(),
+                    proposal_key,
+                    tulip_realms_sdk::types::get_proposal_wrapper,
+                )?
+                .proposal
+                .governance
+            }
+        };
+        Ok(self.governance_realm(&governance_key))
     }
     pub fn fix(&mut self) {
-        self.realm_info.fix();
+        for realm in self.realms.iter_mut() {
+            realm.migrate();
+            realm.fix();
+        }
+        if let Some(realm) = self.realms.first() {
+            if self.discord.ui_base_url.is_empty() && !realm.realm_key.is_empty() {
+                self.discord.ui_base_url = format!("https://realms.today/dao/{}", realm.realm_key);
+            }
+        }
+        // the shared ui_base_url can only point to one realm
+        if self.realms.len() > 1 {
+            for realm in self.realms.iter_mut() {
+                if realm
+                    .ui_base_url
+                    .as_ref()
+                    .map_or(true, |url| url.is_empty())
+                    && !realm.realm_key.is_empty()
+                {
+                    realm.ui_base_url =
+                        Some(format!("https://realms.today/dao/{}", realm.realm_key));
+                }
+            }
+        }
+    }
+    /// checks the configuration for values which would prevent the bot from running,
+    /// returning an error listing every problem found
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = vec![];
+        if self.realms.is_empty() {
+            problems.push("at least one realm must be configured".to_string());
+        }
+        for (idx, realm) in self.realms.iter().enumerate() {
+            for key_check in [
+                realm.try_realm_key().map(|_| ()),
+                realm.try_council_mint_key().map(|_| ()),
+                realm.try_community_mint_key().map(|_| ()),
+                realm.try_governance_keys().map(|_| ()),
+            ] {
+                match key_check {
+                    // only name the realm when it could be ambiguous
+                    Err(err) if self.realms.len() > 1 => {
+                        problems.push(format!("realms[{}]: {}", idx, err))
+                    }
+                    Err(err) => problems.push(err.to_string()),
+                    Ok(()) => (),
+                }
+            }
+        }
+        if let Err(err) = self.try_rpc_commitment() {
+            problems.push(err.to_string());
+        }
+        if let Err(err) = self.try_log_level() {
+            problems.push(err.to_string());
+        }
+        for realm in self.realms.iter() {
+            if let Err(err) = self.realm_channel_id(realm) {
+                problems.push(err.to_string());
+            }
+        }
+        if self.discord.bot_token.is_empty() {
+            problems.push("bot_token must be set".to_string());
+        }
+        if self.discord.worker_loop_frequency == 0 {
+            problems.push("worker_loop_frequency must be greater than 0".to_string());
+        } else if self.discord.worker_loop_jitter_secs >= self.discord.worker_loop_frequency {
+            problems.push(
+                "worker_loop_jitter_secs must be less than worker_loop_frequency".to_string(),
+            );
+        }
+        if self.discord.description_max_chars == 0
+            || self.discord.description_max_chars > MAX_DESCRIPTION_CHARS
+        {
+            problems.push(format!(
+                "description_max_chars must be between 1 and {}",
+                MAX_DESCRIPTION_CHARS
+            ));
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!("invalid configuration: {}", problems.join(", ")))
+    }
+    /// returns a copy of the configuration which is safe to display, with the bot token
+    /// and the query string of rpc urls (commonly used for api keys) redacted
+    pub fn redacted(&self) -> Configuration {
+        let mut config = self.clone();
+        config.discord.bot_token = REDACTED.to_string();
+        config.rpc_url = redact_url(&config.rpc_url);
+        config.gpa_rpc_url = config.gpa_rpc_url.as_deref().map(redact_url);
+        for realm in config.realms.iter_mut() {
+            realm.rpc_url = realm.rpc_url.as_deref().map(redact_url);
+        }
+        // webhook urls typically embed their credentials in the path
+        for sink in config.notification_sinks.iter_mut() {
+            match sink {
+                NotificationSinkConfig::Webhook { url }
+                | NotificationSinkConfig::DiscordWebhook { url } => *url = REDACTED.to_string(),
+            }
+        }
+        config
+    }
+    /// opens the log file for appending, rotating it once it grows beyond `log_max_size_mb`
+    fn log_file(&self) -> Result<rotating_file::RotatingFile> {
+        rotating_file::RotatingFile::new(
+            &self.log_file,
+            self.log_max_size_mb * 1024 * 1024,
+            self.log_keep_files,
+        )
+        .with_context(|| format!("failed to open log file {}", self.log_file))
     }
     /// if file_log is true, log to both file and stdout
     /// otherwise just log to stdout
     pub fn init_log(&self, file_log: bool) -> Result<()> {
-        if !file_log {
-            if self.debug_log {
-                TermLogger::init(
-                    LevelFilter::Debug,
-                    ConfigBuilder::new()
-                        .set_location_level(LevelFilter::Debug)
-                        .build(),
-                    TerminalMode::Mixed,
-                    ColorChoice::Auto,
-                )?;
-                return Ok(());
+        let level = self.try_log_level()?;
+        if self.json_logs {
+            let file = if file_log {
+                Some(self.log_file()?)
             } else {
-                TermLogger::init(
-                    LevelFilter::Info,
-                    ConfigBuilder::new()
-                        .set_location_level(LevelFilter::Error)
-                        .build(),
-                    TerminalMode::Mixed,
-                    ColorChoice::Auto,
-                )?;
-                return Ok(());
-            }
+                None
+            };
+            return json_log::JsonLogger::init(level, file);
         }
-        if self.debug_log {
-            CombinedLogger::init(vec![
-                TermLogger::new(
-                    LevelFilter::Debug,
-                    ConfigBuilder::new()
-                        .set_location_level(LevelFilter::Debug)
-                        .build(),
-                    TerminalMode::Mixed,
-                    ColorChoice::Auto,
-                ),
-                WriteLogger::new(
-                    LevelFilter::Debug,
-                    ConfigBuilder::new()
-                        .set_location_level(LevelFilter::Debug)
-                        .build(),
-                    File::create(self.log_file.as_str()).unwrap(),
-                ),
-            ])?;
+        // source locations are only included when debugging
+        let location_level = if level >= LevelFilter::Debug {
+            LevelFilter::Debug
         } else {
-            CombinedLogger::init(vec![
-                TermLogger::new(
-                    LevelFilter::Info,
-                    ConfigBuilder::new()
-                        .set_location_level(LevelFilter::Error)
-                        .build(),
-                    TerminalMode::Mixed,
-                    ColorChoice::Auto,
-                ),
-                WriteLogger::new(
-                    LevelFilter::Info,
-                    ConfigBuilder::new()
-                        .set_location_level(LevelFilter::Error)
-                        .build(),
-                    File::create(self.log_file.as_str()).unwrap(),
-                ),
-            ])?;
+            LevelFilter::Error
+        };
+        let log_config = ConfigBuilder::new()
+            .set_location_level(location_level)
+            .build();
+        if !file_log {
+            TermLogger::init(level, log_config, TerminalMode::Mixed, ColorChoice::Auto)?;
+            return Ok(());
         }
+        CombinedLogger::init(vec![
+            TermLogger::new(
+                level,
+                log_config.clone(),
+                TerminalMode::Mixed,
+                ColorChoice::Auto,
+            ),
+            WriteLogger::new(level, log_config, self.log_file()?),
+        ])?;
 
         Ok(())
     }
 }
 
+const REDACTED: &str = "<redacted>";
+
+fn redact_url(url: &str) -> String {
+    match url.split_once('?') {
+        Some((base, _)) => format!("{}?{}", base, REDACTED),
+        None => url.to_string(),
+    }
+}
+
 impl Default for Configuration {
     fn default() -> Self {
         Configuration {
             discord: Discord {
                 bot_token: "".to_string(),
                 worker_loop_frequency: 600,
+                worker_loop_jitter_secs: 0,
                 status_channel: 0,
-                ui_base_url: "".to_string(),
-                notification_frequency: 0,
+                proposal_channel: 0,
+                stats_channel: 0,
+                notify_role_id: 0,
+                ui_base_url: "https://realms.today/dao/<realm-id>".to_string(),
+                notification_frequency: default_notification_frequency(),
+                final_reminder_hours: 0,
+                use_threads: false,
+                announce_startup: false,
+                execution_alerts: true,
+                command_roles: vec![],
+                mint_load_attempts: default_mint_load_attempts(),
+                resolve_descriptions: false,
+                description_max_chars: default_description_max_chars(),
+                show_participation_rate: false,
+                dry_run: false,
             },
+            file_log: false,
             log_file: "template.log".to_string(),
+            log_max_size_mb: default_log_max_size_mb(),
+            log_keep_files: default_log_keep_files(),
             debug_log: false,
+            log_level: default_log_level(),
+            json_logs: false,
             rpc_url: "https://solana-api.projectserum.com".to_string(),
+            rpc_commitment: default_rpc_commitment(),
+            gpa_rpc_url: None,
+            metrics_port: None,
+            health_port: None,
+            notification_sinks: vec![],
             db_opts: Default::default(),
-            realm_info: Default::default(),
+            max_db_size_mb: 0,
+            realms: vec![Default::default()],
+        }
+    }
+}
+
+impl Discord {
+    /// returns the channel new proposal notifications are posted to
+    pub fn proposal_channel_id(&self) -> u64 {
+        if self.proposal_channel == 0 {
+            self.status_channel
+        } else {
+            self.proposal_channel
+        }
+    }
+    /// returns the channel voting stats updates are posted to
+    pub fn stats_channel_id(&self) -> u64 {
+        if self.stats_channel == 0 {
+            self.status_channel
+        } else {
+            self.stats_channel
         }
     }
 }
 
 impl RealmsConfig {
+    /// panics if `realm_key` is invalid, use `try_realm_key` to handle the error
     pub fn realm_key(&self) -> Pubkey {
-        Pubkey::from_str(&self.realm_key).unwrap()
+        self.try_realm_key().unwrap()
     }
+    /// panics if `council_mint_key` is invalid, use `try_council_mint_key` to handle the error
     pub fn council_mint_key(&self) -> Pubkey {
-        Pubkey::from_str(&self.council_mint_key).unwrap()
+        self.try_council_mint_key().unwrap()
     }
+    /// panics if `community_mint_key` is invalid, use `try_community_mint_key` to handle the error
     pub fn community_mint_key(&self) -> Pubkey {
-        Pubkey::from_str(&self.community_mint_key).unwrap()
+        self.try_community_mint_key().unwrap()
+    }
+    /// returns the mints whose holders may create proposals, community mint first as most
+    /// proposals are created with it
+    ///
+    /// panics if either mint key is invalid
+    pub fn governing_token_mints(&self) -> Vec<Pubkey> {
+        vec![self.community_mint_key(), self.council_mint_key()]
+    }
+    /// returns the tracked governance keys, including the deprecated `governance_key`
+    /// so that configurations which have not been migrated continue to work.
+    ///
+    /// panics if any key is invalid, use `try_governance_keys` to handle the error
+    pub fn governance_keys(&self) -> Vec<Pubkey> {
+        self.try_governance_keys().unwrap()
     }
-    pub fn governance_key(&self) -> Pubkey {
-        Pubkey::from_str(&self.governance_key).unwrap()
+    pub fn try_realm_key(&self) -> Result<Pubkey> {
+        parse_pubkey("realm_key", &self.realm_key)
+    }
+    pub fn try_council_mint_key(&self) -> Result<Pubkey> {
+        parse_pubkey("council_mint_key", &self.council_mint_key)
+    }
+    pub fn try_community_mint_key(&self) -> Result<Pubkey> {
+        parse_pubkey("community_mint_key", &self.community_mint_key)
+    }
+    pub fn try_governance_keys(&self) -> Result<Vec<Pubkey>> {
+        let mut governance_keys = self
+            .governance_keys
+            .iter()
+            .map(|governance_key| parse_pubkey("governance_keys", governance_key))
+            .collect::<Result<Vec<Pubkey>>>()?;
+        if !self.governance_key.is_empty() {
+            let governance_key = parse_pubkey("governance_key", &self.governance_key)?;
+            if !governance_keys.contains(&governance_key) {
+                governance_keys.push(governance_key);
+            }
+        }
+        Ok(governance_keys)
+    }
+    /// checks that every configured key is a valid pubkey, returning an error
+    /// naming the first invalid field
+    pub fn check_keys(&self) -> Result<()> {
+        self.try_realm_key()?;
+        self.try_council_mint_key()?;
+        self.try_community_mint_key()?;
+        self.try_governance_keys()?;
+        Ok(())
+    }
+    /// upgrades a configuration tracking a single governance with `governance_key`,
+    /// moving the key into `governance_keys`. returns true if the configuration was changed
+    pub fn migrate(&mut self) -> bool {
+        if self.governance_key.is_empty() {
+            return false;
+        }
+        let governance_key = std::mem::take(&mut self.governance_key);
+        if !self.governance_keys.contains(&governance_key) {
+            self.governance_keys.push(governance_key);
+        }
+        true
     }
-    // attempts to "fix" the configuration by populating the governance address
+    // attempts to "fix" the configuration by populating the mint governance address
+    // when no governances are tracked
     pub fn fix(&mut self) {
-        if !self.realm_key.is_empty() && !self.council_mint_key.is_empty() {
-            self.governance_key =
+        if !self.governance_keys.is_empty() {
+            return;
+        }
+        if let (Ok(realm_key), Ok(council_mint_key)) =
+            (self.try_realm_key(), self.try_council_mint_key())
+        {
+            self.governance_keys.push(
                 tulip_realms_sdk::spl_governance::state::governance::get_mint_governance_address(
                     &tulip_realms_sdk::GOVERNANCE_PROGRAM,
-                    &self.realm_key(),
-                    &self.council_mint_key(),
+                    &realm_key,
+                    &council_mint_key,
                 )
-                .to_string();
+                .to_string(),
+            );
         }
     }
 }
 
+fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).with_context(|| format!("{} '{}' is not a valid pubkey", field, value))
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+    #[test]
+    fn test_load_auto() {
+        let config = Configuration::default();
+        config.save("config_test_load_auto.json", true).unwrap();
+        config.save("config_test_load_auto.yaml", false).unwrap();
+        let from_json = Configuration::load_auto("config_test_load_auto.json").unwrap();
+        let from_yaml = Configuration::load_auto("config_test_load_auto.yaml").unwrap();
+        assert_eq!(from_json.rpc_url, config.rpc_url);
+        assert_eq!(from_yaml.rpc_url, config.rpc_url);
+        std::fs::remove_file("config_test_load_auto.json").unwrap();
+        std::fs::remove_file("config_test_load_auto.yaml").unwrap();
+    }
+    #[test]
+    fn test_discord_round_trip() {
+        let mut config = Configuration::default();
+        config.discord.ui_base_url = "https://govern.solend.fi/dao/SLND".to_string();
+        config.discord.notification_frequency = 12;
+        for path in ["config_test_round_trip.json", "config_test_round_trip.yaml"] {
+            config.save(path, path.ends_with(".json")).unwrap();
+            let loaded = Configuration::load_auto(path).unwrap();
+            assert_eq!(loaded.discord.ui_base_url, config.discord.ui_base_url);
+            assert_eq!(loaded.discord.notification_frequency, 12);
+            std::fs::remove_file(path).unwrap();
+        }
+        // configurations omitting the fields fall back to the defaults
+        let mut discord = serde_json::to_value(&config.discord).unwrap();
+        discord.as_object_mut().unwrap().remove("ui_base_url");
+        discord
+            .as_object_mut()
+            .unwrap()
+            .remove("notification_frequency");
+        let discord: Discord = serde_json::from_value(discord).unwrap();
+        assert!(discord.ui_base_url.is_empty());
+        assert_eq!(discord.notification_frequency, 6);
+    }
+    #[test]
+    fn test_discord_channel_fallback() {
+        let mut discord = serde_json::to_value(&Configuration::default().discord).unwrap();
+        discord["status_channel"] = 1.into();
+        discord.as_object_mut().unwrap().remove("proposal_channel");
+        discord.as_object_mut().unwrap().remove("stats_channel");
+        let legacy: Discord = serde_json::from_value(discord.clone()).unwrap();
+        assert_eq!(legacy.proposal_channel_id(), 1);
+        assert_eq!(legacy.stats_channel_id(), 1);
+
+        discord["proposal_channel"] = 2.into();
+        discord["stats_channel"] = 3.into();
+        let discord: Discord = serde_json::from_value(discord).unwrap();
+        assert_eq!(discord.proposal_channel_id(), 2);
+        assert_eq!(discord.stats_channel_id(), 3);
+    }
+    #[test]
+    fn test_redacted() {
+        let mut config = Configuration::default();
+        config.discord.bot_token = "secret-token".to_string();
+        config.rpc_url = "https://rpc.example.com/?api-key=secret".to_string();
+        config
+            .notification_sinks
+            .push(NotificationSinkConfig::DiscordWebhook {
+                url: "https://discord.com/api/webhooks/1/secret".to_string(),
+            });
+        let redacted = serde_yaml::to_string(&config.redacted()).unwrap();
+        assert!(!redacted.contains("secret"));
+        assert!(redacted.contains("https://rpc.example.com/?<redacted>"));
+    }
+    #[test]
+    fn test_migrate_governance_key() {
+        let governance_key = Pubkey::new_unique();
+        std::fs::write(
+            "config_test_migrate.json",
+            serde_json::json!({
+                "discord": Configuration::default().discord,
+                "log_file": "template.log",
+                "debug_log": false,
+                "rpc_url": "https://api.mainnet-beta.solana.com",
+                "db_opts": tulip_sled_util::config::DbOpts::default(),
+                "realm_info": {
+                    "realm_key": "",
+                    "council_mint_key": "",
+                    "community_mint_key": "",
+                    "governance_key": governance_key.to_string(),
+                },
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let mut config = Configuration::load_auto("config_test_migrate.json").unwrap();
+        // file logging is opt-in for configurations which predate it
+        assert!(!config.file_log);
+        // unmigrated configurations still track the single governance
+        assert_eq!(config.realms[0].governance_keys(), vec![governance_key]);
+        assert!(config.realms[0].migrate());
+        assert!(config.realms[0].governance_key.is_empty());
+        assert_eq!(
+            config.realms[0].governance_keys,
+            vec![governance_key.to_string()]
+        );
+        assert_eq!(config.realms[0].governance_keys(), vec![governance_key]);
+        assert!(!config.realms[0].migrate());
+        std::fs::remove_file("config_test_migrate.json").unwrap();
+    }
+    #[test]
+    fn test_multiple_realms() {
+        let mut config = serde_json::to_value(&Configuration::default()).unwrap();
+        let realm = |key: &Pubkey| {
+            serde_json::json!({
+                "realm_key": key.to_string(),
+                "council_mint_key": key.to_string(),
+                "community_mint_key": key.to_string(),
+                "governance_keys": [key.to_string()],
+            })
+        };
+        let (first_key, second_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // configurations created before multiple realms were supported describe a single realm
+        config.as_object_mut().unwrap().remove("realms");
+        config["realm_info"] = realm(&first_key);
+        let single: Configuration = serde_json::from_value(config.clone()).unwrap();
+        assert_eq!(single.realms.len(), 1);
+        assert_eq!(single.realms[0].realm_key(), first_key);
+        let single: Configuration =
+            serde_yaml::from_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(single.realms.len(), 1);
+        // and are saved in the new shape
+        assert!(serde_json::to_value(&single).unwrap()["realms"].is_array());
+
+        config.as_object_mut().unwrap().remove("realm_info");
+        config["realms"] = serde_json::json!([realm(&first_key), realm(&second_key)]);
+        config["realms"][1]["stats_channel"] = 5.into();
+        config["realms"][1]["ui_base_url"] = "https://example.com/dao/second".into();
+        let mut many: Configuration =
+            serde_yaml::from_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(many.realms.len(), 2);
+        assert_eq!(many.realms[1].realm_key(), second_key);
+        assert!(many.realms[0].stats_channel.is_none());
+
+        many.discord.status_channel = 1;
+        many.discord.proposal_channel = 2;
+        let second = many.for_realm(&many.realms[1]);
+        assert_eq!(second.realms.len(), 1);
+        assert_eq!(second.realms[0].realm_key(), second_key);
+        assert_eq!(second.discord.proposal_channel_id(), 2);
+        assert_eq!(second.discord.stats_channel_id(), 5);
+        assert_eq!(second.discord.ui_base_url, "https://example.com/dao/second");
+        assert_eq!(
+            many.for_realm(&many.realms[0]).discord.stats_channel_id(),
+            1
+        );
+
+        assert_eq!(many.governance_realm(&second_key).realm_key(), second_key);
+        assert_eq!(
+            many.governance_realm(&Pubkey::new_unique()).realm_key(),
+            first_key
+        );
+
+        many.discord.ui_base_url = String::new();
+        many.fix();
+        assert_eq!(
+            many.discord.ui_base_url,
+            format!("https://realms.today/dao/{}", first_key)
+        );
+        assert_eq!(
+            many.realms[0].ui_base_url,
+            Some(format!("https://realms.today/dao/{}", first_key))
+        );
+        // overrides are left untouched
+        assert_eq!(
+            many.realms[1].ui_base_url.as_deref(),
+            Some("https://example.com/dao/second")
+        );
+    }
+    #[test]
+    fn test_proposal_realm() {
+        let db = tulip_realms_sdk::Database::new(tulip_sled_util::config::DbOpts {
+            path: "config_test_proposal_realm.db".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut config = Configuration::default();
+        config.realms.clear();
+        assert_eq!(
+            config
+                .proposal_realm(&db, Pubkey::new_unique())
+                .unwrap_err()
+                .to_string(),
+            "at least one realm must be configured"
+        );
+        drop(db);
+        std::fs::remove_dir_all("config_test_proposal_realm.db").unwrap();
+    }
+    #[test]
+    fn test_realm_channel_id() {
+        let mut config = valid_config();
+        config.discord.status_channel = 1;
+        config.discord.proposal_channel = 2;
+        let mut realm = config.realms[0].clone();
+        // without an override the global status channel is used
+        assert_eq!(config.realm_channel_id(&realm).unwrap(), 1);
+        assert_eq!(config.for_realm(&realm).discord.proposal_channel_id(), 2);
+
+        // the realm's channel overrides the global channels
+        realm.channel_id = Some(3);
+        assert_eq!(config.realm_channel_id(&realm).unwrap(), 3);
+        let realm_config = config.for_realm(&realm);
+        assert_eq!(realm_config.discord.status_channel, 3);
+        assert_eq!(realm_config.discord.proposal_channel_id(), 3);
+        assert_eq!(realm_config.discord.stats_channel_id(), 3);
+        // while the realm's notification channels override its channel
+        realm.stats_channel = Some(4);
+        assert_eq!(config.for_realm(&realm).discord.stats_channel_id(), 4);
+
+        // a realm with a channel doesn't need the global status channel
+        config.discord.status_channel = 0;
+        assert_eq!(config.realm_channel_id(&realm).unwrap(), 3);
+        config.realms[0].channel_id = Some(3);
+        assert!(config.validate().is_ok());
+
+        // neither channel is set
+        realm.channel_id = None;
+        let err = config.realm_channel_id(&realm).unwrap_err().to_string();
+        assert!(err.contains("status_channel must be set when realm"));
+        config.realms[0].channel_id = Some(0);
+        assert!(config.validate().is_err());
+    }
+    #[test]
+    fn test_json_logs() {
+        let record = log::Record::builder()
+            .args(format_args!("hello"))
+            .level(log::Level::Info)
+            .target("realms")
+            .build();
+        let line: serde_json::Value =
+            serde_json::from_str(&json_log::JsonLogger::format(&record)).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["target"], "realms");
+        assert_eq!(line["message"], "hello");
+        // json messages have their fields merged into the log line
+        let record = log::Record::builder()
+            .args(format_args!(
+                r#"{{"event":"new_proposal","approval_votes":1.5}}"#
+            ))
+            .level(log::Level::Info)
+            .target("notifications")
+            .build();
+        let line: serde_json::Value =
+            serde_json::from_str(&json_log::JsonLogger::format(&record)).unwrap();
+        assert_eq!(line["event"], "new_proposal");
+        assert_eq!(line["approval_votes"], 1.5);
+        assert!(line.get("message").is_none());
+
+        let config = Configuration {
+            json_logs: true,
+            ..Default::default()
+        };
+        config.init_log(false).unwrap();
+        log::info!("json logger initialized");
+    }
+    #[test]
+    fn test_load_errors() {
+        let err = Configuration::load_auto("config_test_missing.yaml").unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("failed to read config file config_test_missing.yaml")
+        );
+        std::fs::write(
+            "config_test_bad_field.yaml",
+            "rpc_url: https://api.mainnet-beta.solana.com\n",
+        )
+        .unwrap();
+        let err = Configuration::load_auto("config_test_bad_field.yaml").unwrap_err();
+        let err = format!("{:#}", err);
+        assert!(err.contains("failed to parse config_test_bad_field.yaml as yaml"));
+        assert!(err.contains("missing field"));
+        std::fs::remove_file("config_test_bad_field.yaml").unwrap();
+    }
+    fn valid_config() -> Configuration {
+        let mut config = Configuration::default();
+        let key = Pubkey::new_unique().to_string();
+        config.realms[0].realm_key = key.clone();
+        config.realms[0].council_mint_key = key.clone();
+        config.realms[0].community_mint_key = key.clone();
+        config.realms[0].governance_keys = vec![key];
+        config.discord.bot_token = "token".to_string();
+        config.discord.status_channel = 1;
+        config
+    }
+    #[test]
+    fn test_validate() {
+        assert!(valid_config().validate().is_ok());
+
+        let mut config = valid_config();
+        config.realms[0].realm_key = "bogus".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("realm_key 'bogus' is not a valid pubkey"));
+
+        let mut config = valid_config();
+        config.realms[0].council_mint_key = "bogus".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("council_mint_key 'bogus' is not a valid pubkey"));
+
+        let mut config = valid_config();
+        config.realms[0].community_mint_key = "bogus".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("community_mint_key 'bogus' is not a valid pubkey"));
+
+        let mut config = valid_config();
+        config.realms[0].governance_keys.push("bogus".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("governance_keys 'bogus' is not a valid pubkey"));
+
+        // problems with one of several realms name the realm
+        let mut config = valid_config();
+        let mut realm = config.realms[0].clone();
+        realm.realm_key = "bogus".to_string();
+        config.realms.push(realm);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("realms[1]: realm_key 'bogus' is not a valid pubkey"));
+        config.realms.clear();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("at least one realm must be configured"));
+
+        let mut config = valid_config();
+        config.discord.status_channel = 0;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("status_channel must be set"));
+
+        let mut config = valid_config();
+        config.discord.bot_token = String::new();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("bot_token must be set"));
+
+        let mut config = valid_config();
+        config.discord.worker_loop_frequency = 0;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("worker_loop_frequency must be greater than 0"));
+
+        let mut config = valid_config();
+        config.discord.worker_loop_jitter_secs = config.discord.worker_loop_frequency;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("worker_loop_jitter_secs must be less than worker_loop_frequency"));
+        config.discord.worker_loop_jitter_secs = config.discord.worker_loop_frequency - 1;
+        assert!(config.validate().is_ok());
+
+        let mut config = valid_config();
+        config.discord.description_max_chars = MAX_DESCRIPTION_CHARS + 1;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("description_max_chars must be between 1 and 1024"));
+        config.discord.description_max_chars = 0;
+        assert!(config.validate().is_err());
+        config.discord.description_max_chars = MAX_DESCRIPTION_CHARS;
+        assert!(config.validate().is_ok());
+
+        let mut config = valid_config();
+        config.rpc_commitment = "singleGossip".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains(
+            "rpc_commitment 'singleGossip' must be one of processed, confirmed, finalized"
+        ));
+        for commitment in ["processed", "confirmed", "finalized"] {
+            config.rpc_commitment = commitment.to_string();
+            assert!(config.validate().is_ok());
+            assert_eq!(
+                config.rpc_client().commitment(),
+                config.try_rpc_commitment().unwrap()
+            );
+        }
+
+        // every problem is reported at once
+        let err = Configuration::default().validate().unwrap_err().to_string();
+        assert!(err.contains("realm_key"));
+        assert!(err.contains("status_channel"));
+        assert!(err.contains("bot_token"));
+    }
+    #[test]
+    fn test_rotating_file() {
+        use std::io::Write;
+        let path = "config_test_rotating.log";
+        let mut file = rotating_file::RotatingFile::new(path, 16, 2).unwrap();
+        let (rotated_1, rotated_2, rotated_3) = (
+            file.rotated_path(1),
+            file.rotated_path(2),
+            file.rotated_path(3),
+        );
+        file.write_all(b"0123456789\n").unwrap();
+        assert!(!rotated_1.exists());
+        // crossing the threshold moves the current file aside and starts a new one
+        file.write_all(b"abcdefghij\n").unwrap();
+        file.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&rotated_1).unwrap(), "0123456789\n");
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "abcdefghij\n");
+        // only log_keep_files rotated files are kept
+        file.write_all(b"klmnopqrst\n").unwrap();
+        file.write_all(b"uvwxyz\n").unwrap();
+        file.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&rotated_1).unwrap(), "klmnopqrst\n");
+        assert_eq!(std::fs::read_to_string(&rotated_2).unwrap(), "abcdefghij\n");
+        assert!(!rotated_3.exists());
+        // reopening appends instead of truncating
+        drop(file);
+        let mut file = rotating_file::RotatingFile::new(path, 16, 2).unwrap();
+        file.write_all(b"!\n").unwrap();
+        file.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "uvwxyz\n!\n");
+        for path in [std::path::PathBuf::from(path), rotated_1, rotated_2] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+    #[test]
+    fn test_log_level() {
+        let mut config = Configuration::default();
+        assert_eq!(config.try_log_level().unwrap(), LevelFilter::Info);
+        for (level, filter) in [
+            ("trace", LevelFilter::Trace),
+            ("debug", LevelFilter::Debug),
+            ("info", LevelFilter::Info),
+            ("warn", LevelFilter::Warn),
+            ("error", LevelFilter::Error),
+        ] {
+            config.log_level = level.to_string();
+            assert_eq!(config.try_log_level().unwrap(), filter);
+        }
+        config.log_level = "verbose".to_string();
+        assert_eq!(
+            config.try_log_level().unwrap_err().to_string(),
+            "log_level 'verbose' must be one of trace, debug, info, warn, error"
+        );
+        // debug_log overrides log_level
+        config.debug_log = true;
+        assert_eq!(config.try_log_level().unwrap(), LevelFilter::Debug);
+        config.log_level = "error".to_string();
+        assert_eq!(config.try_log_level().unwrap(), LevelFilter::Debug);
+    }
+    #[test]
+    fn test_invalid_pubkey() {
+        let mut config = Configuration::default();
+        let key = Pubkey::new_unique().to_string();
+        config.realms[0].realm_key = key.clone();
+        config.realms[0].council_mint_key = key.clone();
+        config.realms[0].community_mint_key = "not-base58-0OIl".to_string();
+        let err = config.realms[0].check_keys().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "community_mint_key 'not-base58-0OIl' is not a valid pubkey"
+        );
+        config.realms[0].community_mint_key = key;
+        assert!(config.realms[0].check_keys().is_ok());
+        config.realms[0].governance_keys.push("bogus".to_string());
+        let err = config.realms[0].try_governance_keys().unwrap_err();
+        assert!(err.to_string().starts_with("governance_keys 'bogus'"));
+    }
 }
@@ -0,0 +1,78 @@
+//! a log file which is rotated once it grows beyond a size threshold, keeping previous files
+//! alongside it as `<path>.1`, `<path>.2`, etc, where `<path>.1` is the most recent
+
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    keep_files: usize,
+}
+
+impl RotatingFile {
+    /// opens the file for appending, creating it if it doesn't exist. the file is rotated once
+    /// writing to it would grow it beyond `max_size` bytes, unless `max_size` is 0
+    pub fn new(path: impl AsRef<Path>, max_size: u64, keep_files: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = open_append(&path).with_context(|| format!("failed to open {:?}", path))?;
+        let size = file
+            .metadata()
+            .with_context(|| format!("failed to read metadata of {:?}", path))?
+            .len();
+        Ok(RotatingFile {
+            path,
+            file,
+            size,
+            max_size,
+            keep_files,
+        })
+    }
+    /// returns the path of the `idx`th most recent rotated file
+    pub fn rotated_path(&self, idx: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", idx));
+        path.into()
+    }
+    /// shifts the rotated files back by one, replacing the oldest, and starts a new file
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.keep_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            for idx in (1..self.keep_files).rev() {
+                let rotated = self.rotated_path(idx);
+                if rotated.exists() {
+                    fs::rename(&rotated, self.rotated_path(idx + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = open_append(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // a write larger than max_size is written to an empty file rather than rotating forever
+        if self.max_size > 0 && self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
@@ -0,0 +1,600 @@
+//! commands which can be invoked by messaging the bot in the status channel
+
+use crate::{ConfigContainer, DatabaseContainer, StatusContainer, WorkerStatus};
+use chrono::prelude::*;
+use config::RealmsConfig;
+use serenity::framework::standard::{
+    macros::{check, command, hook},
+    Args, CommandOptions, CommandResult, DispatchError, Reason,
+};
+use serenity::model::channel::Message;
+use serenity::model::id::ChannelId;
+use serenity::prelude::*;
+use solana_client::rpc_client::RpcClient;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+use tulip_realms_sdk::spl_governance::state::governance::GovernanceV2;
+use tulip_realms_sdk::spl_governance::state::proposal::ProposalV2;
+use tulip_realms_sdk::spl_governance::state::realm::RealmV2;
+use tulip_realms_sdk::tools::account::fetch_and_deserialize;
+use tulip_realms_sdk::types::ProposalV2Wrapper;
+
+/// re-fetches a single proposal from chain, updating the cached entry
+///
+/// usage: ~refresh <proposal>
+#[command]
+#[owners_only]
+#[num_args(1)]
+async fn refresh(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let proposal_key = match Pubkey::from_str(&args.single::<String>()?) {
+        Ok(proposal_key) => proposal_key,
+        Err(err) => {
+            msg.reply(ctx, format!("invalid proposal key: {}", err))
+                .await?;
+            return Ok(());
+        }
+    };
+    let (config, db, status) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<ConfigContainer>().unwrap().clone(),
+            data.get::<DatabaseContainer>().unwrap().clone(),
+            data.get::<StatusContainer>().unwrap().clone(),
+        )
+    };
+    let rpc_client = match config.proposal_realm(&db, proposal_key) {
+        Ok(realm) => config.realm_rpc_client(realm),
+        Err(err) => {
+            msg.reply(
+                ctx,
+                format!("failed to refresh proposal {}: {:#}", proposal_key, err),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    let (previous, proposal) = match db.refresh_proposal(proposal_key, &rpc_client) {
+        Ok(refreshed) => refreshed,
+        Err(err) => {
+            log::error!("failed to refresh proposal {}: {:#?}", proposal_key, err);
+            msg.reply(
+                ctx,
+                format!("failed to refresh proposal {}: {}", proposal_key, err),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    let realm = config.governance_realm(&proposal.proposal.governance);
+    let decimals = community_mint_decimals(&status, realm, &rpc_client);
+    let ui_base_url = config.for_realm(realm).discord.ui_base_url;
+    let summarize = |proposal: &ProposalV2| {
+        let (approval_votes, deny_votes) = proposal_vote_weights(proposal);
+        format!(
+            "state {:?}, approval votes {}, deny votes {}",
+            proposal.state,
+            tulip_realms_sdk::utils::ui_amount(approval_votes, decimals),
+            tulip_realms_sdk::utils::ui_amount(deny_votes, decimals),
+        )
+    };
+    let before = match previous.as_ref() {
+        Some(previous) => summarize(&previous.proposal),
+        None => "not cached".to_string(),
+    };
+    let after = summarize(&proposal.proposal);
+    crate::send_message(ctx, config.discord.dry_run, msg.channel_id, |m| {
+        m.add_embed(|e| {
+            e.title("Proposal Refreshed");
+            e.field(
+                "proposal",
+                format!(
+                    "[{}]({}/proposal/{})",
+                    proposal.key, ui_base_url, proposal.key
+                ),
+                false,
+            );
+            e.field("name", proposal.proposal.name.clone(), false);
+            e.field("before", before, false);
+            e.field("after", after, false);
+            e
+        });
+        m
+    })
+    .await?;
+    Ok(())
+}
+
+/// permits the read-only commands for users holding one of the configured `command_roles`.
+/// bot owners bypass checks, so they are always permitted
+#[check]
+#[name = "CommandRoles"]
+async fn command_roles_check(
+    ctx: &Context,
+    msg: &Message,
+    _: &mut Args,
+    _: &CommandOptions,
+) -> Result<(), Reason> {
+    let config = {
+        let data = ctx.data.read().await;
+        data.get::<ConfigContainer>().unwrap().clone()
+    };
+    if config.discord.command_roles.is_empty() {
+        return Ok(());
+    }
+    let has_role = msg.member.as_ref().map_or(false, |member| {
+        member
+            .roles
+            .iter()
+            .any(|role| config.discord.command_roles.contains(&role.0))
+    });
+    if has_role {
+        Ok(())
+    } else {
+        Err(Reason::User(
+            "you do not have a role permitted to use this command".to_string(),
+        ))
+    }
+}
+
+/// replies to users who are not permitted to use a command, as the framework otherwise
+/// ignores them silently
+#[hook]
+pub async fn dispatch_error(
+    ctx: &Context,
+    msg: &Message,
+    error: DispatchError,
+    command_name: &str,
+) {
+    let reply = match error {
+        DispatchError::CheckFailed(_, Reason::User(reason)) => reason,
+        DispatchError::OnlyForOwners => {
+            "this command may only be used by the bot owners".to_string()
+        }
+        _ => return,
+    };
+    log::warn!("{} denied ~{}: {}", msg.author.name, command_name, reply);
+    if let Err(err) = msg.reply(ctx, reply).await {
+        log::error!("failed to send message {:#?}", err);
+    }
+}
+
+/// replies with the status of the worker loop
+#[command]
+#[checks(CommandRoles)]
+async fn status(ctx: &Context, msg: &Message) -> CommandResult {
+    let (config, status, db) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<ConfigContainer>().unwrap().clone(),
+            data.get::<StatusContainer>().unwrap().clone(),
+            data.get::<DatabaseContainer>().unwrap().clone(),
+        )
+    };
+    let last_slot = status.last_slot.load(Ordering::SeqCst);
+    let rpc_status = if last_slot == 0 {
+        "unknown"
+    } else if status.rpc_lagging.load(Ordering::SeqCst) {
+        "lagging"
+    } else {
+        "healthy"
+    };
+    let db_size = match db.size_on_disk() {
+        Ok(size) => format!("{:.2}mb", size as f64 / 1024.0 / 1024.0),
+        Err(err) => {
+            log::error!("failed to get database size {:#?}", err);
+            "unknown".to_string()
+        }
+    };
+    crate::send_message(ctx, config.discord.dry_run, msg.channel_id, |m| {
+        m.add_embed(|e| {
+            e.title("Bot Status");
+            e.field("current slot", last_slot.to_string(), false);
+            e.field("rpc status", rpc_status, false);
+            e.field("database size", db_size, false);
+            e
+        });
+        m
+    })
+    .await?;
+    Ok(())
+}
+
+/// replies with the effective configuration of the running bot, with secrets redacted
+#[command]
+#[owners_only]
+async fn config(ctx: &Context, msg: &Message) -> CommandResult {
+    let config = {
+        let data = ctx.data.read().await;
+        data.get::<ConfigContainer>().unwrap().clone()
+    };
+    let config = serde_yaml::to_string(&config.redacted())?;
+    // stay within discord's message length limit, accounting for the code block
+    let config: String = config.chars().take(1900).collect();
+    msg.reply(ctx, format!("```yaml\n{}\n```", config)).await?;
+    Ok(())
+}
+
+/// verifies the bot can reach the rpc node, load the configured realm and governances,
+/// write to the database, and post to the status channel, replying with the result of each check
+#[command]
+#[owners_only]
+async fn selftest(ctx: &Context, msg: &Message) -> CommandResult {
+    let (config, db) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<ConfigContainer>().unwrap().clone(),
+            data.get::<DatabaseContainer>().unwrap().clone(),
+        )
+    };
+    let mut results: Vec<(&str, Result<String, String>)> = vec![];
+    for realm in config.realms.iter() {
+        let rpc_client = config.realm_rpc_client(realm);
+        results.push((
+            "rpc reachable",
+            rpc_client
+                .get_slot()
+                .map(|slot| format!("slot {}", slot))
+                .map_err(|err| err.to_string()),
+        ));
+        let realm_key = realm.realm_key();
+        results.push((
+            "realm loads",
+            fetch_and_deserialize::<RealmV2>(&rpc_client, realm_key)
+                .map(|realm| realm.name)
+                .map_err(|err| format!("{:#}", err)),
+        ));
+        for governance_key in realm.governance_keys() {
+            results.push((
+                "governance loads",
+                fetch_and_deserialize::<GovernanceV2>(&rpc_client, governance_key)
+                    .map(|governance| {
+                        format!(
+                            "{}: {} proposals",
+                            governance_key, governance.proposals_count
+                        )
+                    })
+                    .map_err(|err| format!("{:#}", err)),
+            ));
+        }
+    }
+    results.push((
+        "database writable",
+        db.check_writable()
+            .map(|_| "ok".to_string())
+            .map_err(|err| err.to_string()),
+    ));
+    // each realm may post to its own channel
+    let mut channels: Vec<u64> = config
+        .realms
+        .iter()
+        .filter_map(|realm| config.realm_channel_id(realm).ok())
+        .collect();
+    channels.sort_unstable();
+    channels.dedup();
+    for channel in channels {
+        results.push((
+            "status channel postable",
+            crate::send_message(ctx, config.discord.dry_run, ChannelId(channel), |m| {
+                m.content("selftest: verifying the status channel is postable")
+            })
+            .await
+            .map(|sent| match sent {
+                Some(_) => channel.to_string(),
+                // nothing is posted in dry run mode, so the channel can't be verified
+                None => format!("{}: skipped, dry run is enabled", channel),
+            })
+            .map_err(|err| format!("{}: {}", channel, err)),
+        ));
+    }
+    let mut reply = String::from("selftest results\n");
+    for (check, result) in results {
+        match result {
+            Ok(details) => reply.push_str(&format!("pass: {} ({})\n", check, details)),
+            Err(err) => reply.push_str(&format!("fail: {} ({})\n", check, err)),
+        }
+    }
+    msg.reply(ctx, reply).await?;
+    Ok(())
+}
+
+/// the default, and maximum number of proposals listed by ~results
+const DEFAULT_RESULTS: usize = 5;
+const MAX_RESULTS: usize = 10;
+
+/// lists the most recently decided proposals along with their outcome
+///
+/// usage: ~results [n]
+#[command]
+#[checks(CommandRoles)]
+#[max_args(1)]
+async fn results(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let limit = if args.is_empty() {
+        DEFAULT_RESULTS
+    } else {
+        match args.single::<usize>() {
+            Ok(limit) => limit.min(MAX_RESULTS),
+            Err(_) => {
+                msg.reply(ctx, "usage: ~results [n]").await?;
+                return Ok(());
+            }
+        }
+    };
+    let (config, db, status) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<ConfigContainer>().unwrap().clone(),
+            data.get::<DatabaseContainer>().unwrap().clone(),
+            data.get::<StatusContainer>().unwrap().clone(),
+        )
+    };
+    let proposals = match db.list_decided_proposals(limit) {
+        Ok(proposals) => proposals,
+        Err(err) => {
+            log::error!("failed to list decided proposals {:#?}", err);
+            msg.reply(ctx, "failed to list decided proposals").await?;
+            return Ok(());
+        }
+    };
+    if proposals.is_empty() {
+        msg.reply(ctx, "no decided proposals found").await?;
+        return Ok(());
+    }
+    // the proposals may belong to any of the tracked realms, so look up the mint
+    // decimals and ui url of each realm once
+    let mut realm_displays: HashMap<String, (u8, String)> = HashMap::new();
+    let displays: Vec<(u8, String)> = proposals
+        .iter()
+        .map(|proposal| {
+            let realm = config.governance_realm(&proposal.proposal.governance);
+            realm_displays
+                .entry(realm.realm_key.clone())
+                .or_insert_with(|| {
+                    let decimals =
+                        community_mint_decimals(&status, realm, &config.realm_rpc_client(realm));
+                    (decimals, config.for_realm(realm).discord.ui_base_url)
+                })
+                .clone()
+        })
+        .collect();
+    crate::send_message(ctx, config.discord.dry_run, msg.channel_id, |m| {
+        m.add_embed(|e| {
+            e.title("Recent Proposal Results");
+            for (proposal, (decimals, ui_base_url)) in proposals.iter().zip(displays.iter()) {
+                let decimals = *decimals;
+                let (approval_votes, deny_votes) = proposal_vote_weights(&proposal.proposal);
+                let mut result = format!(
+                    "[{}]({}/proposal/{})\napproval votes {}, deny votes {}",
+                    proposal.key,
+                    ui_base_url,
+                    proposal.key,
+                    tulip_realms_sdk::utils::ui_amount(approval_votes, decimals),
+                    tulip_realms_sdk::utils::ui_amount(deny_votes, decimals),
+                );
+                // the final tally is only available if the bot observed the proposal voting
+                if let Some(final_tally) = db
+                    .get_proposal_tally(proposal.key)
+                    .ok()
+                    .and_then(|tally_entry| tally_entry.final_tally)
+                {
+                    result.push_str(&format!(", {} voters", final_tally.voter_count));
+                }
+                e.field(
+                    format!("{} ({:?})", proposal.proposal.name, proposal.proposal.state),
+                    result,
+                    false,
+                );
+            }
+            e
+        });
+        m
+    })
+    .await?;
+    Ok(())
+}
+
+/// the number of proposals listed per embed by ~proposals, and the maximum listed overall
+const PROPOSALS_PER_EMBED: usize = 10;
+const MAX_PROPOSALS: usize = 30;
+
+/// lists the proposals which are currently accepting votes, soonest deadline first.
+/// each embed is sent as its own message to stay within discord's embed size limits
+///
+/// usage: ~proposals
+#[command]
+#[checks(CommandRoles)]
+async fn proposals(ctx: &Context, msg: &Message) -> CommandResult {
+    let (config, db) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<ConfigContainer>().unwrap().clone(),
+            data.get::<DatabaseContainer>().unwrap().clone(),
+        )
+    };
+    let now = Utc::now();
+    let proposals = match db.list_voting_proposals(now) {
+        Ok(proposals) => proposals,
+        Err(err) => {
+            log::error!("failed to list voting proposals {:#?}", err);
+            msg.reply(ctx, "failed to list voting proposals").await?;
+            return Ok(());
+        }
+    };
+    if proposals.is_empty() {
+        msg.reply(ctx, "no proposals are currently voting").await?;
+        return Ok(());
+    }
+    let total = proposals.len();
+    let proposals: Vec<_> = proposals
+        .into_iter()
+        .take(MAX_PROPOSALS)
+        .map(|proposal| {
+            let ends_at = db
+                .get_governance(proposal.proposal.governance)
+                .ok()
+                .and_then(|governance| proposal.vote_ends_at(&governance.governance.config));
+            (proposal, ends_at)
+        })
+        .collect();
+    let ui_base_url = |proposal: &ProposalV2Wrapper| {
+        let realm = config.governance_realm(&proposal.proposal.governance);
+        config.for_realm(realm).discord.ui_base_url
+    };
+    let pages = voting_proposal_pages(&proposals, &ui_base_url, now);
+    let page_count = pages.len();
+    for (idx, fields) in pages.into_iter().enumerate() {
+        crate::send_message(ctx, config.discord.dry_run, msg.channel_id, |m| {
+            m.add_embed(|e| {
+                if page_count > 1 {
+                    e.title(format!("Voting Proposals ({}/{})", idx + 1, page_count));
+                } else {
+                    e.title("Voting Proposals");
+                }
+                e.fields(fields.into_iter().map(|(name, value)| (name, value, false)));
+                if idx + 1 == page_count && total > MAX_PROPOSALS {
+                    e.footer(|f| {
+                        f.text(format!(
+                            "showing {} of {} voting proposals",
+                            MAX_PROPOSALS, total
+                        ))
+                    });
+                }
+                e
+            });
+            m
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// returns the (name, value) embed fields listing each voting proposal, split into pages of
+/// `PROPOSALS_PER_EMBED`. the time at which voting ends is paired with each proposal, if known,
+/// and `ui_base_url` returns the ui url of the realm each proposal belongs to
+fn voting_proposal_pages(
+    proposals: &[(ProposalV2Wrapper, Option<DateTime<Utc>>)],
+    ui_base_url: &dyn Fn(&ProposalV2Wrapper) -> String,
+    now: DateTime<Utc>,
+) -> Vec<Vec<(String, String)>> {
+    proposals
+        .chunks(PROPOSALS_PER_EMBED)
+        .map(|page| {
+            page.iter()
+                .map(|(proposal, ends_at)| {
+                    let remaining = match ends_at {
+                        Some(ends_at) => format!(
+                            "ends in {}",
+                            tulip_realms_sdk::utils::humanize_duration(*ends_at - now)
+                        ),
+                        None => "end time unknown".to_string(),
+                    };
+                    (
+                        proposal.proposal.name.clone(),
+                        format!(
+                            "[{}]({}/proposal/{})\n{}",
+                            proposal.key,
+                            ui_base_url(proposal),
+                            proposal.key,
+                            remaining
+                        ),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// returns the decimals of the community mint, used to display vote weights. the decimals
+/// loaded by the worker loop are reused, only fetching the mint if they haven't been loaded
+pub(crate) fn community_mint_decimals(
+    status: &WorkerStatus,
+    realm: &RealmsConfig,
+    rpc_client: &RpcClient,
+) -> u8 {
+    let mint_key = realm.community_mint_key();
+    if let Some(decimals) = status
+        .mint_decimals
+        .read()
+        .ok()
+        .and_then(|mint_decimals| mint_decimals.get(&mint_key).copied())
+    {
+        return decimals;
+    }
+    match rpc_client.get_account(&mint_key) {
+        Ok(mint_account) => {
+            match spl_token::state::Mint::unpack_unchecked(&mint_account.data[..]) {
+                Ok(mint) => {
+                    if let Ok(mut mint_decimals) = status.mint_decimals.write() {
+                        mint_decimals.insert(mint_key, mint.decimals);
+                    }
+                    mint.decimals
+                }
+                Err(err) => {
+                    log::error!("failed to unpack community mint {:#?}", err);
+                    0
+                }
+            }
+        }
+        Err(err) => {
+            log::error!("failed to load community mint {:#?}", err);
+            0
+        }
+    }
+}
+
+/// returns the (approval, deny) vote weights recorded on the proposal account
+pub(crate) fn proposal_vote_weights(proposal: &ProposalV2) -> (u64, u64) {
+    let approval_votes = proposal
+        .options
+        .iter()
+        .map(|option| option.vote_weight)
+        .sum();
+    (approval_votes, proposal.deny_vote_weight.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::zeroed;
+    #[test]
+    fn test_voting_proposal_pages() {
+        let now = tulip_realms_sdk::utils::date_time_from_timestamp(1_650_000_000);
+        let proposals: Vec<(ProposalV2Wrapper, Option<DateTime<Utc>>)> = (0..12)
+            .map(|idx| {
+                let mut proposal = ProposalV2Wrapper {
+                    proposal: zeroed(),
+                    key: Pubkey::new_unique(),
+                };
+                proposal.proposal.name = format!("proposal {}", idx);
+                let ends_at = if idx == 11 {
+                    None
+                } else {
+                    Some(now + chrono::Duration::hours(idx + 1))
+                };
+                (proposal, ends_at)
+            })
+            .collect();
+        let pages = voting_proposal_pages(
+            &proposals,
+            &|_| "https://realms.today/dao/tulip".to_string(),
+            now,
+        );
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].len(), PROPOSALS_PER_EMBED);
+        assert_eq!(pages[1].len(), 2);
+        assert_eq!(pages[0][0].0, "proposal 0");
+        assert_eq!(
+            pages[0][0].1,
+            format!(
+                "[{}](https://realms.today/dao/tulip/proposal/{})\nends in 1h",
+                proposals[0].0.key, proposals[0].0.key
+            )
+        );
+        assert_eq!(pages[1][0].0, "proposal 10");
+        assert!(pages[1][0].1.ends_with("ends in 11h"));
+        assert!(pages[1][1].1.ends_with("end time unknown"));
+        assert!(voting_proposal_pages(&[], &|_| String::new(), now).is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+//! liveness and readiness endpoints, allowing a supervisor such as kubernetes or systemd to
+//! restart instances whose worker loop silently stops making progress
+
+use crate::server::{serve_http, HttpResponse};
+use crate::WorkerStatus;
+use anyhow::Result;
+use chrono::Utc;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// returns true if the worker loop started an iteration within `max_age` seconds of `now`
+pub fn is_ready(last_run_at: i64, now: i64, max_age: i64) -> bool {
+    last_run_at > 0 && now - last_run_at <= max_age
+}
+
+/// serves `/healthz`, which succeeds while the process is up, and `/readyz`, which succeeds
+/// while every realm's worker loop has started an iteration within `max_age` seconds, until
+/// the listener fails
+pub async fn serve(listener: TcpListener, status: Arc<WorkerStatus>, max_age: i64) -> Result<()> {
+    serve_http(listener, move |path| {
+        let (status_line, body) = match path {
+            "/healthz" => ("200 OK", "ok"),
+            "/readyz" if is_ready(status.last_run_at(), Utc::now().timestamp(), max_age) => {
+                ("200 OK", "ready")
+            }
+            "/readyz" => (
+                "503 Service Unavailable",
+                "worker loop is not making progress",
+            ),
+            _ => return HttpResponse::not_found(),
+        };
+        HttpResponse {
+            status: status_line,
+            content_type: "text/plain",
+            body: body.to_string(),
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+    #[tokio::test]
+    async fn test_health_endpoints() {
+        let status = Arc::new(WorkerStatus::new(2));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::task::spawn(serve(listener, status.clone(), 1200));
+
+        assert!(get(addr, "/healthz").await.starts_with("HTTP/1.1 200 OK"));
+        // the worker loop hasn't started
+        assert!(get(addr, "/readyz")
+            .await
+            .starts_with("HTTP/1.1 503 Service Unavailable"));
+
+        for last_run_at in status.realm_last_run_at.iter() {
+            last_run_at.store(Utc::now().timestamp(), Ordering::SeqCst);
+        }
+        assert!(get(addr, "/readyz").await.starts_with("HTTP/1.1 200 OK"));
+
+        // one realm's worker loop stalled, while the other keeps running
+        status.realm_last_run_at[1].store(Utc::now().timestamp() - 1201, Ordering::SeqCst);
+        assert!(get(addr, "/readyz")
+            .await
+            .starts_with("HTTP/1.1 503 Service Unavailable"));
+        // liveness only reflects the process being up
+        assert!(get(addr, "/healthz").await.starts_with("HTTP/1.1 200 OK"));
+
+        assert!(get(addr, "/").await.starts_with("HTTP/1.1 404 Not Found"));
+    }
+    #[test]
+    fn test_is_ready() {
+        assert!(!is_ready(0, 1_000, 100));
+        assert!(is_ready(900, 1_000, 100));
+        assert!(!is_ready(899, 1_000, 100));
+    }
+}
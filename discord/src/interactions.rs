@@ -0,0 +1,219 @@
+//! slash commands, which unlike the `~` prefixed commands can be discovered from the discord ui
+
+use crate::commands::{community_mint_decimals, proposal_vote_weights};
+use crate::{ConfigContainer, DatabaseContainer, StatusContainer};
+use serenity::builder::CreateEmbed;
+use serenity::model::id::UserId;
+use serenity::model::interactions::application_command::{
+    ApplicationCommand, ApplicationCommandInteraction, ApplicationCommandOptionType,
+};
+use serenity::model::interactions::{Interaction, InteractionResponseType};
+use serenity::prelude::*;
+use solana_program::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+use tulip_realms_sdk::types::ProposalV2Wrapper;
+
+/// name of the slash command which replies with the state of a proposal
+pub const PROPOSAL_COMMAND: &str = "proposal";
+
+/// registers the slash commands globally
+pub async fn register_commands(ctx: &Context) -> serenity::Result<()> {
+    ApplicationCommand::create_global_application_command(ctx, |command| {
+        command
+            .name(PROPOSAL_COMMAND)
+            .description("show the state and vote counts of a proposal")
+            .create_option(|option| {
+                option
+                    .name("pubkey")
+                    .description("the proposal account")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .await?;
+    Ok(())
+}
+
+/// dispatches slash command interactions, ignoring other interactions
+pub async fn handle_interaction(ctx: &Context, owners: &HashSet<UserId>, interaction: Interaction) {
+    let command = match interaction {
+        Interaction::ApplicationCommand(command) => command,
+        _ => return,
+    };
+    let reply = match command.data.name.as_str() {
+        PROPOSAL_COMMAND => proposal_command(ctx, owners, &command).await,
+        name => Err(format!("unknown command {}", name)),
+    };
+    let result = command
+        .create_interaction_response(ctx, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|data| match reply {
+                    Ok(embed) => data.add_embed(embed),
+                    Err(err) => data.content(err).ephemeral(true),
+                })
+        })
+        .await;
+    if let Err(err) = result {
+        log::error!(
+            "failed to respond to {} command {:#?}",
+            command.data.name,
+            err
+        );
+    }
+}
+
+/// looks up the proposal in the database, falling back to fetching it from the rpc node,
+/// returning an embed of its state and vote counts
+async fn proposal_command(
+    ctx: &Context,
+    owners: &HashSet<UserId>,
+    command: &ApplicationCommandInteraction,
+) -> Result<CreateEmbed, String> {
+    let (config, db, status) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<ConfigContainer>().unwrap().clone(),
+            data.get::<DatabaseContainer>().unwrap().clone(),
+            data.get::<StatusContainer>().unwrap().clone(),
+        )
+    };
+    // mirror the CommandRoles check applied to the read-only prefix commands
+    if !config.discord.command_roles.is_empty() && !owners.contains(&command.user.id) {
+        let has_role = command.member.as_ref().map_or(false, |member| {
+            member
+                .roles
+                .iter()
+                .any(|role| config.discord.command_roles.contains(&role.0))
+        });
+        if !has_role {
+            return Err("you do not have a role permitted to use this command".to_string());
+        }
+    }
+    let proposal_key = parse_proposal_key(
+        command
+            .data
+            .options
+            .iter()
+            .find(|option| option.name.eq("pubkey"))
+            .and_then(|option| option.value.as_ref()),
+    )?;
+    let proposal = match db.get_proposal(proposal_key) {
+        Ok(proposal) => proposal,
+        // proposals which aren't cached are fetched with the first realm's rpc client
+        Err(_) => tulip_realms_sdk::utils::get_wrapper_with_retry(
+            &config.realm_rpc_client(&config.realms[0]),
+            proposal_key,
+            1,
+            std::time::Duration::from_millis(0),
+            tulip_realms_sdk::types::get_proposal_wrapper,
+        )
+        .await
+        .map_err(|err| {
+            log::error!("failed to fetch proposal {}: {:#?}", proposal_key, err);
+            format!("proposal {} not found", proposal_key)
+        })?,
+    };
+    let realm = config.governance_realm(&proposal.proposal.governance);
+    let decimals = community_mint_decimals(&status, realm, &config.realm_rpc_client(realm));
+    let mut embed = CreateEmbed::default();
+    proposal_embed(
+        &mut embed,
+        &proposal,
+        &config.for_realm(realm).discord.ui_base_url,
+        decimals,
+    );
+    Ok(embed)
+}
+
+/// parses the value of the pubkey option of the proposal command
+fn parse_proposal_key(value: Option<&serde_json::Value>) -> Result<Pubkey, String> {
+    let value = value
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| "a proposal pubkey is required".to_string())?;
+    Pubkey::from_str(value.trim()).map_err(|err| format!("invalid proposal key: {}", err))
+}
+
+/// builds an embed of the proposal's state and vote counts
+fn proposal_embed<'a>(
+    embed: &'a mut CreateEmbed,
+    proposal: &ProposalV2Wrapper,
+    ui_base_url: &str,
+    decimals: u8,
+) -> &'a mut CreateEmbed {
+    let (approval_votes, deny_votes) = proposal_vote_weights(&proposal.proposal);
+    embed.title(&proposal.proposal.name);
+    embed.url(format!("{}/proposal/{}", ui_base_url, proposal.key));
+    embed.field("proposal", proposal.key, false);
+    embed.field("state", format!("{:?}", proposal.proposal.state), false);
+    embed.field(
+        "approval vote count",
+        tulip_realms_sdk::utils::ui_amount(approval_votes, decimals),
+        false,
+    );
+    embed.field(
+        "deny vote count",
+        tulip_realms_sdk::utils::ui_amount(deny_votes, decimals),
+        false,
+    );
+    embed.field("created", proposal.created_at().to_rfc2822(), false);
+    if let Some(voting_at) = proposal.proposal.voting_at {
+        embed.field(
+            "voting started",
+            tulip_realms_sdk::utils::date_time_from_timestamp(voting_at).to_rfc2822(),
+            false,
+        );
+    }
+    embed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::zeroed;
+    use serde_json::json;
+    use tulip_realms_sdk::spl_governance::state::enums::ProposalState;
+    #[test]
+    fn test_parse_proposal_key() {
+        let key = Pubkey::new_unique();
+        assert_eq!(
+            parse_proposal_key(Some(&json!(key.to_string()))).unwrap(),
+            key
+        );
+        assert_eq!(
+            parse_proposal_key(Some(&json!(format!(" {} ", key)))).unwrap(),
+            key
+        );
+        assert!(parse_proposal_key(None).is_err());
+        assert!(parse_proposal_key(Some(&json!(1))).is_err());
+        assert!(parse_proposal_key(Some(&json!("not a pubkey")))
+            .unwrap_err()
+            .starts_with("invalid proposal key"));
+    }
+    #[test]
+    fn test_proposal_embed() {
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        proposal.proposal.name = "raise the fee".to_string();
+        proposal.proposal.state = ProposalState::Voting;
+        proposal.proposal.deny_vote_weight = Some(2_500_000);
+        let mut embed = CreateEmbed::default();
+        proposal_embed(&mut embed, &proposal, "https://realms.today/dao/tulip", 6);
+        assert_eq!(embed.0["title"], "raise the fee");
+        assert_eq!(
+            embed.0["url"],
+            format!("https://realms.today/dao/tulip/proposal/{}", proposal.key)
+        );
+        let fields = embed.0["fields"].as_array().unwrap();
+        assert_eq!(fields[0]["value"], proposal.key.to_string());
+        assert_eq!(fields[1]["value"], "Voting");
+        assert_eq!(fields[2]["value"], "0");
+        assert_eq!(fields[3]["value"], "2.5");
+        assert_eq!(fields[4]["value"], "Thu, 01 Jan 1970 00:00:00 +0000");
+        // voting hasn't started
+        assert_eq!(fields.len(), 5);
+    }
+}
@@ -11,27 +11,49 @@
 
 #![feature(async_closure)]
 
+pub mod commands;
+pub mod health;
+pub mod interactions;
+pub mod metrics;
+pub mod server;
+
 use chrono::prelude::*;
+use commands::*;
 
 use serenity::prelude::*;
 use serenity::utils::MessageBuilder;
-use solana_program::account_info::IntoAccountInfo;
+use solana_client::rpc_client::RpcClient;
 use solana_program::program_pack::Pack;
-use std::sync::atomic::AtomicBool;
-use std::{collections::HashSet, sync::Arc};
-use tulip_realms_sdk::GOVERNANCE_PROGRAM;
+use solana_program::pubkey::Pubkey;
+use spl_governance::state::enums::ProposalState;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tulip_realms_sdk::notify::{
+    DiscordWebhookSink, NotificationSink, ProposalEvent, ProposalEventKind, WebhookSink,
+};
+use tulip_realms_sdk::types::{ProposalNotifState, ProposalStateTransition, ProposalV2Wrapper};
+use tulip_realms_sdk::utils::SlotProgress;
+use tulip_realms_sdk::RealmsError;
 
-use anyhow::Result;
-use config::Configuration;
+use anyhow::{anyhow, Context as _, Result};
+use config::{Configuration, NotificationSinkConfig};
 use crossbeam_channel::select;
 use log::{error, info, warn};
-use serenity::model::id::GuildId;
+use serenity::http::StatusCode;
+use serenity::model::id::{GuildId, UserId};
 use serenity::{
     async_trait,
+    builder::CreateMessage,
     client::bridge::gateway::ShardManager,
     framework::{standard::macros::group, StandardFramework},
     http::Http,
-    model::{event::ResumedEvent, gateway::Ready, id::ChannelId},
+    model::{
+        channel::Message, event::ResumedEvent, gateway::Ready, id::ChannelId,
+        interactions::Interaction,
+    },
 };
 
 pub struct ShardManagerContainer;
@@ -40,15 +62,86 @@ impl TypeMapKey for ShardManagerContainer {
     type Value = Arc<Mutex<ShardManager>>;
 }
 
+/// allows commands to access the bot configuration
+pub struct ConfigContainer;
+
+impl TypeMapKey for ConfigContainer {
+    type Value = Arc<Configuration>;
+}
+
+/// allows commands to access the database shared with the worker loop
+pub struct DatabaseContainer;
+
+impl TypeMapKey for DatabaseContainer {
+    type Value = tulip_realms_sdk::Database;
+}
+
+/// allows commands to access the status of the worker loop
+pub struct StatusContainer;
+
+impl TypeMapKey for StatusContainer {
+    type Value = Arc<WorkerStatus>;
+}
+
+/// runtime status of the worker loop, shared with commands
+#[derive(Default)]
+pub struct WorkerStatus {
+    /// the last slot reported by the rpc node
+    pub last_slot: AtomicU64,
+    /// the unix timestamp at which `last_slot` was observed
+    pub last_slot_at: AtomicI64,
+    /// set when the rpc node appears to be stuck, or behind
+    pub rpc_lagging: AtomicBool,
+    /// set once the bot owners have been notified of missing channel permissions
+    pub permission_alert_sent: AtomicBool,
+    /// the unix timestamp at which each realm's worker loop last started an iteration, in the
+    /// order of `config.realms`
+    pub realm_last_run_at: Vec<AtomicI64>,
+    /// incremented each time the worker loop is spawned, allowing a stalled
+    /// worker loop to detect that it has been replaced by the watchdog
+    pub worker_generation: AtomicU64,
+    pub metrics: metrics::Metrics,
+    /// decimals of the tracked realms' community mints keyed by mint, loaded once by the worker
+    /// loop and reused by commands to display vote weights
+    pub mint_decimals: std::sync::RwLock<HashMap<Pubkey, u8>>,
+    /// the number of worker loops which are running, one per tracked realm
+    pub active_workers: AtomicUsize,
+    /// notified when a worker loop exits after receiving the exit signal
+    pub worker_exited: tokio::sync::Notify,
+}
+
+impl WorkerStatus {
+    /// returns the status of worker loops tracking the given number of realms
+    pub fn new(realms: usize) -> Self {
+        Self {
+            realm_last_run_at: (0..realms).map(|_| AtomicI64::new(0)).collect(),
+            ..Default::default()
+        }
+    }
+    /// returns the unix timestamp at which the least recently active realm's worker loop last
+    /// started an iteration, so that a single stalled worker loop isn't masked by the others
+    pub fn last_run_at(&self) -> i64 {
+        self.realm_last_run_at
+            .iter()
+            .map(|last_run_at| last_run_at.load(Ordering::SeqCst))
+            .min()
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Clone)]
 struct Handler {
     is_loop_running: Arc<AtomicBool>,
     config: Arc<Configuration>,
-    exit_chan: crossbeam_channel::Receiver<bool>,
+    db: tulip_realms_sdk::Database,
+    status: Arc<WorkerStatus>,
+    owners: HashSet<UserId>,
+    /// the exit channel of each realm's worker loop, in the order of `config.realms`
+    exit_chans: Vec<crossbeam_channel::Receiver<bool>>,
 }
 
 impl Handler {
-    pub fn handle_ready(&self, _ctx: Context) {
+    pub async fn handle_ready(&self, _ctx: Context) {
         if !self
             .is_loop_running
             .load(std::sync::atomic::Ordering::SeqCst)
@@ -62,98 +155,192 @@ impl Handler {
             }
             info!("starting background task");
             let sleep_time = self.config.discord.worker_loop_frequency;
-            let exit_chan = self.exit_chan.clone();
-            let config = self.config.clone();
-            let rpc_client = Arc::new(self.config.rpc_client());
-            // we need the mint account type used for voting so that we may display vote counts
-            // as f64 instead of u64
-            let voter_mint = match rpc_client.get_account(&config.realm_info.community_mint_key()) {
-                Ok(voter_mint_acct) => {
-                    spl_token::state::Mint::unpack_unchecked(&voter_mint_acct.data[..]).unwrap()
+            let jitter = self.config.discord.worker_loop_jitter_secs;
+            // load every realm's voter mint before spawning any worker loops, so that a failure
+            // doesn't leave some realms running when the next ready event retries
+            let mut workers = Vec::with_capacity(self.config.realms.len());
+            for (realm, exit_chan) in self.config.realms.iter().zip(self.exit_chans.iter()) {
+                let config = Arc::new(self.config.for_realm(realm));
+                let rpc_client = Arc::new(config.realm_rpc_client(realm));
+                let gpa_rpc_client = Arc::new(config.gpa_rpc_client(realm));
+                // we need the mint account type used for voting so that we may display vote counts
+                // as f64 instead of u64
+                let voter_mint = match load_voter_mint_with_retry(
+                    rpc_client.as_ref(),
+                    realm.community_mint_key(),
+                    config.discord.mint_load_attempts,
+                )
+                .await
+                {
+                    Ok(voter_mint) => voter_mint,
+                    Err(err) => {
+                        // allow the next ready or resume event to retry starting the worker loop
+                        log::error!("failed to start background task {:#}", err);
+                        self.is_loop_running.store(false, Ordering::SeqCst);
+                        return;
+                    }
+                };
+                if let Ok(mut mint_decimals) = self.status.mint_decimals.write() {
+                    mint_decimals.insert(realm.community_mint_key(), voter_mint.decimals);
                 }
-                Err(err) => panic!("failed to load community mint {:#?}", err),
-            };
-            //let handler = Arc::new(self.clone());
-            let db = tulip_realms_sdk::Database::new(config.db_opts.clone()).unwrap();
-            if let Err(err) = db.sync_notif_cache_with_proposals(
-                config.realm_info.realm_key(),
-                config.realm_info.community_mint_key(),
-                config.realm_info.council_mint_key(),
-                Utc::now(),
-                &rpc_client,
-            ) {
-                log::error!("failed to sync notification cache with proposal {:#?}", err);
+                workers.push((
+                    config,
+                    realm.clone(),
+                    rpc_client,
+                    gpa_rpc_client,
+                    voter_mint,
+                    exit_chan.clone(),
+                ));
             }
-            tokio::task::spawn(async move {
-                // only send this if debug logs are enabled
-                if config.debug_log {
-                    let mut msg_builder = MessageBuilder::new();
-                    msg_builder.push("listening for new proposals");
-                    if let Err(err) = ChannelId(config.discord.status_channel)
-                        .say(&_ctx, msg_builder)
-                        .await
-                    {
-                        log::error!("failed to send message {:#?}", err);
+            let generation = self.status.worker_generation.fetch_add(1, Ordering::SeqCst) + 1;
+            for last_run_at in self.status.realm_last_run_at.iter() {
+                last_run_at.store(Utc::now().timestamp(), Ordering::SeqCst);
+            }
+            self.spawn_watchdog(_ctx.clone(), generation);
+            // every realm is tracked by its own worker loop, with its own notification caches
+            for (realm_idx, (config, realm, rpc_client, gpa_rpc_client, voter_mint, exit_chan)) in
+                workers.into_iter().enumerate()
+            {
+                let _ctx = _ctx.clone();
+                let db = self.db.clone();
+                let status = self.status.clone();
+                let owners = self.owners.clone();
+                let notification_sinks = build_notification_sinks(&config);
+                let governance_keys = realm.governance_keys();
+                let governing_token_mints = realm.governing_token_mints();
+                // the governances of every realm, used to report the total number
+                // of tracked proposals
+                let all_governance_keys: Vec<Pubkey> = self
+                    .config
+                    .realms
+                    .iter()
+                    .flat_map(|realm| realm.governance_keys())
+                    .collect();
+                for governance_key in governance_keys.iter() {
+                    if let Err(err) = db.sync_notif_cache_with_proposals(
+                        *governance_key,
+                        &governing_token_mints,
+                        Utc::now(),
+                        &rpc_client,
+                    ) {
+                        log::error!(
+                            "failed to sync notification cache with proposals for {}: {:#?}",
+                            governance_key,
+                            err
+                        );
                     }
                 }
-                let do_fn = async || {
-                    // check to see if we have any new proposals that were submitted
-                    match db.get_governance_notif_cache(config.realm_info.governance_key()) {
-                        Ok(mut notif_cache) => {
-                            // fetch the governance account
-                            let governance_account = {
-                                match rpc_client.get_account(&config.realm_info.governance_key()) {
-                                    Ok(account) => {
-                                        let mut account_tup =
-                                            (config.realm_info.governance_key(), account);
-                                        let account_info = account_tup.into_account_info();
-                                        match tulip_realms_sdk::types::get_governance_wrapper(
-                                            &account_info,
-                                        ) {
-                                            Ok(gov_acct) => gov_acct,
+                tokio::task::spawn(async move {
+                    // included in embeds so it's clear which dao a proposal belongs to
+                    let realm_name = match db.get_realm(realm.realm_key()) {
+                        Ok(realm) => realm.display_name().to_string(),
+                        Err(err) => {
+                            log::error!("failed to get realm {:#?}", err);
+                            realm.realm_key().to_string()
+                        }
+                    };
+                    if config.discord.announce_startup {
+                        let tracked_proposals: usize = governance_keys
+                            .iter()
+                            .filter_map(|governance_key| {
+                                db.get_governance_notif_cache(*governance_key).ok()
+                            })
+                            .map(|notif_cache| {
+                                notif_cache.voting_proposals_last_notification_time.len()
+                            })
+                            .sum();
+                        let mut msg_builder = MessageBuilder::new();
+                        msg_builder.push(format!(
+                        "listening for new proposals for {} across {} governances, tracking {} voting proposals",
+                        realm_name,
+                        governance_keys.len(),
+                        tracked_proposals
+                    ));
+                        if let Err(err) = send_message(
+                            &_ctx,
+                            config.discord.dry_run,
+                            ChannelId(config.discord.status_channel),
+                            |m| m.content(msg_builder),
+                        )
+                        .await
+                        {
+                            handle_send_error(
+                                &_ctx,
+                                &owners,
+                                &status,
+                                ChannelId(config.discord.status_channel),
+                                err,
+                            )
+                            .await;
+                        }
+                    }
+                    let do_fn = async || {
+                        status.realm_last_run_at[realm_idx]
+                            .store(Utc::now().timestamp(), Ordering::SeqCst);
+                        status
+                            .metrics
+                            .worker_loop_iterations
+                            .fetch_add(1, Ordering::Relaxed);
+                        // the status and database are shared by the realms' worker loops, so only
+                        // the first realm's loop checks them
+                        if realm_idx == 0 {
+                            check_rpc_progress(&_ctx, &config, &rpc_client, &status, &owners).await;
+                            check_db_size(&config, &db);
+                        }
+                        // check to see if we have any new proposals that were submitted
+                        for governance_key in governance_keys.iter() {
+                            match load_notif_cache(&db, *governance_key) {
+                                Ok(mut notif_cache) => {
+                                    // fetch the governance account
+                                    let governance_account =
+                                        match tulip_realms_sdk::utils::get_wrapper_with_retry(
+                                            rpc_client.as_ref(),
+                                            *governance_key,
+                                            RPC_FETCH_ATTEMPTS,
+                                            RPC_FETCH_BACKOFF,
+                                            tulip_realms_sdk::types::get_governance_wrapper,
+                                        )
+                                        .await
+                                        {
+                                            Ok(governance_account) => governance_account,
                                             Err(err) => {
                                                 log::error!(
                                                     "failed to get governance account {:#?}",
                                                     err
                                                 );
-                                                return;
+                                                status
+                                                    .metrics
+                                                    .rpc_errors
+                                                    .fetch_add(1, Ordering::Relaxed);
+                                                continue;
                                             }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        log::error!("failed to get governance account {:#?}", err);
-                                        return;
-                                    }
-                                }
-                            };
-                            if governance_account
-                                .governance
-                                .proposals_count
-                                .gt(&notif_cache.last_proposals_count)
-                            {
-                                log::warn!("found new proposals. proposals_count_previous {}, proposals_count_new {}", notif_cache.last_proposals_count, governance_account.governance.proposals_count);
-                                let mut new_proposals = Vec::with_capacity(
-                                    (governance_account.governance.proposals_count
-                                        - notif_cache.last_proposals_count)
-                                        as usize,
-                                );
-                                for idx in notif_cache.last_proposals_count
-                                    ..governance_account.governance.proposals_count
-                                {
-                                    let proposal_key =
-                                        spl_governance::state::proposal::get_proposal_address(
-                                            &GOVERNANCE_PROGRAM,
-                                            &config.realm_info.governance_key(),
-                                            &config.realm_info.community_mint_key(),
-                                            &idx.to_le_bytes()[..],
+                                        };
+                                    if governance_account
+                                        .governance
+                                        .proposals_count
+                                        .gt(&notif_cache.last_proposals_count)
+                                    {
+                                        log::warn!("found new proposals. proposals_count_previous {}, proposals_count_new {}", notif_cache.last_proposals_count, governance_account.governance.proposals_count);
+                                        let mut new_proposals = Vec::with_capacity(
+                                            (governance_account.governance.proposals_count
+                                                - notif_cache.last_proposals_count)
+                                                as usize,
                                         );
-                                    match rpc_client.get_account(&proposal_key) {
-                                        Ok(account) => {
-                                            let mut account_tup = (proposal_key, account);
-                                            let account_info = account_tup.into_account_info();
-                                            match tulip_realms_sdk::types::get_proposal_wrapper(
-                                                &account_info,
-                                            ) {
+                                        for idx in notif_cache.last_proposals_count
+                                            ..governance_account.governance.proposals_count
+                                        {
+                                            // proposals may be created with either the
+                                            // community or council mint
+                                            match tulip_realms_sdk::utils::get_proposal_at_index_with_retry(
+                                                rpc_client.as_ref(),
+                                                *governance_key,
+                                                &governing_token_mints,
+                                                idx,
+                                                RPC_FETCH_ATTEMPTS,
+                                                RPC_FETCH_BACKOFF,
+                                            )
+                                            .await
+                                            {
                                                 Ok(proposal) => {
                                                     new_proposals.push(proposal);
                                                 }
@@ -162,387 +349,1547 @@ impl Handler {
                                                         "failed to get proposal account {:#?}",
                                                         err
                                                     );
+                                                    status
+                                                        .metrics
+                                                        .rpc_errors
+                                                        .fetch_add(1, Ordering::Relaxed);
+                                                    continue;
+                                                }
+                                            }
+                                        }
+                                        for proposal in new_proposals.iter() {
+                                            // guard against re-announcing proposals if the bot restarted
+                                            // before the notification cache was updated
+                                            if db
+                                                .is_proposal_announced(proposal.key)
+                                                .unwrap_or(false)
+                                            {
+                                                log::warn!(
+                                                    "proposal {} was already announced, skipping",
+                                                    proposal.key
+                                                );
+                                                if let Err(err) =
+                                                    db.update_proposal_if_changed(proposal)
+                                                {
+                                                    log::error!(
+                                                        "failed to insert new proposal {:#?}",
+                                                        err
+                                                    );
+                                                }
+                                                continue;
+                                            }
+                                            let tally = tally_proposal_votes(
+                                                &db,
+                                                proposal,
+                                                gpa_rpc_client.as_ref(),
+                                                &status,
+                                            )
+                                            .unwrap_or_default();
+                                            let (approval_votes, deny_votes) =
+                                                tally.ui_amounts(voter_mint.decimals);
+                                            let abstain_votes =
+                                                tally.ui_abstain_votes(voter_mint.decimals);
+                                            let description = proposal_description(
+                                                proposal,
+                                                config.discord.resolve_descriptions,
+                                                config.discord.description_max_chars,
+                                            )
+                                            .await;
+                                            let event = ProposalEvent {
+                                                kind: ProposalEventKind::NewProposal,
+                                                realm_name: realm_name.clone(),
+                                                governance_key: governance_key.to_string(),
+                                                proposal_key: proposal.key.to_string(),
+                                                name: proposal.proposal.name.clone(),
+                                                description: description.clone(),
+                                                url: format!(
+                                                    "{}/proposal/{}",
+                                                    config.discord.ui_base_url, proposal.key
+                                                ),
+                                                approval_votes,
+                                                deny_votes,
+                                                abstain_votes,
+                                                voter_count: None,
+                                                vote_ends_at: None,
+                                                result: None,
+                                            };
+                                            match send_notification(
+                                                &_ctx,
+                                                config.discord.dry_run,
+                                                ChannelId(config.discord.proposal_channel_id()),
+                                                &notification_sinks,
+                                                &event,
+                                                |m| {
+                                                    mention_role(m, config.discord.notify_role_id);
+                                                    m.add_embed(|e| {
+                                                        e.title("New Proposal Detected");
+                                                        e.author(|a| a.name(&realm_name));
+                                                        e.field(
+                                                            "proposal".to_string(),
+                                                            format!(
+                                                                "[{}]({}/proposal/{})",
+                                                                proposal.key,
+                                                                config.discord.ui_base_url,
+                                                                proposal.key
+                                                            ),
+                                                            false,
+                                                        );
+                                                        e.field(
+                                                            "name".to_string(),
+                                                            proposal.proposal.name.clone(),
+                                                            false,
+                                                        );
+                                                        e.field("description", description, false);
+                                                        e.field(
+                                                            "created",
+                                                            proposal.created_at().to_rfc2822(),
+                                                            false,
+                                                        );
+                                                        e.field(
+                                                            "approval vote count",
+                                                            approval_votes.to_string(),
+                                                            false,
+                                                        );
+                                                        e.field(
+                                                            "deny vote count",
+                                                            deny_votes.to_string(),
+                                                            false,
+                                                        );
+                                                        e.field(
+                                                            "abstain vote count",
+                                                            abstain_votes.to_string(),
+                                                            false,
+                                                        );
+                                                        e
+                                                    });
+                                                    m
+                                                },
+                                            )
+                                            .await
+                                            {
+                                                Err(err) => {
+                                                    handle_send_error(
+                                                        &_ctx,
+                                                        &owners,
+                                                        &status,
+                                                        ChannelId(
+                                                            config.discord.proposal_channel_id(),
+                                                        ),
+                                                        err,
+                                                    )
+                                                    .await;
+                                                }
+                                                Ok(message) => {
+                                                    status
+                                                        .metrics
+                                                        .notifications_sent
+                                                        .fetch_add(1, Ordering::Relaxed);
+                                                    log_notification(
+                                                        "new_proposal",
+                                                        *governance_key,
+                                                        proposal.key,
+                                                        approval_votes,
+                                                        deny_votes,
+                                                    );
+                                                    if let Err(err) = db.mark_proposal_announced(
+                                                        proposal.key,
+                                                        Utc::now().timestamp(),
+                                                    ) {
+                                                        log::error!(
+                                                        "failed to mark proposal {} as announced {:#?}",
+                                                        proposal.key,
+                                                        err
+                                                    );
+                                                    }
+                                                    // messages are not posted in dry run mode, so there is nothing
+                                                    // to create a thread from
+                                                    if let (true, Some(message)) =
+                                                        (config.discord.use_threads, message)
+                                                    {
+                                                        let thread_name: String = proposal
+                                                            .proposal
+                                                            .name
+                                                            .chars()
+                                                            .take(100)
+                                                            .collect();
+                                                        match ChannelId(
+                                                            config.discord.proposal_channel_id(),
+                                                        )
+                                                        .create_public_thread(
+                                                            &_ctx,
+                                                            message.id,
+                                                            |t| t.name(thread_name),
+                                                        )
+                                                        .await
+                                                        {
+                                                            Ok(thread) => {
+                                                                notif_cache.proposal_threads.push(
+                                                                    (proposal.key, thread.id.0),
+                                                                );
+                                                            }
+                                                            Err(err) => {
+                                                                log::error!(
+                                                            "failed to create thread for proposal {}: {:#?}",
+                                                            proposal.key,
+                                                            err
+                                                        );
+                                                            }
+                                                        }
+                                                    }
+                                                    let mut contains_proposal = false;
+                                                    notif_cache
+                                                        .voting_proposals_last_notification_time
+                                                        .iter()
+                                                        .for_each(|notif_state| {
+                                                            if notif_state
+                                                                .proposal_key
+                                                                .eq(&proposal.key)
+                                                            {
+                                                                contains_proposal = true;
+                                                            }
+                                                        });
+                                                    if !contains_proposal {
+                                                        notif_cache
+                                                            .voting_proposals_last_notification_time
+                                                            .push(ProposalNotifState::new(
+                                                                proposal.key,
+                                                                Utc::now().timestamp(),
+                                                            ));
+                                                    }
+                                                    // only insert proposal after a successful notification
+                                                    if let Err(err) =
+                                                        db.update_proposal_if_changed(proposal)
+                                                    {
+                                                        log::error!(
+                                                            "failed to insert new proposal {:#?}",
+                                                            err
+                                                        );
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    if let Err(err) =
+                                        db.update_governance_if_changed(&governance_account)
+                                    {
+                                        log::error!("failed to isnert governance {:#?}", err);
+                                    }
+                                    // update the notif cache with the new proposal count
+                                    notif_cache.last_proposals_count =
+                                        governance_account.governance.proposals_count;
+                                    if let Err(err) = db.insert_notif_cache_entry(&notif_cache) {
+                                        log::error!("failed to insert notif cache {:#?}", err);
+                                    }
+                                    // now sync everything
+                                    match db.sync_notif_cache_with_proposals(
+                                        *governance_key,
+                                        &governing_token_mints,
+                                        Utc::now(),
+                                        &rpc_client,
+                                    ) {
+                                        Ok(transitions) => {
+                                            for transition in transitions.iter() {
+                                                if config.discord.execution_alerts
+                                                    && matches!(
+                                                        transition.state,
+                                                        ProposalState::Executing
+                                                            | ProposalState::ExecutingWithErrors
+                                                    )
+                                                {
+                                                    send_execution_alert(
+                                                        &_ctx,
+                                                        &config,
+                                                        &db,
+                                                        &status,
+                                                        &owners,
+                                                        &realm_name,
+                                                        transition,
+                                                    )
+                                                    .await;
                                                 }
                                             }
                                         }
                                         Err(err) => {
                                             log::error!(
-                                                "failed to get proposal account {:#?}",
+                                                "failed to sync disk backed cache {:#?}",
                                                 err
                                             );
-                                            continue;
                                         }
                                     }
-                                }
-                                for proposal in new_proposals.iter() {
-                                    if let Err(err) = ChannelId(config.discord.status_channel)
-                                        .send_message(&_ctx, |m| {
-                                            m.add_embed(|e| {
-                                                e.title("New Proposal Detected");
-                                                e.field(
-                                                    "proposal".to_string(),
-                                                    format!(
-                                                        "[{}]({}/proposal/{})",
-                                                        proposal.key,
-                                                        config.discord.ui_base_url,
-                                                        proposal.key
-                                                    ),
-                                                    false,
-                                                );
-                                                let mut proposal = proposal.proposal.clone();
-                                                // truncate description length if longer than 512 chars
-                                                proposal.description_link.truncate(
-                                                    if proposal.description_link.chars().count()
-                                                        > 512
-                                                    {
-                                                        512_usize
-                                                    } else {
-                                                        proposal.description_link.len()
-                                                    },
-                                                );
-                                                e.field("name".to_string(), proposal.name, false);
-                                                e.field(
-                                                    "description",
-                                                    proposal.description_link,
-                                                    false,
-                                                );
-                                                e
-                                            });
-                                            m
-                                        })
-                                        .await
-                                    {
-                                        log::error!("failed to send message {:#?}", err);
-                                    } else {
-                                        let mut contains_proposal = false;
-                                        notif_cache
-                                            .voting_proposals_last_notification_time
-                                            .iter()
-                                            .for_each(|(proposal_key, _)| {
-                                                if proposal_key.eq(&proposal.key) {
-                                                    contains_proposal = true;
-                                                }
-                                            });
-                                        if !contains_proposal {
-                                            notif_cache
-                                                .voting_proposals_last_notification_time
-                                                .push((proposal.key, Utc::now().timestamp()));
+                                    // announce the results of concluded proposals, including those
+                                    // whose announcement failed on a previous iteration
+                                    match db.list_pending_results(*governance_key) {
+                                        Ok(pending_results) => {
+                                            for pending in pending_results.iter() {
+                                                send_conclusion_notification(
+                                                    &_ctx,
+                                                    &config,
+                                                    &db,
+                                                    &rpc_client,
+                                                    &status,
+                                                    &owners,
+                                                    &notification_sinks,
+                                                    &realm_name,
+                                                    voter_mint.decimals,
+                                                    pending.proposal_key,
+                                                )
+                                                .await;
+                                            }
                                         }
-                                        // only insert proposal after a successful notification
-                                        if let Err(err) = db.insert_proposal(proposal) {
-                                            log::error!("failed to insert new proposal {:#?}", err);
+                                        Err(err) => {
+                                            log::error!(
+                                                "failed to list pending results {:#?}",
+                                                err
+                                            );
                                         }
                                     }
                                 }
-                            }
-                            if let Err(err) = db.insert_governance(&governance_account) {
-                                log::error!("failed to isnert governance {:#?}", err);
-                            }
-                            // update the notif cache with the new proposal count
-                            notif_cache.last_proposals_count =
-                                governance_account.governance.proposals_count;
-                            if let Err(err) = db.insert_notif_cache_entry(&notif_cache) {
-                                log::error!("failed to insert notif cache {:#?}", err);
-                            }
-                            // now sync everything
-                            if let Err(err) = db.sync_notif_cache_with_proposals(
-                                config.realm_info.realm_key(),
-                                config.realm_info.community_mint_key(),
-                                config.realm_info.council_mint_key(),
-                                Utc::now(),
-                                &rpc_client,
-                            ) {
-                                log::error!("failed to sync disk backed cache {:#?}", err);
+                                // neither the governance nor its notification cache have been
+                                // stored yet, which is expected until the database is seeded
+                                Err(RealmsError::NotFound { .. }) => {
+                                    log::warn!(
+                                    "no notification cache for governance {}, has the database been seeded?",
+                                    governance_key
+                                );
+                                }
+                                Err(err) => {
+                                    log::error!("failed to load notif cache {:#?}", err);
+                                }
                             }
                         }
-                        Err(err) => {
-                            log::error!("failed to load notif cache {:#?}", err);
+                        if let Err(err) = db.flush() {
+                            log::error!("failed to flush database {:#?}", err);
                         }
-                    }
-                    if let Err(err) = db.db.flush() {
-                        log::error!("failed to flush database {:#?}", err);
-                    }
-                    // now handle existing proposal notification
-                    match db.get_governance_notif_cache(config.realm_info.governance_key()) {
-                        Ok(mut notif_cache) => {
-                            // fetch the governance account
-                            let governance_account = {
-                                match rpc_client.get_account(&config.realm_info.governance_key()) {
-                                    Ok(account) => {
-                                        let mut account_tup =
-                                            (config.realm_info.governance_key(), account);
-                                        let account_info = account_tup.into_account_info();
-                                        match tulip_realms_sdk::types::get_governance_wrapper(
-                                            &account_info,
-                                        ) {
-                                            Ok(gov_acct) => gov_acct,
+                        // now handle existing proposal notification
+                        for governance_key in governance_keys.iter() {
+                            match db.get_governance_notif_cache(*governance_key) {
+                                Ok(mut notif_cache) => {
+                                    // fetch the governance account
+                                    let governance_account =
+                                        match tulip_realms_sdk::utils::get_wrapper_with_retry(
+                                            rpc_client.as_ref(),
+                                            *governance_key,
+                                            RPC_FETCH_ATTEMPTS,
+                                            RPC_FETCH_BACKOFF,
+                                            tulip_realms_sdk::types::get_governance_wrapper,
+                                        )
+                                        .await
+                                        {
+                                            Ok(governance_account) => governance_account,
                                             Err(err) => {
                                                 log::error!(
                                                     "failed to get governance account {:#?}",
                                                     err
                                                 );
-                                                return;
+                                                status
+                                                    .metrics
+                                                    .rpc_errors
+                                                    .fetch_add(1, Ordering::Relaxed);
+                                                continue;
                                             }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        log::error!("failed to get governance account {:#?}", err);
-                                        return;
-                                    }
-                                }
-                            };
-                            log::info!("notif cache\n{:#?}", notif_cache);
-                            let mut finished_proposals = Vec::with_capacity(
-                                notif_cache.voting_proposals_last_notification_time.len(),
-                            );
-                            for (proposal_key, last_notif_time) in notif_cache
-                                .voting_proposals_last_notification_time
-                                .iter_mut()
-                            {
-                                let now = Utc::now();
-                                let last_notif_ts =
-                                    tulip_realms_sdk::utils::date_time_from_timestamp(
-                                        *last_notif_time,
+                                        };
+                                    log::info!("notif cache\n{:#?}", notif_cache);
+                                    let mut finished_proposals = Vec::with_capacity(
+                                        notif_cache.voting_proposals_last_notification_time.len(),
                                     );
-                                match db.get_proposal(*proposal_key) {
-                                    Ok(proposal) => {
-                                        if !proposal.has_vote_time_ended(
-                                            &governance_account.governance.config,
-                                            now,
-                                        ) && now.gt(&last_notif_ts)
-                                        {
-                                            let duration_diff =
-                                                now.signed_duration_since(last_notif_ts);
-                                            if duration_diff.ge(&chrono::Duration::hours(
-                                                config.discord.notification_frequency,
-                                            )) {
-                                                if let Some(ends_at) = proposal.vote_ends_at(
+                                    let proposal_threads = notif_cache.proposal_threads.clone();
+                                    for notif_state in notif_cache
+                                        .voting_proposals_last_notification_time
+                                        .iter_mut()
+                                    {
+                                        let proposal_key = notif_state.proposal_key;
+                                        let now = Utc::now();
+                                        let last_notif_ts =
+                                            tulip_realms_sdk::utils::date_time_from_timestamp(
+                                                notif_state.last_notif_time,
+                                            );
+                                        match db.get_proposal_or_fetch(proposal_key, &rpc_client) {
+                                            Ok(proposal) => {
+                                                if !proposal.has_vote_time_ended(
                                                     &governance_account.governance.config,
-                                                ) {
-                                                    let time_until_end =
-                                                        ends_at.signed_duration_since(now);
-                                                    let voter_records = match tulip_realms_sdk::utils::get_vote_records_for_proposal(
-                                                        &rpc_client,
-                                                        proposal.key,
-                                                    ) {
-                                                        Ok(voter_records) => voter_records,
-                                                        Err(err) => {
-                                                            log::error!("failed to fetch voter records for proposal {}: {:#?}", proposal.key, err);
-                                                            vec![]
-                                                        }
-                                                    };
-                                                    let mut approval_votes = 0;
-                                                    let mut deny_votes = 0;
-                                                    // do not track relinquished votes
-                                                    for voter_record in
-                                                        voter_records.iter().filter(|vote_record| {
-                                                            !vote_record.is_relinquished
-                                                        })
-                                                    {
-                                                        match voter_record.vote {
-                                                            spl_governance::state::vote_record::Vote::Approve(_) => {
-                                                                approval_votes += voter_record.voter_weight
+                                                    now,
+                                                ) && !proposal.is_decided()
+                                                    && now.gt(&last_notif_ts)
+                                                {
+                                                    let duration_diff =
+                                                        now.signed_duration_since(last_notif_ts);
+                                                    if duration_diff.ge(&chrono::Duration::hours(
+                                                        config.discord.notification_frequency,
+                                                    )) {
+                                                        if let Some(ends_at) = proposal
+                                                            .vote_ends_at(
+                                                                &governance_account
+                                                                    .governance
+                                                                    .config,
+                                                            )
+                                                        {
+                                                            let time_until_end =
+                                                                ends_at.signed_duration_since(now);
+                                                            let tally = tally_proposal_votes(
+                                                                &db,
+                                                                proposal,
+                                                                gpa_rpc_client.as_ref(),
+                                                                &status,
+                                                            );
+                                                            if let Some(tally) = tally.as_ref() {
+                                                                if let Err(err) = db
+                                                                    .record_vote_tally(
+                                                                        proposal.key,
+                                                                        tally.clone(),
+                                                                    )
+                                                                {
+                                                                    log::error!("failed to record vote tally for proposal {}: {:#?}", proposal.key, err);
+                                                                }
                                                             }
-                                                            spl_governance::state::vote_record::Vote::Deny => {
-                                                                deny_votes += voter_record.voter_weight
+                                                            let tally = tally.unwrap_or_default();
+                                                            let (approval_votes, deny_votes) =
+                                                                tally.ui_amounts(
+                                                                    voter_mint.decimals,
+                                                                );
+                                                            let abstain_votes = tally
+                                                                .ui_abstain_votes(
+                                                                    voter_mint.decimals,
+                                                                );
+                                                            // the cached proposal's vote weights are as of when it was stored,
+                                                            // so refresh it before computing the progress to the threshold
+                                                            let live_proposal = tulip_realms_sdk::utils::get_wrapper_with_retry(
+                                                            rpc_client.as_ref(),
+                                                            proposal.key,
+                                                            RPC_FETCH_ATTEMPTS,
+                                                            RPC_FETCH_BACKOFF,
+                                                            tulip_realms_sdk::types::get_proposal_wrapper,
+                                                        )
+                                                        .await
+                                                        .unwrap_or_else(|_| ProposalV2Wrapper {
+                                                            proposal: proposal.proposal.clone(),
+                                                            key: proposal.key,
+                                                        });
+                                                            // max_vote_weight is only set once voting completes, so fall back
+                                                            // to the live supply of the community mint, which is also what
+                                                            // participation is measured against
+                                                            let mint_supply = if live_proposal
+                                                                .proposal
+                                                                .max_vote_weight
+                                                                .is_none()
+                                                                || config
+                                                                    .discord
+                                                                    .show_participation_rate
+                                                            {
+                                                                load_voter_mint_with_retry(
+                                                                    rpc_client.as_ref(),
+                                                                    realm.community_mint_key(),
+                                                                    1,
+                                                                )
+                                                                .await
+                                                                .map(|mint| mint.supply)
+                                                                .unwrap_or(voter_mint.supply)
+                                                            } else {
+                                                                voter_mint.supply
+                                                            };
+                                                            let max_vote_weight = live_proposal
+                                                                .proposal
+                                                                .max_vote_weight
+                                                                .unwrap_or(mint_supply);
+                                                            let approval_progress = live_proposal
+                                                                .approval_progress(
+                                                                    &governance_account
+                                                                        .governance
+                                                                        .config,
+                                                                    max_vote_weight,
+                                                                );
+                                                            let event = ProposalEvent {
+                                                                kind:
+                                                                    ProposalEventKind::VotingStats,
+                                                                realm_name: realm_name.clone(),
+                                                                governance_key: governance_key
+                                                                    .to_string(),
+                                                                proposal_key: proposal
+                                                                    .key
+                                                                    .to_string(),
+                                                                name: proposal
+                                                                    .proposal
+                                                                    .name
+                                                                    .clone(),
+                                                                description: proposal
+                                                                    .description_or_default(
+                                                                        config
+                                                                            .discord
+                                                                            .description_max_chars,
+                                                                    ),
+                                                                url: format!(
+                                                                    "{}/proposal/{}",
+                                                                    config.discord.ui_base_url,
+                                                                    proposal.key
+                                                                ),
+                                                                approval_votes,
+                                                                deny_votes,
+                                                                abstain_votes,
+                                                                voter_count: Some(
+                                                                    tally.voter_count,
+                                                                ),
+                                                                vote_ends_at: Some(
+                                                                    ends_at.timestamp(),
+                                                                ),
+                                                                result: None,
+                                                            };
+                                                            // post into the proposal's thread when one exists, otherwise the status channel
+                                                            let stats_channel = proposal_threads
+                                                                .iter()
+                                                                .find(|(key, _)| {
+                                                                    config.discord.use_threads
+                                                                        && key.eq(&proposal_key)
+                                                                })
+                                                                .map(|(_, thread_id)| {
+                                                                    ChannelId(*thread_id)
+                                                                })
+                                                                .unwrap_or(ChannelId(
+                                                                    config
+                                                                        .discord
+                                                                        .stats_channel_id(),
+                                                                ));
+                                                            if let Err(err) = send_notification(
+                                                            &_ctx,
+                                                            config.discord.dry_run,
+                                                            stats_channel,
+                                                            &notification_sinks,
+                                                            &event,
+                                                            |m| {
+                                                                m.add_embed(|e| {
+                                                                    e.title("Proposal Voting Stats".to_string());
+                                                                    e.author(|a| a.name(&realm_name));
+                                                                    e.description("stats for proposals accepting votes".to_string());
+                                                                    e.field(
+                                                                        "proposal".to_string(), 
+                                                                        format!("[{}]({}/proposal/{})", proposal.key, config.discord.ui_base_url, proposal.key),
+                                                                        false,
+                                                                    );
+                                                                    e.field("name".to_string(), proposal.proposal.name.clone(), false);
+                                                                    e.field(
+                                                                        "description",
+                                                                        proposal.description_or_default(config.discord.description_max_chars),
+                                                                        false,
+                                                                    );
+                                                                    e.field(
+                                                                        "approval vote count",
+                                                                        approval_votes.to_string().as_str(),
+                                                                        false,
+                                                                    );
+                                                                    e.field(
+                                                                        "deny vote count",
+                                                                        deny_votes.to_string().as_str(),
+                                                                        false,
+                                                                    );
+                                                                    e.field(
+                                                                        "abstain vote count",
+                                                                        abstain_votes.to_string().as_str(),
+                                                                        false,
+                                                                    );
+                                                                    e.field(
+                                                                        "voters",
+                                                                        format!("{} voters", tally.voter_count),
+                                                                        false,
+                                                                    );
+                                                                    e.field(
+                                                                        "progress to threshold",
+                                                                        format!("{:.2}%", approval_progress),
+                                                                        false,
+                                                                    );
+                                                                    if config.discord.show_participation_rate {
+                                                                        e.field(
+                                                                            "participation",
+                                                                            format!(
+                                                                                "{:.2}%",
+                                                                                live_proposal.participation_rate(mint_supply) * 100.0
+                                                                            ),
+                                                                            false,
+                                                                        );
+                                                                    }
+                                                                    if let Some(started_at) = live_proposal.vote_started_at() {
+                                                                        e.field(
+                                                                            "voting started",
+                                                                            started_at.to_rfc2822(),
+                                                                            false,
+                                                                        );
+                                                                    }
+                                                                    e.field(
+                                                                        "voting ends",
+                                                                        ends_at.to_rfc2822(),
+                                                                        false,
+                                                                    );
+                                                                    e.field(
+                                                                        "time left".to_string(),
+                                                                        tulip_realms_sdk::utils::humanize_duration(time_until_end),
+                                                                         false,
+                                                                    );
+                                                                    log::info!("embed {:#?}", e);
+                                                                    e
+                                                                });
+                                                                m
+                                                            },
+                                                        )
+                                                        .await
+                                                            {
+                                                                handle_send_error(&_ctx, &owners, &status, stats_channel, err).await;
+                                                            } else {
+                                                                status.metrics.notifications_sent.fetch_add(1, Ordering::Relaxed);
+                                                                log_notification("voting_stats", *governance_key, proposal.key, approval_votes, deny_votes);
+                                                                notif_state.last_notif_time = now.timestamp();
                                                             }
-                                                            _ => log::warn!("unsupported vote type {:#?}", voter_record.vote)
                                                         }
                                                     }
-                                                    let approval_votes = if approval_votes == 0 {
-                                                        0.0
-                                                    } else {
-                                                        spl_token::amount_to_ui_amount(
-                                                            approval_votes,
-                                                            voter_mint.decimals,
+                                                }
+                                                if let Some(ends_at) = proposal.vote_ends_at(
+                                                    &governance_account.governance.config,
+                                                ) {
+                                                    if !proposal.is_decided()
+                                                        && notif_state.needs_final_reminder(
+                                                            ends_at,
+                                                            now,
+                                                            config.discord.final_reminder_hours,
                                                         )
-                                                    };
-                                                    let deny_votes = if deny_votes == 0 {
-                                                        0.0
-                                                    } else {
-                                                        spl_token::amount_to_ui_amount(
-                                                            deny_votes,
-                                                            voter_mint.decimals,
+                                                        && send_final_reminder(
+                                                            &_ctx,
+                                                            &config,
+                                                            &status,
+                                                            &owners,
+                                                            &realm_name,
+                                                            &proposal,
+                                                            ends_at,
+                                                            now,
                                                         )
-                                                    };
-                                                    if let Err(err) = ChannelId(config.discord.status_channel)
-                                                        .send_message(&_ctx, |m| {
-                                                            m.add_embed(|e| {
-                                                                e.title("Proposal Voting Stats".to_string());
-                                                                e.description("stats for proposals accepting votes".to_string());
-                                                                e.field(
-                                                                    "proposal".to_string(), 
-                                                                    format!("[{}]({}/proposal/{})", proposal.key, config.discord.ui_base_url, proposal.key),
-                                                                    false,
-                                                                );
-                                                                let mut proposal = proposal.proposal.clone();
-                                                                // truncate description length if longer than 512 chars
-                                                                proposal.description_link.truncate(
-                                                                    if proposal.description_link.chars().count()
-                                                                        > 512
-                                                                    {
-                                                                        512_usize
-                                                                    } else {
-                                                                        proposal.description_link.len()
-                                                                    },
-                                                                );
-                                                                e.field("name".to_string(), proposal.name, false);
-                                                                let description = if proposal.description_link.eq_ignore_ascii_case("") {
-                                                                    "no description provided".to_string()
-                                                                } else {
-                                                                    proposal.description_link.clone()
-                                                                };
-                                                                e.field(
-                                                                    "description",
-                                                                    description.as_str(),
-                                                                    false,
-                                                                );
-                                                                e.field(
-                                                                    "approval vote count",
-                                                                    approval_votes.to_string().as_str(),
-                                                                    false,
-                                                                );
-                                                                e.field(
-                                                                    "deny vote count",
-                                                                    deny_votes.to_string().as_str(),
-                                                                    false,
-                                                                );
-                                                                e.field(
-                                                                    "time left".to_string(),
-                                                                    format!("{} hours", time_until_end.num_hours()),
-                                                                     false,
-                                                                );
-                                                                log::info!("embed {:#?}", e);
-                                                                e
-                                                            });
-                                                            m
-                                                        })
                                                         .await
-                                                        {
-                                                            log::error!("failed to send message {:#?}", err);
-                                                        } else {
-                                                            *last_notif_time = now.timestamp();
-                                                        }
+                                                    {
+                                                        notif_state.final_reminder_sent = true;
+                                                    }
                                                 }
+                                                // mark a proposal as finished if vote time has ended, the vote was
+                                                // tipped early, **or** state is not voting
+                                                let inserted = if proposal.has_vote_time_ended(
+                                                    &governance_account.governance.config,
+                                                    now,
+                                                ) || proposal.is_decided()
+                                                {
+                                                    finished_proposals.push(proposal.key);
+                                                    true
+                                                } else {
+                                                    false
+                                                };
+                                                if !inserted && proposal.proposal.state.ne(
+                                                &spl_governance::state::enums::ProposalState::Voting,
+                                            ) {
+                                                finished_proposals.push(proposal.key);
+                                            }
+                                                log::info!(
+                                                    "proposal {}, state {:#?}",
+                                                    proposal.key,
+                                                    proposal.proposal.state
+                                                );
+                                            }
+                                            Err(err) => {
+                                                log::error!(
+                                                    "failed to get proposal for {}: {:#?}",
+                                                    proposal_key,
+                                                    err
+                                                );
                                             }
                                         }
-                                        // mark a proposal as finished if vote time has ended **or** state is not voting
-                                        let inserted = if proposal.has_vote_time_ended(
-                                            &governance_account.governance.config,
-                                            now,
-                                        ) {
-                                            finished_proposals.push(proposal.key);
-                                            true
+                                    }
+                                    log::info!("checking for proposals to remove");
+                                    // remove any proposals which finished
+                                    for proposal in finished_proposals.iter() {
+                                        log::info!("checking proposal {}", proposal);
+                                        if let Ok(prop_info) = db.get_proposal(*proposal) {
+                                            log::info!(
+                                                "checking proposal {}, state {:#?}",
+                                                proposal,
+                                                prop_info.proposal.state
+                                            );
                                         } else {
-                                            false
-                                        };
-                                        if !inserted && proposal.proposal.state.ne(
-                                            &spl_governance::state::enums::ProposalState::Voting,
-                                        ) {
-                                            finished_proposals.push(proposal.key);
+                                            continue;
                                         }
-                                        log::info!(
-                                            "proposal {}, state {:#?}",
-                                            proposal.key,
-                                            proposal.proposal.state
+                                        for (idx, notif_state) in notif_cache
+                                            .clone()
+                                            .voting_proposals_last_notification_time
+                                            .iter()
+                                            .enumerate()
+                                        {
+                                            if proposal.eq(&notif_state.proposal_key) {
+                                                log::info!("removing proposal {}", proposal);
+                                                // remove this index
+                                                notif_cache
+                                                    .voting_proposals_last_notification_time
+                                                    .swap_remove(idx);
+                                                break;
+                                            }
+                                        }
+                                        notif_cache
+                                            .proposal_threads
+                                            .retain(|(key, _)| key.ne(proposal));
+                                    }
+                                    if let Err(err) = db.insert_notif_cache_entry(&notif_cache) {
+                                        log::error!(
+                                            "failed to update notification cache {:#?}",
+                                            err
                                         );
                                     }
-                                    Err(err) => {
+                                    if let Err(err) =
+                                        db.update_governance_if_changed(&governance_account)
+                                    {
                                         log::error!(
-                                            "failed to get proposal for {}: {:#?}",
-                                            err,
-                                            proposal_key
+                                            "failed to update governance account {:#?}",
+                                            err
                                         );
                                     }
-                                }
-                            }
-                            log::info!("checking for proposals to remove");
-                            // remove any proposals which finished
-                            for proposal in finished_proposals.iter() {
-                                log::info!("checking proposal {}", proposal);
-                                if let Ok(prop_info) = db.get_proposal(*proposal) {
-                                    log::info!(
-                                        "checking proposal {}, state {:#?}",
-                                        proposal,
-                                        prop_info.proposal.state
-                                    );
-                                } else {
-                                    continue;
-                                }
-                                for (idx, (key, _)) in notif_cache
-                                    .clone()
-                                    .voting_proposals_last_notification_time
-                                    .iter()
-                                    .enumerate()
-                                {
-                                    if proposal.eq(key) {
-                                        log::info!("removing proposal {}", proposal);
-                                        // remove this index
-                                        notif_cache
-                                            .voting_proposals_last_notification_time
-                                            .swap_remove(idx);
-                                        break;
+                                    if let Err(err) = db.flush() {
+                                        log::error!("failed to flush database {:#?}", err);
                                     }
                                 }
-                            }
-                            if let Err(err) = db.insert_notif_cache_entry(&notif_cache) {
-                                log::error!("failed to update notification cache {:#?}", err);
-                            }
-                            if let Err(err) = db.insert_governance(&governance_account) {
-                                log::error!("failed to update governance account {:#?}", err);
-                            }
-                            if let Err(err) = db.db.flush() {
-                                log::error!("failed to flush database {:#?}", err);
+
+                                Err(err) => {
+                                    log::error!("failed to load notif cache {:#?}", err);
+                                }
                             }
                         }
+                        let tracked_proposals: usize = all_governance_keys
+                            .iter()
+                            .filter_map(|governance_key| {
+                                db.get_governance_notif_cache(*governance_key).ok()
+                            })
+                            .map(|notif_cache| {
+                                notif_cache.voting_proposals_last_notification_time.len()
+                            })
+                            .sum();
+                        status
+                            .metrics
+                            .proposals_tracked
+                            .store(tracked_proposals as u64, Ordering::Relaxed);
+                    };
+                    run_worker_loop(exit_chan, &status, generation, sleep_time, jitter, do_fn)
+                        .await;
+                });
+            }
+        }
+    }
 
-                        Err(err) => {
-                            log::error!("failed to load notif cache {:#?}", err);
-                        }
-                    }
+    /// spawns a supervisor for the given worker loop generation which checks that every realm's
+    /// worker loop is still making progress. if any of them hasn't started an iteration within
+    /// 3 times the worker loop frequency, they are flagged as stopped so that the next ready or
+    /// resume event respawns them
+    fn spawn_watchdog(&self, ctx: Context, generation: u64) {
+        let config = self.config.clone();
+        let status = self.status.clone();
+        let owners = self.owners.clone();
+        let is_loop_running = self.is_loop_running.clone();
+        tokio::task::spawn(async move {
+            let check_frequency =
+                std::time::Duration::from_secs(config.discord.worker_loop_frequency.max(1));
+            let max_stall = config.discord.worker_loop_frequency as i64 * 3;
+            loop {
+                tokio::time::sleep(check_frequency).await;
+                if status.worker_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                // the realm whose worker loop least recently started an iteration
+                let (realm_idx, last_run_at) = match status
+                    .realm_last_run_at
+                    .iter()
+                    .map(|last_run_at| last_run_at.load(Ordering::SeqCst))
+                    .enumerate()
+                    .min_by_key(|(_, last_run_at)| *last_run_at)
+                {
+                    Some(oldest) => oldest,
+                    None => return,
                 };
-                loop {
-                    select! {
-                        recv(exit_chan) -> _msg => {
-                            warn!("discord workerloop received exit signal");
-                            return;
-                        }
-                        default() => {
-                            do_fn().await;
-                            std::thread::sleep(std::time::Duration::from_secs(sleep_time));
-                        }
-                    }
+                let stalled_for = Utc::now().timestamp() - last_run_at;
+                if stalled_for <= max_stall {
+                    continue;
+                }
+                let realm = &config.realms[realm_idx];
+                log::error!(
+                    "worker loop of realm {} hasn't made progress in {} seconds, flagging for restart",
+                    realm.realm_key(),
+                    stalled_for
+                );
+                // the worker loops share a generation, so every realm's worker loop is invalidated
+                // and respawned together, exiting if they ever resume
+                status.worker_generation.fetch_add(1, Ordering::SeqCst);
+                is_loop_running.store(false, Ordering::SeqCst);
+                let channel = ChannelId(config.for_realm(realm).discord.status_channel);
+                let mut msg_builder = MessageBuilder::new();
+                msg_builder.push(format!(
+                    "⚠️ worker loop of realm {} hasn't made progress in {} seconds, restarting on the next gateway event",
+                    realm.realm_key(),
+                    stalled_for
+                ));
+                if let Err(err) = send_message(&ctx, config.discord.dry_run, channel, |m| {
+                    m.content(msg_builder)
+                })
+                .await
+                {
+                    handle_send_error(&ctx, &owners, &status, channel, err).await;
+                }
+                return;
+            }
+        });
+    }
+}
+
+/// number of attempts made when fetching governance and proposal accounts in the worker loop
+const RPC_FETCH_ATTEMPTS: u32 = 3;
+
+/// delay before the first retry when fetching accounts in the worker loop
+const RPC_FETCH_BACKOFF: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// delay before the first retry when loading the voter mint, doubled after each failed attempt
+const MINT_RETRY_BACKOFF_MS: u64 = 500;
+
+/// how long shutdown waits for the worker loop to finish its current iteration and exit
+const WORKER_EXIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// minutes for which cached vote records are used before they are refetched, as
+/// fetching vote records requires a program account scan
+const VOTE_RECORD_MAX_AGE_MINS: i64 = 5;
+
+/// tallies the votes cast on the proposal from its cached vote records, so the new proposal and
+/// stats embeds report counts from the same source. returns None if the vote records could not
+/// be fetched, which is counted as an rpc error
+fn tally_proposal_votes(
+    db: &tulip_realms_sdk::Database,
+    proposal: &ProposalV2Wrapper,
+    gpa_rpc_client: &impl tulip_realms_sdk::utils::VoteRecordFetcher,
+    status: &WorkerStatus,
+) -> Option<tulip_realms_sdk::types::VoteTally> {
+    let cached_fetcher = tulip_realms_sdk::CachedVoteRecordFetcher {
+        db,
+        max_age: chrono::Duration::minutes(VOTE_RECORD_MAX_AGE_MINS),
+        rpc: gpa_rpc_client,
+    };
+    match proposal.vote_tally(&cached_fetcher) {
+        Ok(tally) => Some(tally),
+        Err(err) => {
+            log::error!(
+                "failed to fetch voter records for proposal {}: {:#?}",
+                proposal.key,
+                err
+            );
+            status.metrics.rpc_errors.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+}
+
+/// fetches raw account data, allowing rpc access to be mocked
+pub trait AccountDataFetcher {
+    fn fetch_account_data(&self, key: &Pubkey) -> Result<Vec<u8>>;
+}
+
+impl AccountDataFetcher for RpcClient {
+    fn fetch_account_data(&self, key: &Pubkey) -> Result<Vec<u8>> {
+        Ok(self.get_account_data(key)?)
+    }
+}
+
+/// loads the given mint, making up to `attempts` attempts with exponential backoff between them
+pub async fn load_voter_mint_with_retry(
+    rpc: &impl AccountDataFetcher,
+    mint: Pubkey,
+    attempts: u32,
+) -> Result<spl_token::state::Mint> {
+    let mut backoff = std::time::Duration::from_millis(MINT_RETRY_BACKOFF_MS);
+    let mut attempt = 1;
+    loop {
+        match rpc.fetch_account_data(&mint) {
+            Ok(data) => {
+                return spl_token::state::Mint::unpack_unchecked(&data[..])
+                    .map_err(|err| anyhow!("failed to unpack mint {}: {:#?}", mint, err));
+            }
+            Err(err) if attempt >= attempts => {
+                return Err(err.context(format!(
+                    "failed to load mint {} after {} attempts",
+                    mint, attempt
+                )));
+            }
+            Err(err) => {
+                warn!(
+                    "failed to load mint {} (attempt {}/{}), retrying in {:?}: {:#}",
+                    mint, attempt, attempts, backoff, err
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// compares the slot reported by the rpc node against the slot observed during the previous
+/// iteration of the worker loop, alerting the status channel when the node starts lagging as
+/// proposal counts and states will be stale
+async fn check_rpc_progress(
+    ctx: &Context,
+    config: &Configuration,
+    rpc_client: &RpcClient,
+    status: &WorkerStatus,
+    owners: &HashSet<UserId>,
+) {
+    let now = Utc::now();
+    let slot = match rpc_client.get_slot() {
+        Ok(slot) => slot,
+        Err(err) => {
+            log::error!("failed to get slot {:#?}", err);
+            status.metrics.rpc_errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    };
+    let previous_slot = status.last_slot.swap(slot, Ordering::SeqCst);
+    let previous_slot_at = status.last_slot_at.swap(now.timestamp(), Ordering::SeqCst);
+    if previous_slot == 0 {
+        return;
+    }
+    let elapsed = now.signed_duration_since(tulip_realms_sdk::utils::date_time_from_timestamp(
+        previous_slot_at,
+    ));
+    let progress = tulip_realms_sdk::utils::slot_progress(previous_slot, slot, elapsed);
+    if progress == SlotProgress::Healthy {
+        if status.rpc_lagging.swap(false, Ordering::SeqCst) {
+            log::info!("rpc node recovered at slot {}", slot);
+        }
+        return;
+    }
+    log::warn!(
+        "rpc node appears {:?}. previous slot {}, current slot {}, elapsed {}s",
+        progress,
+        previous_slot,
+        slot,
+        elapsed.num_seconds()
+    );
+    // only alert when the node starts lagging to avoid spamming the channel
+    if !status.rpc_lagging.swap(true, Ordering::SeqCst) {
+        let mut msg_builder = MessageBuilder::new();
+        msg_builder.push(format!(
+            "rpc node appears {:?} at slot {}, proposal information may be stale",
+            progress, slot
+        ));
+        if let Err(err) = send_message(
+            ctx,
+            config.discord.dry_run,
+            ChannelId(config.discord.status_channel),
+            |m| m.content(msg_builder),
+        )
+        .await
+        {
+            handle_send_error(
+                ctx,
+                owners,
+                status,
+                ChannelId(config.discord.status_channel),
+                err,
+            )
+            .await;
+        }
+    }
+}
+
+/// returns the notification sinks configured in addition to the discord bot
+fn build_notification_sinks(config: &Configuration) -> Vec<Box<dyn NotificationSink>> {
+    config
+        .notification_sinks
+        .iter()
+        .map(|sink| -> Box<dyn NotificationSink> {
+            match sink {
+                NotificationSinkConfig::Webhook { url } => Box::new(WebhookSink::new(url.clone())),
+                NotificationSinkConfig::DiscordWebhook { url } => {
+                    Box::new(DiscordWebhookSink::new(url.clone()))
+                }
+            }
+        })
+        .collect()
+}
+
+/// delivers the event to each of the configured notification sinks. when `dry_run` is enabled
+/// the event is logged instead of being delivered
+async fn dispatch_event(sinks: &[Box<dyn NotificationSink>], dry_run: bool, event: &ProposalEvent) {
+    if dry_run {
+        if !sinks.is_empty() {
+            log::info!(
+                "dry run, not delivering event to {} notification sinks: {:#?}",
+                sinks.len(),
+                event
+            );
+        }
+        return;
+    }
+    for sink in sinks.iter() {
+        if let Err(err) = sink.send_proposal_event(event).await {
+            log::error!(
+                "failed to deliver {:?} event for proposal {}: {:#}",
+                event.kind,
+                event.proposal_key,
+                err
+            );
+        }
+    }
+}
+
+/// logs a structured record of a sent notification. the message is a json object so that
+/// when `json_logs` is enabled its fields are emitted as fields of the log line
+fn log_notification(
+    event: &str,
+    governance_key: Pubkey,
+    proposal_key: Pubkey,
+    approval_votes: f64,
+    deny_votes: f64,
+) {
+    log::info!(
+        target: "notifications",
+        "{}",
+        serde_json::json!({
+            "event": event,
+            "governance_key": governance_key.to_string(),
+            "proposal_key": proposal_key.to_string(),
+            "approval_votes": approval_votes,
+            "deny_votes": deny_votes,
+        })
+    );
+}
+
+/// returns the notification cache of the governance, initializing it from the stored
+/// governance account when missing, such as for a freshly seeded governance
+fn load_notif_cache(
+    db: &tulip_realms_sdk::Database,
+    governance_key: Pubkey,
+) -> Result<tulip_realms_sdk::types::NotifCacheEntry, RealmsError> {
+    match db.get_governance(governance_key) {
+        Ok(governance) => {
+            db.get_or_init_notif_cache(governance_key, governance.governance.proposals_count)
+        }
+        // without the governance account there is no way to tell which proposals are new
+        Err(_) => db.get_governance_notif_cache(governance_key),
+    }
+}
+
+/// warns when the database grows beyond the configured `max_db_size_mb`, to preempt
+/// the bot failing once the volume it is stored on fills up
+fn check_db_size(config: &Configuration, db: &tulip_realms_sdk::Database) {
+    if config.max_db_size_mb == 0 {
+        return;
+    }
+    match db.size_on_disk() {
+        Ok(size) => {
+            let size_mb = size / 1024 / 1024;
+            if size_mb > config.max_db_size_mb {
+                log::warn!(
+                    "database size of {}mb exceeds max_db_size_mb of {}mb",
+                    size_mb,
+                    config.max_db_size_mb
+                );
+            }
+        }
+        Err(err) => log::error!("failed to get database size {:#?}", err),
+    }
+}
+
+/// alerts the status channel that a proposal has entered the Executing, or ExecutingWithErrors
+/// state. a failed execution is actionable by the dao's admins, and otherwise goes unnoticed
+async fn send_execution_alert(
+    ctx: &Context,
+    config: &Configuration,
+    db: &tulip_realms_sdk::Database,
+    status: &WorkerStatus,
+    owners: &HashSet<UserId>,
+    realm_name: &str,
+    transition: &ProposalStateTransition,
+) {
+    let name = match db.get_proposal(transition.proposal_key) {
+        Ok(proposal) => proposal.proposal.name,
+        Err(err) => {
+            log::error!(
+                "failed to get proposal {}: {:#?}",
+                transition.proposal_key,
+                err
+            );
+            "unknown".to_string()
+        }
+    };
+    let title = if transition.state.eq(&ProposalState::ExecutingWithErrors) {
+        "Proposal Execution Failed"
+    } else {
+        "Proposal Executing"
+    };
+    if let Err(err) = send_message(
+        ctx,
+        config.discord.dry_run,
+        ChannelId(config.discord.status_channel),
+        |m| {
+            m.add_embed(|e| {
+                e.title(title);
+                e.author(|a| a.name(realm_name));
+                e.field(
+                    "proposal",
+                    format!(
+                        "[{}]({}/proposal/{})",
+                        transition.proposal_key,
+                        config.discord.ui_base_url,
+                        transition.proposal_key
+                    ),
+                    false,
+                );
+                e.field("name", name, false);
+                e.field(
+                    "state",
+                    format!("{:?} -> {:?}", transition.previous_state, transition.state),
+                    false,
+                );
+                e
+            });
+            m
+        },
+    )
+    .await
+    {
+        handle_send_error(
+            ctx,
+            owners,
+            status,
+            ChannelId(config.discord.status_channel),
+            err,
+        )
+        .await;
+    }
+}
+
+/// runs `do_fn` every `sleep_time` seconds, randomly offset by up to `jitter` seconds, until the
+/// exit signal is received, or the worker loop is replaced by the watchdog. `status.worker_exited`
+/// is notified when the loop exits due to the exit signal, allowing shutdown to wait for the
+/// current iteration to finish
+async fn run_worker_loop<F, Fut>(
+    exit_chan: crossbeam_channel::Receiver<bool>,
+    status: &WorkerStatus,
+    generation: u64,
+    sleep_time: u64,
+    jitter: u64,
+    do_fn: F,
+) where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    status.active_workers.fetch_add(1, Ordering::SeqCst);
+    loop {
+        select! {
+            recv(exit_chan) -> _msg => {
+                warn!("discord workerloop received exit signal");
+                // stop the watchdog from flagging the worker loop as stalled
+                status.worker_generation.fetch_add(1, Ordering::SeqCst);
+                status.active_workers.fetch_sub(1, Ordering::SeqCst);
+                status.worker_exited.notify_one();
+                return;
+            }
+            default() => {
+                if status.worker_generation.load(Ordering::SeqCst) != generation {
+                    warn!("discord workerloop was replaced by the watchdog, goodbye");
+                    status.active_workers.fetch_sub(1, Ordering::SeqCst);
+                    return;
+                }
+                do_fn().await;
+                // sleep in short increments so the exit signal is picked up promptly,
+                // without blocking the runtime thread shared with the gateway
+                let sleep_time =
+                    worker_loop_sleep_time(sleep_time, jitter, &mut rand::thread_rng());
+                let deadline =
+                    tokio::time::Instant::now() + std::time::Duration::from_secs(sleep_time);
+                while exit_chan.is_empty() && tokio::time::Instant::now() < deadline {
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                }
+            }
+        }
+    }
+}
+
+/// returns `frequency` offset by a random amount of up to `jitter` seconds in either direction,
+/// never returning less than a second
+fn worker_loop_sleep_time<R: rand::Rng>(frequency: u64, jitter: u64, rng: &mut R) -> u64 {
+    if jitter == 0 {
+        return frequency;
+    }
+    let offset = rng.gen_range(-(jitter as i64)..=jitter as i64);
+    (frequency as i64).saturating_add(offset).max(1) as u64
+}
+
+/// announces the result of a proposal once voting on it concludes, unless it was already announced.
+/// the final tally recorded while the proposal was voting is used when available, otherwise the
+/// vote weights stored on the proposal account
+#[allow(clippy::too_many_arguments)]
+async fn send_conclusion_notification(
+    ctx: &Context,
+    config: &Configuration,
+    db: &tulip_realms_sdk::Database,
+    rpc_client: &RpcClient,
+    status: &WorkerStatus,
+    owners: &HashSet<UserId>,
+    notification_sinks: &[Box<dyn NotificationSink>],
+    realm_name: &str,
+    voter_mint_decimals: u8,
+    proposal_key: Pubkey,
+) {
+    match db.is_result_announced(proposal_key) {
+        Ok(false) => (),
+        Ok(true) => {
+            log::info!(
+                "result of proposal {} was already announced, skipping",
+                proposal_key
+            );
+            return;
+        }
+        Err(err) => {
+            log::error!(
+                "failed to check if result of proposal {} was announced {:#?}",
+                proposal_key,
+                err
+            );
+            return;
+        }
+    }
+    let proposal = match tulip_realms_sdk::utils::get_wrapper_with_retry(
+        rpc_client,
+        proposal_key,
+        RPC_FETCH_ATTEMPTS,
+        RPC_FETCH_BACKOFF,
+        tulip_realms_sdk::types::get_proposal_wrapper,
+    )
+    .await
+    {
+        Ok(proposal) => proposal,
+        Err(err) => {
+            log::error!("failed to get proposal {}: {:#?}", proposal_key, err);
+            status.metrics.rpc_errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    };
+    let final_tally = db
+        .get_proposal_tally(proposal_key)
+        .ok()
+        .and_then(|tally_entry| tally_entry.final_tally);
+    let (approval_votes, deny_votes) = match final_tally.as_ref() {
+        Some(final_tally) => final_tally.ui_amounts(voter_mint_decimals),
+        None => {
+            let approval_votes = proposal
+                .proposal
+                .options
+                .iter()
+                .map(|option| option.vote_weight)
+                .sum();
+            tulip_realms_sdk::types::VoteTally {
+                approval_votes,
+                deny_votes: proposal.proposal.deny_vote_weight.unwrap_or(0),
+                ..Default::default()
+            }
+            .ui_amounts(voter_mint_decimals)
+        }
+    };
+    let event = ProposalEvent {
+        kind: ProposalEventKind::Concluded,
+        realm_name: realm_name.to_string(),
+        governance_key: proposal.proposal.governance.to_string(),
+        proposal_key: proposal_key.to_string(),
+        name: proposal.proposal.name.clone(),
+        description: proposal.description_or_default(config.discord.description_max_chars),
+        url: format!("{}/proposal/{}", config.discord.ui_base_url, proposal_key),
+        approval_votes,
+        deny_votes,
+        abstain_votes: final_tally
+            .as_ref()
+            .map(|final_tally| final_tally.ui_abstain_votes(voter_mint_decimals))
+            .unwrap_or_default(),
+        voter_count: final_tally
+            .as_ref()
+            .map(|final_tally| final_tally.voter_count),
+        vote_ends_at: None,
+        result: Some(format!("{:?}", proposal.proposal.state)),
+    };
+    match send_notification(
+        ctx,
+        config.discord.dry_run,
+        ChannelId(config.discord.proposal_channel_id()),
+        notification_sinks,
+        &event,
+        |m| {
+            m.add_embed(|e| {
+                e.title("Proposal Concluded");
+                e.author(|a| a.name(realm_name));
+                e.field(
+                    "proposal",
+                    format!(
+                        "[{}]({}/proposal/{})",
+                        proposal_key, config.discord.ui_base_url, proposal_key
+                    ),
+                    false,
+                );
+                e.field("name", &proposal.proposal.name, false);
+                e.field("result", format!("{:?}", proposal.proposal.state), false);
+                e.field("approval vote count", approval_votes.to_string(), false);
+                e.field("deny vote count", deny_votes.to_string(), false);
+                if let Some(final_tally) = final_tally.as_ref() {
+                    e.field(
+                        "voters",
+                        format!("{} voters", final_tally.voter_count),
+                        false,
+                    );
                 }
+                e
             });
+            m
+        },
+    )
+    .await
+    {
+        Ok(_) => {
+            status
+                .metrics
+                .notifications_sent
+                .fetch_add(1, Ordering::Relaxed);
+            log_notification(
+                "proposal_concluded",
+                proposal.proposal.governance,
+                proposal_key,
+                approval_votes,
+                deny_votes,
+            );
+            if let Err(err) = db.mark_result_announced(proposal_key, Utc::now().timestamp()) {
+                log::error!(
+                    "failed to mark result of proposal {} as announced {:#?}",
+                    proposal_key,
+                    err
+                );
+            }
+        }
+        Err(err) => {
+            handle_send_error(
+                ctx,
+                owners,
+                status,
+                ChannelId(config.discord.proposal_channel_id()),
+                err,
+            )
+            .await;
+        }
+    }
+}
+
+/// sends a message to the channel. when `dry_run` is enabled the fully built message is logged
+/// instead of being posted, and None is returned
+pub(crate) async fn send_message<'a, F>(
+    http: impl AsRef<Http>,
+    dry_run: bool,
+    channel: ChannelId,
+    f: F,
+) -> serenity::Result<Option<Message>>
+where
+    for<'b> F: FnOnce(&'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a>,
+{
+    if dry_run {
+        let mut create_message = CreateMessage::default();
+        f(&mut create_message);
+        log::info!(
+            "dry run, not sending message to {}: {:#?}",
+            channel,
+            create_message.0
+        );
+        return Ok(None);
+    }
+    channel.send_message(http, f).await.map(Some)
+}
+
+/// sends a message to the channel as `send_message` does, delivering the event to the
+/// notification sinks only once the message was sent. a failed send is retried on the next
+/// iteration of the worker loop, which would otherwise deliver the event to the sinks again
+async fn send_notification<'a, F>(
+    http: impl AsRef<Http>,
+    dry_run: bool,
+    channel: ChannelId,
+    sinks: &[Box<dyn NotificationSink>],
+    event: &ProposalEvent,
+    f: F,
+) -> serenity::Result<Option<Message>>
+where
+    for<'b> F: FnOnce(&'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a>,
+{
+    let message = send_message(http, dry_run, channel, f).await?;
+    dispatch_event(sinks, dry_run, event).await;
+    Ok(message)
+}
+
+/// posts a reminder to the proposal channel that voting on the proposal ends soon, mentioning
+/// `notify_role_id` when set. returns true if the reminder was sent
+#[allow(clippy::too_many_arguments)]
+async fn send_final_reminder(
+    ctx: &Context,
+    config: &Configuration,
+    status: &WorkerStatus,
+    owners: &HashSet<UserId>,
+    realm_name: &str,
+    proposal: &ProposalV2Wrapper,
+    ends_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> bool {
+    let channel = ChannelId(config.discord.proposal_channel_id());
+    match send_message(ctx, config.discord.dry_run, channel, |m| {
+        mention_role(m, config.discord.notify_role_id);
+        m.add_embed(|e| {
+            e.title("⏰ Voting ends soon");
+            e.author(|a| a.name(realm_name));
+            e.field(
+                "proposal",
+                format!(
+                    "[{}]({}/proposal/{})",
+                    proposal.key, config.discord.ui_base_url, proposal.key
+                ),
+                false,
+            );
+            e.field("name", &proposal.proposal.name, false);
+            e.field("voting ends", ends_at.to_rfc2822(), false);
+            e.field(
+                "time left",
+                tulip_realms_sdk::utils::humanize_duration(ends_at.signed_duration_since(now)),
+                false,
+            );
+            e
+        })
+    })
+    .await
+    {
+        Ok(_) => {
+            status
+                .metrics
+                .notifications_sent
+                .fetch_add(1, Ordering::Relaxed);
+            log::info!("sent final reminder for proposal {}", proposal.key);
+            true
+        }
+        Err(err) => {
+            handle_send_error(ctx, owners, status, channel, err).await;
+            false
         }
     }
 }
 
+/// returns the description shown in new proposal notifications, truncated to `max_chars`. when
+/// `resolve` is set the content linked to by the description is fetched, falling back to the
+/// raw link on failure
+async fn proposal_description(
+    proposal: &ProposalV2Wrapper,
+    resolve: bool,
+    max_chars: usize,
+) -> String {
+    if resolve && !proposal.proposal.description_link.trim().is_empty() {
+        match tulip_realms_sdk::utils::resolve_description(&proposal.proposal.description_link)
+            .await
+        {
+            Ok(excerpt) => {
+                return tulip_realms_sdk::utils::truncate_chars(&excerpt, max_chars).to_string()
+            }
+            Err(err) => log::warn!("failed to resolve proposal description {:#}", err),
+        }
+    }
+    proposal.description_or_default(max_chars)
+}
+
+/// prepends a mention of the role to the message content, permitting only that role to be
+/// pinged. does nothing when `role_id` is 0
+fn mention_role<'a, 'b>(m: &'b mut CreateMessage<'a>, role_id: u64) -> &'b mut CreateMessage<'a> {
+    if role_id == 0 {
+        return m;
+    }
+    m.content(format!("<@&{}>", role_id));
+    m.allowed_mentions(|am| am.empty_parse().roles(vec![role_id]))
+}
+
+/// logs a failure to send a message, and when the failure is caused by the bot lacking
+/// permissions in the channel, direct messages the bot owners. owners are only notified
+/// once to avoid spamming them every iteration of the worker loop
+async fn handle_send_error(
+    ctx: &Context,
+    owners: &HashSet<UserId>,
+    status: &WorkerStatus,
+    channel: ChannelId,
+    err: SerenityError,
+) {
+    log::error!("failed to send message to {}: {:#?}", channel, err);
+    if !is_permission_error(&err) || status.permission_alert_sent.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let content = format!(
+        "failed to send a message to <#{}> due to missing permissions. please ensure the bot \
+         has the View Channel, Send Messages, and Embed Links permissions in the channel: {}",
+        channel, err
+    );
+    for owner in owners.iter() {
+        let dm_channel = match owner.create_dm_channel(ctx).await {
+            Ok(dm_channel) => dm_channel,
+            Err(err) => {
+                log::error!("failed to create dm channel with {}: {:#?}", owner, err);
+                continue;
+            }
+        };
+        if let Err(err) = dm_channel.say(ctx, &content).await {
+            log::error!("failed to send dm to {}: {:#?}", owner, err);
+        }
+    }
+}
+
+/// returns true if the error was caused by the bot lacking permissions in a channel
+fn is_permission_error(err: &SerenityError) -> bool {
+    match err {
+        SerenityError::Model(ModelError::InvalidPermissions(_)) => true,
+        SerenityError::Http(err) => match err.as_ref() {
+            // 50001: missing access, 50013: missing permissions
+            HttpError::UnsuccessfulRequest(response) => {
+                response.status_code == StatusCode::FORBIDDEN
+                    || matches!(response.error.code, 50001 | 50013)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 #[async_trait]
 impl EventHandler for Handler {
     // use this to spawn a task to log messages
     async fn ready(&self, ctx: Context, _ready: Ready) {
         info!("Connected as {}", _ready.user.name);
-        self.handle_ready(ctx);
+        if let Err(err) = interactions::register_commands(&ctx).await {
+            error!("failed to register slash commands {:#?}", err);
+        }
+        self.handle_ready(ctx).await;
+    }
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        interactions::handle_interaction(&ctx, &self.owners, interaction).await;
     }
     async fn cache_ready(&self, ctx: Context, _guilds: Vec<GuildId>) {
-        self.handle_ready(ctx);
+        self.handle_ready(ctx).await;
     }
     async fn resume(&self, ctx: Context, _: ResumedEvent) {
-        self.handle_ready(ctx);
+        self.handle_ready(ctx).await;
         info!("Resumed");
     }
 }
 
 #[group]
+#[commands(config, proposals, refresh, results, selftest, status)]
 struct General;
 
 pub async fn start_discord_bot(
     config: &Arc<config::Configuration>,
     exit_chan: crossbeam_channel::Receiver<bool>,
 ) -> Result<()> {
+    config.validate()?;
     info!("starting bot");
 
+    // the database is shared between the worker loop and commands, as sled
+    // only allows a single handle to be opened for a given path
+    let db = tulip_realms_sdk::Database::new(config.db_opts.clone())?;
+    let status = Arc::new(WorkerStatus::new(config.realms.len()));
+
     let http = Http::new(&config.discord.bot_token);
 
     // We will fetch your bot's owners and id
@@ -556,8 +1903,14 @@ pub async fn start_discord_bot(
         Err(why) => panic!("Could not access application info: {:?}", why),
     };
 
+    // each realm's worker loop subscribes to a broadcaster owned by the bot, which the exit
+    // signal received on `exit_chan` is forwarded to by `forward_exit_signal`
     let mut broadcaster = channels::broadcast::UnboundedBroadcast::new();
-    let subscriber = broadcaster.subscribe();
+    let exit_chans = config
+        .realms
+        .iter()
+        .map(|_| broadcaster.subscribe())
+        .collect();
     // Create the framework
     let framework = StandardFramework::new()
         .configure(|c| {
@@ -565,49 +1918,95 @@ pub async fn start_discord_bot(
                 .allow_dm(false)
                 .ignore_bots(true)
                 .allowed_channels(
-                    vec![ChannelId(config.discord.status_channel)]
-                        .into_iter()
+                    // commands are accepted in the status channel, and each realm's channel
+                    std::iter::once(config.discord.status_channel)
+                        .chain(config.realms.iter().filter_map(|realm| realm.channel_id))
+                        .filter(|channel| *channel != 0)
+                        .map(ChannelId)
                         .collect(),
                 )
                 .with_whitespace(true)
                 .on_mention(Some(serenity::model::id::UserId(_bot_id.0)))
-                .owners(owners)
+                .owners(owners.clone())
         })
+        .on_dispatch_error(dispatch_error)
         .group(&GENERAL_GROUP);
 
+    let is_loop_running = Arc::new(AtomicBool::new(false));
     // create the intents
     let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
 
     // initialize the framework, and event handler
     let mut client = Client::builder(&config.discord.bot_token, intents)
         .event_handler(Handler {
-            is_loop_running: Arc::new(AtomicBool::new(false)),
+            is_loop_running: is_loop_running.clone(),
             config: Arc::clone(config),
-            exit_chan: subscriber,
+            db: db.clone(),
+            status: status.clone(),
+            owners: owners.clone(),
+            exit_chans,
         })
         .framework(framework)
         .await?;
     {
         let mut data = client.data.write().await;
         data.insert::<ShardManagerContainer>(client.shard_manager.clone());
+        data.insert::<ConfigContainer>(Arc::clone(config));
+        data.insert::<DatabaseContainer>(db);
+        data.insert::<StatusContainer>(status.clone());
+    }
+
+    if let Some(metrics_port) = config.metrics_port {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", metrics_port))
+            .await
+            .with_context(|| format!("failed to bind metrics port {}", metrics_port))?;
+        info!("serving metrics on port {}", metrics_port);
+        let status = status.clone();
+        tokio::task::spawn(async move {
+            if let Err(err) = metrics::serve(listener, status).await {
+                error!("metrics server failed {:#?}", err);
+            }
+        });
+    }
+
+    if let Some(health_port) = config.health_port {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", health_port))
+            .await
+            .with_context(|| format!("failed to bind health port {}", health_port))?;
+        info!("serving health checks on port {}", health_port);
+        let status = status.clone();
+        // the worker loop is considered stuck once it misses two iterations
+        let max_age = config.discord.worker_loop_frequency as i64 * 2;
+        tokio::task::spawn(async move {
+            if let Err(err) = health::serve(listener, status, max_age).await {
+                error!("health check server failed {:#?}", err);
+            }
+        });
     }
 
     let shard_manager = client.shard_manager.clone();
     tokio::spawn(async move {
-        select! {
-            recv(exit_chan) -> _msg => {
-                warn!("received exit signal");
-                // todo(bonedaddy): should we add a waitgroup here
-                if let Err(err) = broadcaster.send(true) {
-                    error!("discord bot failed to notify workers to exit {:#?}", err);
-                }
-                // hacky workaround to give worker loops time to exit
-                // definitely needs to have some better thread synchronization
-                std::thread::sleep(std::time::Duration::from_secs(5));
-                shard_manager.lock().await.shutdown_all().await;
-                info!("shutdown finalized, goodbye...")
+        if !forward_exit_signal(exit_chan, broadcaster).await {
+            return;
+        }
+        // wait for the worker loops to finish their current iteration and exit
+        let workers_exited = async {
+            while status.active_workers.load(Ordering::SeqCst) > 0 {
+                status.worker_exited.notified().await;
             }
+        };
+        if is_loop_running.load(Ordering::SeqCst)
+            && tokio::time::timeout(WORKER_EXIT_TIMEOUT, workers_exited)
+                .await
+                .is_err()
+        {
+            warn!(
+                "worker loops did not exit within {:?}, shutting down anyway",
+                WORKER_EXIT_TIMEOUT
+            );
         }
+        shard_manager.lock().await.shutdown_all().await;
+        info!("shutdown finalized, goodbye...")
     });
     if let Err(why) = client.start().await {
         error!("Client error: {:?}", why);
@@ -615,3 +2014,311 @@ pub async fn start_discord_bot(
 
     Ok(())
 }
+
+/// waits for the exit signal sent by the signal handler on `exit_chan`, and forwards it to the
+/// worker loop, which is subscribed to `broadcaster`. returns false if `exit_chan` was
+/// disconnected without an exit signal being sent, in which case nothing is forwarded
+async fn forward_exit_signal(
+    exit_chan: crossbeam_channel::Receiver<bool>,
+    mut broadcaster: channels::broadcast::UnboundedBroadcast<bool>,
+) -> bool {
+    // wait for the exit signal on a blocking thread, as receiving from the
+    // crossbeam channel would otherwise block a runtime thread
+    match tokio::task::spawn_blocking(move || exit_chan.recv()).await {
+        Ok(Ok(_)) => (),
+        Ok(Err(err)) => {
+            error!(
+                "exit channel disconnected, exit signals will not be received {:#?}",
+                err
+            );
+            return false;
+        }
+        Err(err) => {
+            error!("failed to wait for exit signal {:#?}", err);
+            return false;
+        }
+    }
+    warn!("received exit signal");
+    if broadcaster.send(true) == 0 {
+        error!("discord bot failed to notify workers to exit, no workers are subscribed");
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+    /// deserializes an account from zeroed bytes, giving an empty value for types
+    /// which do not implement Default
+    pub fn zeroed<T: borsh::BorshDeserialize>() -> T {
+        solana_program::borsh::try_from_slice_unchecked(&[0_u8; 1024]).unwrap()
+    }
+    #[tokio::test]
+    async fn test_exit_signal_reaches_worker_loop() {
+        // the broadcaster the cli's signal handler sends on
+        let mut signal_broadcaster = channels::broadcast::UnboundedBroadcast::new();
+        let exit_chan = signal_broadcaster.subscribe();
+        // the broadcaster the bot forwards the exit signal to the worker loop on
+        let mut worker_broadcaster = channels::broadcast::UnboundedBroadcast::new();
+        let worker_exit_chan = worker_broadcaster.subscribe();
+        let status = Arc::new(WorkerStatus::default());
+        let worker = {
+            let status = status.clone();
+            tokio::task::spawn(async move {
+                run_worker_loop(worker_exit_chan, &status, 0, 1, 0, || async {}).await;
+            })
+        };
+        let forwarder = tokio::task::spawn(forward_exit_signal(exit_chan, worker_broadcaster));
+        assert_eq!(signal_broadcaster.send(true), 1);
+        assert!(forwarder.await.unwrap());
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            status.worker_exited.notified(),
+        )
+        .await
+        .expect("worker loop did not receive the exit signal");
+        worker.await.unwrap();
+
+        // a disconnected exit channel is not mistaken for an exit signal
+        let mut signal_broadcaster = channels::broadcast::UnboundedBroadcast::<bool>::new();
+        let exit_chan = signal_broadcaster.subscribe();
+        let mut worker_broadcaster = channels::broadcast::UnboundedBroadcast::new();
+        let worker_exit_chan = worker_broadcaster.subscribe();
+        drop(signal_broadcaster);
+        assert!(!forward_exit_signal(exit_chan, worker_broadcaster).await);
+        assert!(worker_exit_chan.is_empty());
+    }
+    #[tokio::test]
+    async fn test_worker_loop_signals_exit() {
+        let status = Arc::new(WorkerStatus::default());
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let iterations = Arc::new(AtomicU32::new(0));
+        let handle = {
+            let status = status.clone();
+            let iterations = iterations.clone();
+            tokio::task::spawn(async move {
+                run_worker_loop(receiver, &status, 0, 1, 0, || {
+                    let iterations = iterations.clone();
+                    async move {
+                        iterations.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+                .await;
+            })
+        };
+        while iterations.load(Ordering::SeqCst) == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        sender.send(true).unwrap();
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            status.worker_exited.notified(),
+        )
+        .await
+        .expect("worker loop did not signal that it exited");
+        handle.await.unwrap();
+        // the generation is bumped so the watchdog stops supervising the exited loop
+        assert_eq!(status.worker_generation.load(Ordering::SeqCst), 1);
+    }
+    struct FlakyFetcher {
+        failures: u32,
+        calls: AtomicU32,
+    }
+    impl AccountDataFetcher for FlakyFetcher {
+        fn fetch_account_data(&self, _key: &Pubkey) -> Result<Vec<u8>> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(anyhow!("connection reset"));
+            }
+            let mint = spl_token::state::Mint {
+                decimals: 6,
+                is_initialized: true,
+                ..Default::default()
+            };
+            let mut data = vec![0_u8; spl_token::state::Mint::LEN];
+            spl_token::state::Mint::pack(mint, &mut data[..]).unwrap();
+            Ok(data)
+        }
+    }
+    #[test]
+    fn test_mention_role() {
+        let mut m = CreateMessage::default();
+        mention_role(&mut m, 0);
+        assert!(m.0.get("content").is_none());
+        assert!(m.0.get("allowed_mentions").is_none());
+
+        let mut m = CreateMessage::default();
+        mention_role(&mut m, 42);
+        assert_eq!(m.0["content"], "<@&42>");
+        assert_eq!(m.0["allowed_mentions"]["roles"], serde_json::json!(["42"]));
+        assert_eq!(m.0["allowed_mentions"]["parse"], serde_json::json!([]));
+    }
+    #[test]
+    fn test_worker_loop_sleep_time() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        assert_eq!(worker_loop_sleep_time(600, 0, &mut rng), 600);
+        let samples: Vec<u64> = (0..1_000)
+            .map(|_| worker_loop_sleep_time(600, 30, &mut rng))
+            .collect();
+        assert!(samples.iter().all(|sleep| (570..=630).contains(sleep)));
+        // the offset spreads across the range in both directions
+        assert!(samples.iter().any(|sleep| *sleep < 590));
+        assert!(samples.iter().any(|sleep| *sleep > 610));
+        // the sleep never drops below a second, even when misconfigured
+        assert!((0..1_000).all(|_| worker_loop_sleep_time(5, 10, &mut rng) >= 1));
+    }
+    #[tokio::test]
+    async fn test_send_message_dry_run() {
+        // any request made with an invalid token fails, so a successful
+        // result means no message was sent
+        let http = Http::new("invalid");
+        let message = send_message(&http, true, ChannelId(1), |m| {
+            m.add_embed(|e| {
+                e.title("New Proposal Detected");
+                e
+            })
+        })
+        .await
+        .unwrap();
+        assert!(message.is_none());
+        assert!(
+            send_message(&http, false, ChannelId(1), |m| m.content("hello"))
+                .await
+                .is_err()
+        );
+    }
+    #[tokio::test]
+    async fn test_load_voter_mint_with_retry() {
+        let mint_key = Pubkey::new_unique();
+        let fetcher = FlakyFetcher {
+            failures: 2,
+            calls: AtomicU32::new(0),
+        };
+        let mint = load_voter_mint_with_retry(&fetcher, mint_key, 3)
+            .await
+            .unwrap();
+        assert_eq!(mint.decimals, 6);
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), 3);
+
+        let fetcher = FlakyFetcher {
+            failures: 3,
+            calls: AtomicU32::new(0),
+        };
+        let err = load_voter_mint_with_retry(&fetcher, mint_key, 3)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("after 3 attempts"));
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), 3);
+    }
+    /// returns a single deny vote, or an error when `fail` is set
+    struct VoteFetcher {
+        fail: bool,
+    }
+    impl tulip_realms_sdk::utils::VoteRecordFetcher for VoteFetcher {
+        fn fetch_vote_records(
+            &self,
+            proposal: Pubkey,
+        ) -> Result<Vec<tulip_realms_sdk::types::VoteRecordV2Wrapper>> {
+            if self.fail {
+                return Err(anyhow!("rpc unavailable"));
+            }
+            Ok(vec![tulip_realms_sdk::types::VoteRecordV2Wrapper {
+                vote_record: spl_governance::state::vote_record::VoteRecordV2 {
+                    account_type: spl_governance::state::enums::GovernanceAccountType::VoteRecordV2,
+                    proposal,
+                    governing_token_owner: Pubkey::new_unique(),
+                    is_relinquished: false,
+                    voter_weight: 2_000_000,
+                    vote: spl_governance::state::vote_record::Vote::Deny,
+                    reserved_v2: [0; 8],
+                },
+                key: Pubkey::new_unique(),
+            }])
+        }
+    }
+    #[test]
+    fn test_tally_proposal_votes() {
+        let db = tulip_realms_sdk::Database::new(tulip_sled_util::config::DbOpts {
+            path: "discord_tally_proposal_votes.db".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        let status = WorkerStatus::default();
+        let proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        let tally =
+            tally_proposal_votes(&db, &proposal, &VoteFetcher { fail: false }, &status).unwrap();
+        assert_eq!(tally.ui_amounts(6), (0.0, 2.0));
+        assert_eq!(tally.voter_count, 1);
+        assert_eq!(status.metrics.rpc_errors.load(Ordering::Relaxed), 0);
+
+        let missing = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        assert!(
+            tally_proposal_votes(&db, &missing, &VoteFetcher { fail: true }, &status).is_none()
+        );
+        assert_eq!(status.metrics.rpc_errors.load(Ordering::Relaxed), 1);
+        drop(db);
+        std::fs::remove_dir_all("discord_tally_proposal_votes.db").unwrap();
+    }
+    /// counts the events delivered to it
+    struct CountingSink {
+        events: Arc<AtomicU32>,
+    }
+    #[async_trait::async_trait]
+    impl NotificationSink for CountingSink {
+        async fn send_proposal_event(&self, _event: &ProposalEvent) -> Result<()> {
+            self.events.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+    #[tokio::test]
+    async fn test_send_notification() {
+        let events = Arc::new(AtomicU32::new(0));
+        let sinks: Vec<Box<dyn NotificationSink>> = vec![Box::new(CountingSink {
+            events: events.clone(),
+        })];
+        let event = ProposalEvent {
+            kind: ProposalEventKind::NewProposal,
+            realm_name: "Tulip Protocol".to_string(),
+            governance_key: Pubkey::new_unique().to_string(),
+            proposal_key: Pubkey::new_unique().to_string(),
+            name: "raise the fee".to_string(),
+            description: "no description provided".to_string(),
+            url: "https://realms.today/dao/tulip".to_string(),
+            approval_votes: 0.0,
+            deny_votes: 0.0,
+            abstain_votes: 0.0,
+            voter_count: None,
+            vote_ends_at: None,
+            result: None,
+        };
+        // requests made with an invalid token fail, so the sinks aren't sent the event, leaving
+        // it to be delivered once the message is sent on a later iteration
+        let http = Http::new("invalid");
+        assert!(
+            send_notification(&http, false, ChannelId(1), &sinks, &event, |m| m
+                .content("hello"))
+            .await
+            .is_err()
+        );
+        assert_eq!(events.load(Ordering::SeqCst), 0);
+
+        assert!(
+            send_notification(&http, true, ChannelId(1), &sinks, &event, |m| m
+                .content("hello"))
+            .await
+            .unwrap()
+            .is_none()
+        );
+        // sinks are skipped in dry run mode, as discord messages are
+        assert_eq!(events.load(Ordering::SeqCst), 0);
+        dispatch_event(&sinks, false, &event).await;
+        assert_eq!(events.load(Ordering::SeqCst), 1);
+    }
+}
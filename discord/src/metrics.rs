@@ -0,0 +1,126 @@
+//! prometheus metrics for the worker loop, served over a minimal http server
+//! so that instances which silently stop making progress can be alerted on
+
+use crate::server::{serve_http, HttpResponse};
+use crate::WorkerStatus;
+use anyhow::Result;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// counters and gauges exposed on the metrics endpoint
+#[derive(Default)]
+pub struct Metrics {
+    /// the number of iterations of the worker loop
+    pub worker_loop_iterations: AtomicU64,
+    /// the number of failed rpc requests
+    pub rpc_errors: AtomicU64,
+    /// the number of voting proposals being tracked for notifications
+    pub proposals_tracked: AtomicU64,
+    /// the number of proposal notifications posted to discord
+    pub notifications_sent: AtomicU64,
+}
+
+impl Metrics {
+    /// renders the metrics in the prometheus text exposition format
+    pub fn render(&self, last_loop_timestamp: i64) -> String {
+        let mut output = String::new();
+        let metrics: [(&str, &str, &str, i64); 5] = [
+            (
+                "worker_loop_iterations_total",
+                "counter",
+                "number of iterations of the worker loop",
+                self.worker_loop_iterations.load(Ordering::Relaxed) as i64,
+            ),
+            (
+                "rpc_errors_total",
+                "counter",
+                "number of failed rpc requests",
+                self.rpc_errors.load(Ordering::Relaxed) as i64,
+            ),
+            (
+                "proposals_tracked",
+                "gauge",
+                "number of voting proposals tracked for notifications",
+                self.proposals_tracked.load(Ordering::Relaxed) as i64,
+            ),
+            (
+                "notifications_sent_total",
+                "counter",
+                "number of proposal notifications posted",
+                self.notifications_sent.load(Ordering::Relaxed) as i64,
+            ),
+            (
+                "last_loop_timestamp",
+                "gauge",
+                "unix timestamp at which the worker loop last started an iteration",
+                last_loop_timestamp,
+            ),
+        ];
+        for (name, kind, help, value) in metrics {
+            // writing to a string can't fail
+            let _ = writeln!(output, "# HELP {} {}", name, help);
+            let _ = writeln!(output, "# TYPE {} {}", name, kind);
+            let _ = writeln!(output, "{} {}", name, value);
+        }
+        output
+    }
+}
+
+/// serves the metrics of the worker loop on `/metrics` until the listener fails
+pub async fn serve(listener: TcpListener, status: Arc<WorkerStatus>) -> Result<()> {
+    serve_http(listener, move |path| {
+        if path != "/metrics" {
+            return HttpResponse::not_found();
+        }
+        HttpResponse {
+            status: "200 OK",
+            content_type: "text/plain; version=0.0.4",
+            body: status.metrics.render(status.last_run_at()),
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let status = Arc::new(WorkerStatus::new(1));
+        status
+            .metrics
+            .worker_loop_iterations
+            .fetch_add(3, Ordering::Relaxed);
+        status.metrics.rpc_errors.fetch_add(1, Ordering::Relaxed);
+        status.realm_last_run_at[0].store(1_650_000_000, Ordering::SeqCst);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::task::spawn(serve(listener, status));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("# TYPE worker_loop_iterations_total counter\n"));
+        assert!(response.contains("\nworker_loop_iterations_total 3\n"));
+        assert!(response.contains("\nrpc_errors_total 1\n"));
+        assert!(response.contains("\nproposals_tracked 0\n"));
+        assert!(response.contains("\nnotifications_sent_total 0\n"));
+        assert!(response.contains("# TYPE last_loop_timestamp gauge\n"));
+        assert!(response.contains("\nlast_loop_timestamp 1650000000\n"));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    }
+}
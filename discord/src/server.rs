@@ -0,0 +1,64 @@
+//! a minimal http server, used to serve the metrics and health endpoints without pulling in
+//! a web framework
+
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// the response to a request, written with `Connection: close`
+pub struct HttpResponse {
+    /// the status code and reason phrase, ie: `200 OK`
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn not_found() -> Self {
+        Self {
+            status: "404 Not Found",
+            content_type: "text/plain",
+            body: String::new(),
+        }
+    }
+    /// renders the response as sent on the wire
+    fn render(&self) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+/// accepts connections until the listener fails, responding to each request with the response
+/// `handler` returns for the requested path
+pub async fn serve_http<F>(listener: TcpListener, handler: F) -> Result<()>
+where
+    F: Fn(&str) -> HttpResponse + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let handler = handler.clone();
+        tokio::task::spawn(async move {
+            let mut buf = [0_u8; 1024];
+            let read = match stream.read(&mut buf).await {
+                Ok(read) => read,
+                Err(err) => {
+                    log::warn!("failed to read http request {:#?}", err);
+                    return;
+                }
+            };
+            let request = String::from_utf8_lossy(&buf[..read]);
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let response = handler(path);
+            if let Err(err) = stream.write_all(response.render().as_bytes()).await {
+                log::warn!("failed to write http response {:#?}", err);
+            }
+        });
+    }
+}
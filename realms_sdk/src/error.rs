@@ -0,0 +1,63 @@
+//! errors returned by the database, allowing consumers to tell missing entries apart
+//! from entries which fail to deserialize, and from rpc failures
+
+use borsh::BorshDeserialize;
+use solana_client::client_error::ClientError;
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RealmsError {
+    /// no entry is stored under the key, ie: the account was never cached
+    #[error("{kind} {key} not found")]
+    NotFound { kind: &'static str, key: Pubkey },
+    /// an entry is stored under the key, but doesn't match the layout of the requested type
+    #[error("failed to deserialize {kind} {key}")]
+    Deserialize {
+        kind: &'static str,
+        key: Pubkey,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("rpc request failed")]
+    Rpc(#[from] ClientError),
+    #[error("database is read-only")]
+    ReadOnly,
+    #[error("database error")]
+    Db(#[from] sled::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// deserializes from any stored bytes, used to check whether an entry exists when it fails to
+/// deserialize into the requested type, as the tree only reports that deserializing failed
+pub(crate) struct RawEntry;
+
+impl BorshDeserialize for RawEntry {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        if buf.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "entry is empty",
+            ));
+        }
+        *buf = &[];
+        Ok(RawEntry)
+    }
+}
+
+/// returns the error for an entry which failed to deserialize, given the result of reading
+/// the entry as a `RawEntry`
+pub(crate) fn entry_error(
+    kind: &'static str,
+    key: Pubkey,
+    raw: anyhow::Result<RawEntry>,
+    err: anyhow::Error,
+) -> RealmsError {
+    match raw {
+        Ok(_) => RealmsError::Deserialize {
+            kind,
+            key,
+            source: err.into(),
+        },
+        Err(_) => RealmsError::NotFound { kind, key },
+    }
+}
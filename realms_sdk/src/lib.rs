@@ -1,12 +1,15 @@
 //! disk backed cache for realms related accounts using sled
 
+pub mod error;
+pub mod notify;
+pub mod tools;
 pub mod types;
 pub mod utils;
 use crate::utils::governance_notif_cache_key;
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use chrono::prelude::*;
-use sled::IVec;
+use error::{entry_error, RawEntry};
 use solana_client::rpc_client::RpcClient;
 use solana_program::account_info::AccountInfo;
 use solana_program::account_info::IntoAccountInfo;
@@ -15,377 +18,2442 @@ use spl_governance::{
     state::{governance::GovernanceV2, proposal::ProposalV2, realm::RealmV2},
 };
 use static_pubkey::static_pubkey;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use tulip_sled_util::types::{DbKey, DbTrees};
-use types::NotifCacheEntry;
 use types::{
     get_governance_wrapper, get_proposal_wrapper, get_realm_wrapper, GovernanceV2Wrapper,
-    ProposalV2Wrapper, RealmV2Wrapper,
+    ProposalV2Wrapper, RealmV2Wrapper, VoteRecordV2Wrapper,
+};
+use types::{
+    AnnouncedProposal, CachedVoteRecords, DatabaseExport, DbStats, ExportedEntry, ImportSummary,
+    LegacyNotifCacheEntry, LegacyProposalTallyEntry, LegacyReminderNotifCacheEntry,
+    LegacyTupleNotifCacheEntry, NotifCacheEntry, PendingResult, ProposalNotifState,
+    ProposalStateTransition, ProposalTallyEntry, SchemaVersionEntry, SeedSummary, SelfTestEntry,
+    VoteTally,
 };
 
 pub const GOVERNANCE_TREE: &str = "governance_info";
 pub const PROPOSAL_TREE: &str = "proposal_info";
 pub const REALM_TREE: &str = "realm_info";
+pub const VOTE_TALLY_TREE: &str = "vote_tally_info";
+pub const ANNOUNCED_PROPOSAL_TREE: &str = "announced_proposals";
+pub const VOTE_RECORD_TREE: &str = "vote_records";
+pub const ANNOUNCED_RESULT_TREE: &str = "announced_results";
+pub const PENDING_RESULT_TREE: &str = "pending_results";
+pub const META_TREE: &str = "meta";
+/// version of the on-disk layout of the database, incremented whenever the layout of a stored
+/// type changes in a way which requires a migration
+pub const SCHEMA_VERSION: u32 = 2;
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+/// oldest schema version of exports accepted by `Database::import`. exports don't include the
+/// cached vote records, so their layout is unchanged since version 1
+const MIN_IMPORT_SCHEMA_VERSION: u32 = 1;
 pub const GOVERNANCE_PROGRAM: Pubkey =
     static_pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
 
+pub use error::RealmsError;
 pub use spl_governance;
 
-/// Database is the main embedded database object using sled db
+/// Database is the main embedded database object using sled db. entries are keyed by the
+/// pubkey of the account they describe, so a single database can be shared by several realms
 #[derive(Clone)]
 pub struct Database {
-    pub db: Arc<tulip_sled_util::Database>,
+    db: Arc<tulip_sled_util::Database>,
+    /// proposal indices resolved by `proposal_index`, keyed by proposal
+    proposal_indices: Arc<RwLock<HashMap<Pubkey, u32>>>,
+    /// when true, the methods which write to the database return an error
+    read_only: bool,
+    /// the copy of the database opened by `new_read_only`, removed once the last handle is
+    /// dropped. declared after `db` so the copy is closed before it is removed
+    snapshot: Option<Arc<Snapshot>>,
+}
+
+/// a temporary copy of a database directory, removed when dropped
+struct Snapshot {
+    path: std::path::PathBuf,
+}
+
+impl Snapshot {
+    /// copies the database directory at `path` into a new temporary directory
+    fn new(path: &str) -> Result<Self> {
+        let nanos = Utc::now().timestamp_nanos();
+        let snapshot = Snapshot {
+            path: std::env::temp_dir().join(format!(
+                "realms_bot_snapshot_{}_{}",
+                std::process::id(),
+                nanos
+            )),
+        };
+        copy_dir(std::path::Path::new(path), &snapshot.path)
+            .with_context(|| format!("failed to copy database at {}", path))?;
+        Ok(snapshot)
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_dir_all(&self.path) {
+            log::warn!(
+                "failed to remove database snapshot {}: {:#?}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// recursively copies the directory at `from` to `to`
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// fetches vote records through the database's vote record cache, only fetching them from
+/// `rpc` once the cached vote records are older than `max_age`
+pub struct CachedVoteRecordFetcher<'a, F: utils::VoteRecordFetcher> {
+    pub db: &'a Database,
+    pub max_age: chrono::Duration,
+    pub rpc: &'a F,
+}
+
+impl<'a, F: utils::VoteRecordFetcher> utils::VoteRecordFetcher for CachedVoteRecordFetcher<'a, F> {
+    fn fetch_vote_records(&self, proposal: Pubkey) -> Result<Vec<VoteRecordV2Wrapper>> {
+        Ok(self
+            .db
+            .get_cached_vote_records(proposal, self.max_age, self.rpc)?)
+    }
 }
 
 impl Database {
-    pub fn new(opts: tulip_sled_util::config::DbOpts) -> Result<Self> {
-        Ok(Self {
-            db: tulip_sled_util::Database::new(&opts)?,
-        })
+    /// opens the database at `opts.path`
+    ///
+    /// sled holds an exclusive lock on its directory, so if another bot instance is already
+    /// running against the same path a descriptive error is returned instead of sled's raw io error
+    ///
+    /// the schema version of the database is checked once opened, migrating databases
+    /// written by older versions of the bot
+    pub fn new(opts: tulip_sled_util::config::DbOpts) -> Result<Self, RealmsError> {
+        Self::open(opts, false)
+    }
+    /// opens the database at `opts.path` for reading, with the methods which write to the
+    /// database returning an error. databases written by older versions of the bot are
+    /// rejected instead of being migrated
+    ///
+    /// sled's lock is exclusive regardless of how the database is used, so a temporary copy of
+    /// the database directory is opened instead. this permits reading the database of a running
+    /// bot, with the copy reflecting the writes the bot flushed before it was taken
+    pub fn new_read_only(opts: tulip_sled_util::config::DbOpts) -> Result<Self, RealmsError> {
+        let snapshot = Snapshot::new(&opts.path)?;
+        let mut db = Self::open(
+            tulip_sled_util::config::DbOpts {
+                path: snapshot.path.to_string_lossy().to_string(),
+                ..opts
+            },
+            true,
+        )?;
+        db.snapshot = Some(Arc::new(snapshot));
+        Ok(db)
+    }
+    fn open(opts: tulip_sled_util::config::DbOpts, read_only: bool) -> Result<Self, RealmsError> {
+        let db = match tulip_sled_util::Database::new(&opts) {
+            Ok(db) => Self {
+                db,
+                proposal_indices: Default::default(),
+                read_only,
+                snapshot: None,
+            },
+            Err(err) => {
+                if format!("{:#}", err).contains("could not acquire lock") {
+                    return Err(anyhow!(
+                        "another instance is already using the database at {}. hint: stop the other instance, or point db_opts.path at a different directory",
+                        opts.path
+                    )
+                    .into());
+                } else {
+                    return Err(err.into());
+                }
+            }
+        };
+        db.check_schema_version()
+            .with_context(|| format!("failed to check schema of database at {}", opts.path))?;
+        Ok(db)
+    }
+    /// returns the schema version stored in the database. databases which have no stored
+    /// version were either just created, in which case None is returned, or were written
+    /// before the schema version was tracked, in which case 0 is returned
+    pub fn schema_version(&self) -> Result<Option<u32>, RealmsError> {
+        let entry: Result<SchemaVersionEntry> = self
+            .db
+            .open_tree(DbTrees::Custom(META_TREE))?
+            .deserialize(SCHEMA_VERSION_KEY);
+        if let Ok(entry) = entry {
+            return Ok(Some(entry.schema_version));
+        }
+        for tree_name in [REALM_TREE, GOVERNANCE_TREE, PROPOSAL_TREE] {
+            if self
+                .db
+                .open_tree(DbTrees::Custom(tree_name))?
+                .iter()
+                .next()
+                .is_some()
+            {
+                return Ok(Some(0));
+            }
+        }
+        Ok(None)
+    }
+    fn set_schema_version(&self, schema_version: u32) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        self.db
+            .open_tree(DbTrees::Custom(META_TREE))?
+            .insert(&SchemaVersionEntry { schema_version })?;
+        Ok(())
+    }
+    /// compares the stored schema version against `SCHEMA_VERSION`, migrating the database
+    /// if it was written by an older version of the bot
+    fn check_schema_version(&self) -> Result<(), RealmsError> {
+        if self.read_only {
+            return match self.schema_version()? {
+                None | Some(SCHEMA_VERSION) => Ok(()),
+                Some(schema_version) => Err(anyhow!(
+                    "database schema version {} differs from the supported version {}, and can't be migrated when read-only",
+                    schema_version,
+                    SCHEMA_VERSION
+                )
+                .into()),
+            };
+        }
+        match self.schema_version()? {
+            None => self.set_schema_version(SCHEMA_VERSION),
+            Some(SCHEMA_VERSION) => Ok(()),
+            Some(schema_version) => self.migrate(schema_version, SCHEMA_VERSION),
+        }
+    }
+    /// migrates the database from schema version `from` to `to`, one version at a time. trees
+    /// whose entries can't be converted are dropped, to be re-fetched by the worker loop
+    pub fn migrate(&self, from: u32, to: u32) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        if from > to {
+            return Err(anyhow!(
+                "database schema version {} is newer than the supported version {}. hint: upgrade the bot, or point db_opts.path at a different directory",
+                from,
+                to
+            )
+            .into());
+        }
+        for version in from..to {
+            log::warn!(
+                "migrating database from schema version {} to {}",
+                version,
+                version + 1
+            );
+            match version {
+                // databases written before the schema version was tracked. entries using the
+                // legacy notification cache, and vote tally layouts are converted when read,
+                // so only the cached vote records are dropped
+                0 => self
+                    .db
+                    .open_tree(DbTrees::Custom(VOTE_RECORD_TREE))?
+                    .clear()?,
+                // cached vote records are stored along with the keys of their accounts
+                1 => self
+                    .db
+                    .open_tree(DbTrees::Custom(VOTE_RECORD_TREE))?
+                    .clear()?,
+                _ => {
+                    return Err(anyhow!(
+                        "no migration from database schema version {} to {}",
+                        version,
+                        version + 1
+                    )
+                    .into())
+                }
+            }
+            self.set_schema_version(version + 1)?;
+        }
+        Ok(())
+    }
+    /// returns true if the database was opened with `new_read_only`
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+    /// returns an error if the database was opened with `new_read_only`
+    fn ensure_writable(&self) -> Result<(), RealmsError> {
+        if self.read_only {
+            return Err(RealmsError::ReadOnly);
+        }
+        Ok(())
+    }
+    /// flushes pending writes to disk
+    pub fn flush(&self) -> Result<(), RealmsError> {
+        self.db.flush().context("failed to flush database")?;
+        Ok(())
+    }
+    /// returns the size of the database on disk in bytes
+    pub fn size_on_disk(&self) -> Result<u64, RealmsError> {
+        Ok(self.db.size_on_disk()?)
+    }
+    /// flushes pending writes to disk, logging the size of the database before and after.
+    ///
+    /// sled doesn't expose compaction, instead reclaiming the space of rewritten segments in the
+    /// background as writes are flushed, so flushing is the only maintenance which can be triggered
+    pub fn maintenance(&self) -> Result<(), RealmsError> {
+        let size_before = self.size_on_disk()?;
+        let flushed = self.db.flush().context("failed to flush database")?;
+        let size_after = self.size_on_disk()?;
+        log::info!(
+            "flushed {} bytes, database size on disk {} -> {} bytes",
+            flushed,
+            size_before,
+            size_after
+        );
+        Ok(())
+    }
+    /// verifies the database is writable by inserting an entry, and reading it back
+    pub fn check_writable(&self) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        let tree = self.db.open_tree(DbTrees::Default)?;
+        let entry = SelfTestEntry {
+            written_at: Utc::now().timestamp(),
+        };
+        tree.insert(&entry)?;
+        let read: SelfTestEntry = tree.deserialize(entry.key()?)?;
+        if read.ne(&entry) {
+            return Err(anyhow!("read back {:?} after writing {:?}", read, entry).into());
+        }
+        Ok(())
     }
-    pub fn insert_governance(&self, governance: &GovernanceV2Wrapper) -> Result<()> {
+    pub fn insert_governance(&self, governance: &GovernanceV2Wrapper) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
         self.db
             .open_tree(DbTrees::Custom(GOVERNANCE_TREE))?
             .insert(governance)?;
         Ok(())
     }
-    pub fn insert_proposal(&self, proposal: &ProposalV2Wrapper) -> Result<()> {
+    pub fn insert_proposal(&self, proposal: &ProposalV2Wrapper) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
         self.db
             .open_tree(DbTrees::Custom(PROPOSAL_TREE))?
             .insert(proposal)?;
         Ok(())
     }
-    pub fn insert_realm(&self, realm: &RealmV2Wrapper) -> Result<()> {
+    /// inserts the governance only if it differs from the stored entry, returning true
+    /// if it was written
+    pub fn update_governance_if_changed(
+        &self,
+        governance: &GovernanceV2Wrapper,
+    ) -> Result<bool, RealmsError> {
+        self.insert_if_changed(GOVERNANCE_TREE, governance.key, governance)
+    }
+    /// inserts the proposal only if it differs from the stored entry, returning true
+    /// if it was written
+    pub fn update_proposal_if_changed(
+        &self,
+        proposal: &ProposalV2Wrapper,
+    ) -> Result<bool, RealmsError> {
+        self.insert_if_changed(PROPOSAL_TREE, proposal.key, proposal)
+    }
+    /// compares the borsh encoding of the value against the stored entry, as the wrapped
+    /// account types don't implement PartialEq, only writing the value when they differ
+    fn insert_if_changed<T: DbKey + BorshSerialize + BorshDeserialize>(
+        &self,
+        tree_name: &'static str,
+        key: Pubkey,
+        value: &T,
+    ) -> Result<bool, RealmsError> {
+        self.ensure_writable()?;
+        let tree = self.db.open_tree(DbTrees::Custom(tree_name))?;
+        let existing: Result<T> = tree.deserialize(key);
+        if let Ok(existing) = existing {
+            if existing
+                .try_to_vec()
+                .context("failed to serialize stored entry")?
+                == value.try_to_vec().context("failed to serialize entry")?
+            {
+                return Ok(false);
+            }
+        }
+        tree.insert(value)?;
+        Ok(true)
+    }
+    pub fn insert_realm(&self, realm: &RealmV2Wrapper) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
         self.db
             .open_tree(DbTrees::Custom(REALM_TREE))?
             .insert(realm)?;
         Ok(())
     }
-    pub fn insert_notif_cache_entry(&self, cache_entry: &NotifCacheEntry) -> Result<()> {
+    pub fn insert_notif_cache_entry(
+        &self,
+        cache_entry: &NotifCacheEntry,
+    ) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
         self.db.open_tree(DbTrees::Default)?.insert(cache_entry)?;
         Ok(())
     }
-    pub fn get_proposal(&self, key: Pubkey) -> Result<ProposalV2Wrapper> {
+    pub fn insert_proposal_tally(
+        &self,
+        tally_entry: &ProposalTallyEntry,
+    ) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
         self.db
-            .open_tree(DbTrees::Custom(PROPOSAL_TREE))?
-            .deserialize(key)
+            .open_tree(DbTrees::Custom(VOTE_TALLY_TREE))?
+            .insert(tally_entry)?;
+        Ok(())
+    }
+    pub fn get_proposal(&self, key: Pubkey) -> Result<ProposalV2Wrapper, RealmsError> {
+        self.get_entry("proposal", PROPOSAL_TREE, key)
+    }
+    pub fn get_realm(&self, key: Pubkey) -> Result<RealmV2Wrapper, RealmsError> {
+        self.get_entry("realm", REALM_TREE, key)
+    }
+    pub fn get_governance(&self, key: Pubkey) -> Result<GovernanceV2Wrapper, RealmsError> {
+        self.get_entry("governance", GOVERNANCE_TREE, key)
+    }
+    /// returns the entry stored under `key`, distinguishing entries which aren't stored from
+    /// those which fail to deserialize
+    fn get_entry<T: BorshDeserialize>(
+        &self,
+        kind: &'static str,
+        tree_name: &'static str,
+        key: Pubkey,
+    ) -> Result<T, RealmsError> {
+        let tree = self.db.open_tree(DbTrees::Custom(tree_name))?;
+        tree.deserialize(key)
+            .map_err(|err| entry_error(kind, key, tree.deserialize(key), err))
+    }
+    pub fn get_proposal_tally(&self, key: Pubkey) -> Result<ProposalTallyEntry, RealmsError> {
+        let tree = self.db.open_tree(DbTrees::Custom(VOTE_TALLY_TREE))?;
+        match tree.deserialize(key) {
+            Ok(tally_entry) => Ok(tally_entry),
+            Err(err) => {
+                // entries written before `abstain_votes` was added use the legacy layout
+                let legacy: LegacyProposalTallyEntry = if let Ok(legacy) = tree.deserialize(key) {
+                    legacy
+                } else {
+                    return Err(entry_error("vote tally", key, tree.deserialize(key), err));
+                };
+                Ok(legacy.into())
+            }
+        }
+    }
+    /// records that a new proposal notification was sent for the proposal
+    pub fn mark_proposal_announced(
+        &self,
+        proposal_key: Pubkey,
+        announced_at: i64,
+    ) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        self.db
+            .open_tree(DbTrees::Custom(ANNOUNCED_PROPOSAL_TREE))?
+            .insert(&AnnouncedProposal {
+                proposal_key,
+                announced_at,
+            })?;
+        Ok(())
     }
-    pub fn get_governance_notif_cache(&self, governance_key: Pubkey) -> Result<NotifCacheEntry> {
-        let notif_cache = self
+    /// returns true if a new proposal notification was previously sent for the proposal
+    pub fn is_proposal_announced(&self, proposal_key: Pubkey) -> Result<bool, RealmsError> {
+        let announced: Result<AnnouncedProposal> = self
             .db
-            .open_tree(DbTrees::Default)?
-            .deserialize(governance_notif_cache_key(governance_key))?;
+            .open_tree(DbTrees::Custom(ANNOUNCED_PROPOSAL_TREE))?
+            .deserialize(proposal_key);
+        Ok(announced.is_ok())
+    }
+    /// returns the vote records of the proposal, only fetching them from rpc if there are no
+    /// cached vote records or they were fetched more than `max_age` ago. fetched vote records
+    /// aren't cached when the database is read-only
+    pub fn get_cached_vote_records(
+        &self,
+        proposal_key: Pubkey,
+        max_age: chrono::Duration,
+        rpc: &impl utils::VoteRecordFetcher,
+    ) -> Result<Vec<VoteRecordV2Wrapper>, RealmsError> {
+        let tree = self.db.open_tree(DbTrees::Custom(VOTE_RECORD_TREE))?;
+        let now = Utc::now();
+        let cached: Result<CachedVoteRecords> = tree.deserialize(proposal_key);
+        if let Ok(cached) = cached {
+            let age = now.signed_duration_since(utils::date_time_from_timestamp(cached.fetched_at));
+            if age < max_age {
+                return Ok(cached.vote_records);
+            }
+        }
+        let vote_records = rpc
+            .fetch_vote_records(proposal_key)
+            .with_context(|| format!("failed to fetch vote records for {}", proposal_key))?;
+        if !self.read_only {
+            tree.insert(&CachedVoteRecords {
+                proposal_key,
+                fetched_at: now.timestamp(),
+                vote_records: vote_records.clone(),
+            })?;
+        }
+        Ok(vote_records)
+    }
+    /// records that the result of the proposal was announced once voting concluded, removing
+    /// it from the pending results
+    pub fn mark_result_announced(
+        &self,
+        proposal_key: Pubkey,
+        announced_at: i64,
+    ) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        self.db
+            .open_tree(DbTrees::Custom(ANNOUNCED_RESULT_TREE))?
+            .insert(&AnnouncedProposal {
+                proposal_key,
+                announced_at,
+            })?;
+        let pending_tree = self.db.open_tree(DbTrees::Custom(PENDING_RESULT_TREE))?;
+        let pending: Result<PendingResult> = pending_tree.deserialize(proposal_key);
+        if let Ok(pending) = pending {
+            pending_tree.delete(&pending)?;
+        }
+        Ok(())
+    }
+    /// records that voting on the proposal concluded, queueing its result to be announced
+    /// unless it already was
+    pub fn mark_result_pending(&self, pending: &PendingResult) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        if self.is_result_announced(pending.proposal_key)? {
+            return Ok(());
+        }
+        self.db
+            .open_tree(DbTrees::Custom(PENDING_RESULT_TREE))?
+            .insert(pending)?;
+        Ok(())
+    }
+    /// returns the concluded proposals of the governance whose results have yet to be announced,
+    /// oldest first
+    pub fn list_pending_results(
+        &self,
+        governance_key: Pubkey,
+    ) -> Result<Vec<PendingResult>, RealmsError> {
+        let mut pending: Vec<PendingResult> = self
+            .list_tree_entries(PENDING_RESULT_TREE)?
+            .0
+            .into_iter()
+            .filter(|pending: &PendingResult| pending.governance_key.eq(&governance_key))
+            .collect();
+        pending.sort_by_key(|pending| pending.concluded_at);
+        Ok(pending)
+    }
+    /// returns true if the result of the proposal was previously announced
+    pub fn is_result_announced(&self, proposal_key: Pubkey) -> Result<bool, RealmsError> {
+        let announced: Result<AnnouncedProposal> = self
+            .db
+            .open_tree(DbTrees::Custom(ANNOUNCED_RESULT_TREE))?
+            .deserialize(proposal_key);
+        Ok(announced.is_ok())
+    }
+    /// removes the proposal from the database, returning an error if it is not stored
+    pub fn delete_proposal(&self, key: Pubkey) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        let proposal = self.get_proposal(key)?;
+        self.db
+            .open_tree(DbTrees::Custom(PROPOSAL_TREE))?
+            .delete(&proposal)?;
+        Ok(())
+    }
+    /// removes the governance from the database, returning an error if it is not stored
+    pub fn delete_governance(&self, key: Pubkey) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        let governance = self.get_governance(key)?;
+        self.db
+            .open_tree(DbTrees::Custom(GOVERNANCE_TREE))?
+            .delete(&governance)?;
+        Ok(())
+    }
+    /// removes the realm from the database, returning an error if it is not stored
+    pub fn delete_realm(&self, key: Pubkey) -> Result<(), RealmsError> {
+        self.ensure_writable()?;
+        let realm = self.get_realm(key)?;
+        self.db
+            .open_tree(DbTrees::Custom(REALM_TREE))?
+            .delete(&realm)?;
+        Ok(())
+    }
+    /// records the tally computed from the most recently fetched vote records of a proposal.
+    /// this is a no-op once the proposal has a final tally
+    pub fn record_vote_tally(
+        &self,
+        proposal_key: Pubkey,
+        tally: VoteTally,
+    ) -> Result<(), RealmsError> {
+        let mut tally_entry =
+            self.get_proposal_tally(proposal_key)
+                .unwrap_or_else(|_| ProposalTallyEntry {
+                    proposal_key,
+                    ..Default::default()
+                });
+        if tally_entry.final_tally.is_some() {
+            return Ok(());
+        }
+        tally_entry.last_tally = tally;
+        self.insert_proposal_tally(&tally_entry)
+    }
+    /// snapshots the last recorded tally of a proposal as its final tally, returning the final
+    /// tally. returns None if no tally was ever recorded for the proposal
+    pub fn finalize_vote_tally(
+        &self,
+        proposal_key: Pubkey,
+    ) -> Result<Option<VoteTally>, RealmsError> {
+        let mut tally_entry = match self.get_proposal_tally(proposal_key) {
+            Ok(tally_entry) => tally_entry,
+            Err(_) => return Ok(None),
+        };
+        if tally_entry.final_tally.is_none() {
+            tally_entry.final_tally = Some(tally_entry.last_tally.clone());
+            self.insert_proposal_tally(&tally_entry)?;
+        }
+        Ok(tally_entry.final_tally)
+    }
+    pub fn get_governance_notif_cache(
+        &self,
+        governance_key: Pubkey,
+    ) -> Result<NotifCacheEntry, RealmsError> {
+        let tree = self.db.open_tree(DbTrees::Default)?;
+        let key = governance_notif_cache_key(governance_key);
+        let notif_cache: NotifCacheEntry = match tree.deserialize(&key) {
+            Ok(notif_cache) => notif_cache,
+            Err(err) => {
+                // older entries use one of the legacy layouts, from newest to oldest: those
+                // written before `result_announced` was added, those which track proposals as
+                // tuples, and those written before `proposal_threads` was added
+                let reminder_legacy: Result<LegacyReminderNotifCacheEntry> = tree.deserialize(&key);
+                let tuple_legacy: Result<LegacyTupleNotifCacheEntry> = tree.deserialize(&key);
+                let legacy: Result<LegacyNotifCacheEntry> = tree.deserialize(&key);
+                match (reminder_legacy, tuple_legacy, legacy) {
+                    (Ok(legacy), _, _) => legacy.into(),
+                    (_, Ok(legacy), _) => legacy.into(),
+                    (_, _, Ok(legacy)) => legacy.into(),
+                    _ => {
+                        return Err(entry_error(
+                            "notification cache",
+                            governance_key,
+                            tree.deserialize(&key),
+                            err,
+                        ))
+                    }
+                }
+            }
+        };
         Ok(notif_cache)
     }
-    pub fn list_governances(&self) -> Result<Vec<GovernanceV2Wrapper>> {
-        let tree = self.db.open_tree(DbTrees::Custom(GOVERNANCE_TREE))?;
-        let keys: Vec<IVec> = tree
+    /// returns the notification cache of the governance, inserting an empty entry when the
+    /// governance has none yet. `proposals_count` is used as the initial `last_proposals_count`
+    /// so that existing proposals are not announced as new
+    pub fn get_or_init_notif_cache(
+        &self,
+        governance_key: Pubkey,
+        proposals_count: u32,
+    ) -> Result<NotifCacheEntry, RealmsError> {
+        match self.get_governance_notif_cache(governance_key) {
+            Ok(notif_cache) => Ok(notif_cache),
+            Err(err) => {
+                log::warn!(
+                    "initializing notification cache for governance {}: {:#?}",
+                    governance_key,
+                    err
+                );
+                let notif_cache = NotifCacheEntry {
+                    governance_key,
+                    last_proposals_count: proposals_count,
+                    voting_proposals_last_notification_time: vec![],
+                    proposal_threads: vec![],
+                };
+                self.insert_notif_cache_entry(&notif_cache)?;
+                Ok(notif_cache)
+            }
+        }
+    }
+    /// returns the notification cache entries of all governances
+    pub fn list_notif_caches(&self) -> Result<Vec<NotifCacheEntry>, RealmsError> {
+        let tree = self.db.open_tree(DbTrees::Default)?;
+        let governance_keys: Vec<Pubkey> = tree
             .iter()
             .filter_map(|entry| {
-                if let Ok((key, _)) = entry {
-                    Some(key)
-                } else {
-                    None
-                }
+                let (key, _) = entry.ok()?;
+                let key = std::str::from_utf8(&key).ok()?;
+                let governance_key = key.strip_prefix(utils::NOTIF_CACHE_KEY_PREFIX)?;
+                governance_key.parse().ok()
             })
             .collect();
-        let govs = keys
-            .iter()
-            .filter_map(|key| {
-                let governance: GovernanceV2Wrapper = if let Ok(gov) = tree.deserialize(key) {
-                    gov
-                } else {
-                    return None;
-                };
-                Some(governance)
+        governance_keys
+            .into_iter()
+            .map(|governance_key| self.get_governance_notif_cache(governance_key))
+            .collect()
+    }
+    /// returns an iterator which deserializes the entries of the tree as they are iterated,
+    /// allowing large trees to be processed without loading every entry into memory
+    pub fn iter_tree_entries<T: BorshDeserialize>(
+        &self,
+        tree_name: &'static str,
+    ) -> Result<impl Iterator<Item = Result<T, RealmsError>>, RealmsError> {
+        let tree = self.db.open_tree(DbTrees::Custom(tree_name))?;
+        let entries = tree.iter();
+        Ok(entries.map(move |entry| -> Result<T, RealmsError> {
+            let (key, _) = entry?;
+            tree.deserialize(&key).map_err(|err| {
+                err.context(format!(
+                    "failed to deserialize entry {:?} of {}",
+                    key, tree_name
+                ))
+                .into()
             })
-            .collect();
-        Ok(govs)
+        }))
+    }
+    /// returns the entries of the tree which could be deserialized, along with the number of
+    /// entries which failed to deserialize, so that corruption or schema drift is not invisible
+    pub fn list_tree_entries<T: BorshDeserialize>(
+        &self,
+        tree_name: &'static str,
+    ) -> Result<(Vec<T>, usize), RealmsError> {
+        let mut entries = vec![];
+        let mut failed = 0;
+        for entry in self.iter_tree_entries(tree_name)? {
+            match entry {
+                Ok(value) => entries.push(value),
+                Err(err) => {
+                    log::debug!("failed to read entry of {}: {:#}", tree_name, err);
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 {
+            log::warn!(
+                "failed to deserialize {} of {} entries in {}",
+                failed,
+                entries.len() + failed,
+                tree_name
+            );
+        }
+        Ok((entries, failed))
+    }
+    /// returns all stored governances, skipping entries which fail to deserialize
+    pub fn list_governances(&self) -> Result<Vec<GovernanceV2Wrapper>, RealmsError> {
+        Ok(self.list_tree_entries(GOVERNANCE_TREE)?.0)
+    }
+    /// returns an iterator over the stored proposals, deserializing each one as it is reached,
+    /// for streaming proposals without allocating all of them up front
+    pub fn iter_proposals(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<ProposalV2Wrapper, RealmsError>>, RealmsError> {
+        self.iter_tree_entries(PROPOSAL_TREE)
     }
-    pub fn list_proposals(&self) -> Result<Vec<ProposalV2Wrapper>> {
+    /// returns all stored proposals, skipping entries which fail to deserialize
+    pub fn list_proposals(&self) -> Result<Vec<ProposalV2Wrapper>, RealmsError> {
+        Ok(self.list_tree_entries(PROPOSAL_TREE)?.0)
+    }
+    /// returns all stored proposals, returning an error for the first entry which
+    /// fails to deserialize
+    pub fn list_proposals_strict(&self) -> Result<Vec<ProposalV2Wrapper>, RealmsError> {
         let tree = self.db.open_tree(DbTrees::Custom(PROPOSAL_TREE))?;
-        let keys: Vec<IVec> = tree
+        let proposals = tree
             .iter()
-            .filter_map(|entry| {
-                if let Ok((key, _)) = entry {
-                    Some(key)
-                } else {
-                    None
-                }
+            .map(|entry| {
+                let (key, _) = entry?;
+                tree.deserialize(&key)
+                    .with_context(|| format!("failed to deserialize proposal {:?}", key))
             })
-            .collect();
-        let props = keys
+            .collect::<Result<_>>()?;
+        Ok(proposals)
+    }
+    /// returns up to `limit` proposals, skipping the first `offset`, without loading every key.
+    ///
+    /// sled iterates lexicographically by key, so proposals are ordered by their pubkey bytes and
+    /// pages are stable as long as no proposals are inserted or removed between calls. entries which
+    /// fail to deserialize are skipped, but still count towards the offset
+    pub fn list_proposals_paginated(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<ProposalV2Wrapper>, RealmsError> {
+        let tree = self.db.open_tree(DbTrees::Custom(PROPOSAL_TREE))?;
+        let props = tree
             .iter()
-            .filter_map(|key| {
-                let proposal: ProposalV2Wrapper = if let Ok(prop) = tree.deserialize(key) {
-                    prop
-                } else {
-                    return None;
-                };
-                Some(proposal)
+            .skip(offset)
+            .take(limit)
+            .filter_map(|entry| {
+                let (key, _) = entry.ok()?;
+                tree.deserialize(key).ok()
             })
             .collect();
         Ok(props)
     }
-    pub fn list_realms(&self) -> Result<Vec<RealmV2Wrapper>> {
-        let tree = self.db.open_tree(DbTrees::Custom(REALM_TREE))?;
-        let keys: Vec<IVec> = tree
+    /// returns all stored realms, skipping entries which fail to deserialize
+    pub fn list_realms(&self) -> Result<Vec<RealmV2Wrapper>, RealmsError> {
+        Ok(self.list_tree_entries(REALM_TREE)?.0)
+    }
+    /// returns the number of cached entries. entries are counted without being deserialized,
+    /// except for the notification caches whose voting proposals are counted
+    pub fn stats(&self) -> Result<DbStats, RealmsError> {
+        let count_entries = |tree_name: &'static str| -> Result<usize, RealmsError> {
+            Ok(self
+                .db
+                .open_tree(DbTrees::Custom(tree_name))?
+                .iter()
+                .count())
+        };
+        let notif_caches = self.list_notif_caches()?;
+        Ok(DbStats {
+            governances: count_entries(GOVERNANCE_TREE)?,
+            proposals: count_entries(PROPOSAL_TREE)?,
+            realms: count_entries(REALM_TREE)?,
+            notif_caches: notif_caches.len(),
+            voting_proposals: notif_caches
+                .iter()
+                .map(|notif_cache| notif_cache.voting_proposals_last_notification_time.len())
+                .sum(),
+        })
+    }
+    /// returns a snapshot of the stored realms, governances, proposals and notification caches.
+    /// notification caches stored in a legacy layout are exported in the current layout
+    pub fn export(&self) -> Result<DatabaseExport, RealmsError> {
+        Ok(DatabaseExport {
+            schema_version: self.schema_version()?.unwrap_or(SCHEMA_VERSION),
+            realms: self
+                .list_realms()?
+                .iter()
+                .map(|realm| ExportedEntry::new(realm.key, realm))
+                .collect::<Result<_>>()?,
+            governances: self
+                .list_governances()?
+                .iter()
+                .map(|governance| ExportedEntry::new(governance.key, governance))
+                .collect::<Result<_>>()?,
+            proposals: self
+                .list_proposals()?
+                .iter()
+                .map(|proposal| ExportedEntry::new(proposal.key, proposal))
+                .collect::<Result<_>>()?,
+            notif_caches: self
+                .list_notif_caches()?
+                .iter()
+                .map(|notif_cache| ExportedEntry::new(notif_cache.governance_key, notif_cache))
+                .collect::<Result<_>>()?,
+        })
+    }
+    /// inserts the entries of a snapshot returned by `export`. entries whose key is already
+    /// stored are skipped unless `overwrite` is set
+    ///
+    /// every entry is deserialized before any are inserted, so an export containing a malformed
+    /// entry leaves the database untouched
+    pub fn import(
+        &self,
+        export: &DatabaseExport,
+        overwrite: bool,
+    ) -> Result<ImportSummary, RealmsError> {
+        self.ensure_writable()?;
+        if !(MIN_IMPORT_SCHEMA_VERSION..=SCHEMA_VERSION).contains(&export.schema_version) {
+            return Err(anyhow!(
+                "export has schema version {}, expected {} to {}",
+                export.schema_version,
+                MIN_IMPORT_SCHEMA_VERSION,
+                SCHEMA_VERSION
+            )
+            .into());
+        }
+        let realms: Vec<RealmV2Wrapper> = export
+            .realms
             .iter()
-            .filter_map(|entry| {
-                if let Ok((key, _)) = entry {
-                    Some(key)
-                } else {
-                    None
+            .map(|entry| entry.decode())
+            .collect::<Result<_>>()?;
+        let governances: Vec<GovernanceV2Wrapper> = export
+            .governances
+            .iter()
+            .map(|entry| entry.decode())
+            .collect::<Result<_>>()?;
+        let proposals: Vec<ProposalV2Wrapper> = export
+            .proposals
+            .iter()
+            .map(|entry| entry.decode())
+            .collect::<Result<_>>()?;
+        let notif_caches: Vec<NotifCacheEntry> = export
+            .notif_caches
+            .iter()
+            .map(|entry| entry.decode())
+            .collect::<Result<_>>()?;
+        let mut summary = ImportSummary::default();
+        for realm in realms.iter() {
+            if overwrite || self.get_realm(realm.key).is_err() {
+                self.insert_realm(realm)?;
+                summary.imported += 1;
+            } else {
+                summary.skipped += 1;
+            }
+        }
+        for governance in governances.iter() {
+            if overwrite || self.get_governance(governance.key).is_err() {
+                self.insert_governance(governance)?;
+                summary.imported += 1;
+            } else {
+                summary.skipped += 1;
+            }
+        }
+        for proposal in proposals.iter() {
+            if overwrite || self.get_proposal(proposal.key).is_err() {
+                self.insert_proposal(proposal)?;
+                summary.imported += 1;
+            } else {
+                summary.skipped += 1;
+            }
+        }
+        for notif_cache in notif_caches.iter() {
+            if overwrite
+                || self
+                    .get_governance_notif_cache(notif_cache.governance_key)
+                    .is_err()
+            {
+                self.insert_notif_cache_entry(notif_cache)?;
+                summary.imported += 1;
+            } else {
+                summary.skipped += 1;
+            }
+        }
+        Ok(summary)
+    }
+    /// given a realm key, populate the database with the given governance accounts of the realm, and their proposals
+    ///
+    /// this will not be the most performant as every insert flushes and syncs to disk, so if maximal performance
+    /// is desired you'll want to use `populate_database_with_governances_batched`.
+    ///
+    /// proposals created with any of the `governing_token_mints` are loaded. proposals which fail to load
+    /// (ie: closed proposal accounts) are skipped, and reported in the returned summary
+    pub fn populate_database_with_governances(
+        &self,
+        // the realm account key
+        realm_key: Pubkey,
+        governance_keys: &[Pubkey],
+        governing_token_mints: &[Pubkey],
+        now: DateTime<Utc>,
+        rpc: &RpcClient,
+    ) -> Result<SeedSummary, RealmsError> {
+        let seed = fetch_realm_seed(realm_key, governance_keys, governing_token_mints, now, rpc)?;
+        self.insert_realm(&seed.realm)?;
+        for governance in seed.governances.iter() {
+            self.insert_governance(governance)?;
+        }
+        for proposal in seed.proposals.iter() {
+            self.insert_proposal(proposal)?;
+        }
+        for notif_cache in seed.notif_caches.iter() {
+            self.insert_notif_cache_entry(notif_cache)?;
+        }
+        Ok(seed.summary)
+    }
+    /// same as `populate_database_with_governances`, except all proposals are written in
+    /// a single batch, and the database is flushed once at the end. this is considerably faster
+    /// for realms with hundreds of proposals
+    pub fn populate_database_with_governances_batched(
+        &self,
+        // the realm account key
+        realm_key: Pubkey,
+        governance_keys: &[Pubkey],
+        governing_token_mints: &[Pubkey],
+        now: DateTime<Utc>,
+        rpc: &RpcClient,
+    ) -> Result<SeedSummary, RealmsError> {
+        let seed = fetch_realm_seed(realm_key, governance_keys, governing_token_mints, now, rpc)?;
+        self.insert_realm(&seed.realm)?;
+        self.db
+            .open_tree(DbTrees::Custom(GOVERNANCE_TREE))?
+            .insert_batch(&seed.governances)?;
+        self.db
+            .open_tree(DbTrees::Custom(PROPOSAL_TREE))?
+            .insert_batch(&seed.proposals)?;
+        self.db
+            .open_tree(DbTrees::Default)?
+            .insert_batch(&seed.notif_caches)?;
+        self.db.flush()?;
+        Ok(seed.summary)
+    }
+    /// fetches the proposal from chain and updates the cached entry, returning the previously
+    /// cached proposal (if any) along with the refreshed proposal
+    pub fn refresh_proposal(
+        &self,
+        key: Pubkey,
+        rpc: &RpcClient,
+    ) -> Result<(Option<ProposalV2Wrapper>, ProposalV2Wrapper), RealmsError> {
+        let previous = self.get_proposal(key).ok();
+        let mut proposal_account_tup = (key, rpc.get_account(&key)?);
+        let proposal_account_info = proposal_account_tup.into_account_info();
+        let proposal = get_proposal_wrapper(&proposal_account_info)?;
+        self.insert_proposal(&proposal)?;
+        Ok((previous, proposal))
+    }
+    /// returns the cached proposal, fetching it from chain and caching it when it isn't cached
+    /// or the cached entry fails to deserialize. fetched proposals aren't cached when the
+    /// database is read-only
+    pub fn get_proposal_or_fetch(
+        &self,
+        key: Pubkey,
+        rpc: &RpcClient,
+    ) -> Result<ProposalV2Wrapper, RealmsError> {
+        match self.get_proposal(key) {
+            Err(RealmsError::NotFound { .. }) | Err(RealmsError::Deserialize { .. }) => {
+                let mut proposal_account_tup = (key, rpc.get_account(&key)?);
+                let proposal = get_proposal_wrapper(&proposal_account_tup.into_account_info())?;
+                if !self.read_only {
+                    self.insert_proposal(&proposal)?;
                 }
+                Ok(proposal)
+            }
+            result => result,
+        }
+    }
+    /// proposals cached while still voting have a `max_vote_weight` of None, which is needed
+    /// to display historical quorum information once voting has ended.
+    ///
+    /// this recomputes `max_vote_weight` for all ended proposals missing it, and persists the updated
+    /// proposal. `mint_supply` is the supply of the community mint, while the council mint supply is
+    /// fetched when a council proposal needs backfilling. returns the number of proposals updated
+    pub fn backfill_vote_params(
+        &self,
+        rpc: &RpcClient,
+        mint_supply: u64,
+    ) -> Result<usize, RealmsError> {
+        let now = Utc::now();
+        let gov_tree = self.db.open_tree(DbTrees::Custom(GOVERNANCE_TREE))?;
+        let realms = self.list_realms()?;
+        let mut council_mint_supply: Option<u64> = None;
+        let mut updated = 0;
+        for mut proposal in self.list_proposals()? {
+            if proposal.proposal.max_vote_weight.is_some() {
+                continue;
+            }
+            let governance: GovernanceV2Wrapper =
+                match gov_tree.deserialize(proposal.proposal.governance) {
+                    Ok(governance) => governance,
+                    Err(err) => {
+                        log::warn!(
+                            "failed to load governance for proposal {}: {:#?}",
+                            proposal.key,
+                            err
+                        );
+                        continue;
+                    }
+                };
+            if proposal.proposal.voting_completed_at.is_none()
+                && !proposal.has_vote_time_ended(&governance.governance.config, now)
+            {
+                continue;
+            }
+            let realm = if let Some(realm) = realms
+                .iter()
+                .find(|realm| realm.key.eq(&governance.governance.realm))
+            {
+                realm
+            } else {
+                log::warn!(
+                    "realm {} for proposal {} is not cached",
+                    governance.governance.realm,
+                    proposal.key
+                );
+                continue;
+            };
+            let supply = if Some(proposal.proposal.governing_token_mint)
+                == realm.realm.config.council_mint
+            {
+                match council_mint_supply {
+                    Some(supply) => supply,
+                    None => {
+                        let mint_key = proposal.proposal.governing_token_mint;
+                        let mut mint_account_tup = (mint_key, rpc.get_account(&mint_key)?);
+                        let mint_account_info = mint_account_tup.into_account_info();
+                        let supply = spl_governance::tools::spl_token::get_spl_token_mint_supply(
+                            &mint_account_info,
+                        )
+                        .with_context(|| format!("failed to read supply of mint {}", mint_key))?;
+                        council_mint_supply = Some(supply);
+                        supply
+                    }
+                }
+            } else {
+                mint_supply
+            };
+            match proposal.max_vote_weight(&realm.realm, supply) {
+                Ok(max_vote_weight) => {
+                    proposal.proposal.max_vote_weight = Some(max_vote_weight);
+                    self.insert_proposal(&proposal)?;
+                    updated += 1;
+                }
+                Err(err) => {
+                    log::warn!(
+                        "failed to compute max vote weight for proposal {}: {:#?}",
+                        proposal.key,
+                        err
+                    );
+                }
+            }
+        }
+        Ok(updated)
+    }
+    /// used to check existing proposals, filter for actively voting ones,
+    /// and updating the notification cache if they are missing from the cache
+    ///
+    /// additionally it performs a proposal state sync, ensuring that if a proposal has a state on-disk
+    /// which does match its on-chain state, that the on-disk state is updated to reflect the on-chain state.
+    /// every state change detected during the sync is returned
+    ///
+    /// only proposals belonging to the given governance are synced
+    pub fn sync_notif_cache_with_proposals(
+        &self,
+        governance_key: Pubkey,
+        governing_token_mints: &[Pubkey],
+        now: DateTime<Utc>,
+        rpc: &RpcClient,
+    ) -> Result<Vec<ProposalStateTransition>, RealmsError> {
+        let mut transitions = vec![];
+        let mint_gov_key = governance_key;
+        let mint_gov = utils::get_wrapper(rpc, mint_gov_key, get_governance_wrapper)?;
+        self.insert_governance(&mint_gov)?;
+        let mut notif_cache = self.get_governance_notif_cache(mint_gov_key)?;
+        log::info!("notif_cache {:#?}", notif_cache);
+        let list_governance_proposals = || -> Result<Vec<ProposalV2Wrapper>> {
+            Ok(self
+                .list_proposals()?
+                .into_iter()
+                .filter(|proposal| proposal.proposal.governance.eq(&governance_key))
+                .collect())
+        };
+        let proposals = list_governance_proposals()?;
+        log::info!("proposals count {}", proposals.len());
+        let mut proposals = if proposals.len().lt(&(mint_gov.governance.proposals_count as usize)) {
+            log::warn!("proposal count of {} less than governance count {}, backfilling", proposals.len(), mint_gov.governance.proposals_count);
+            for idx in proposals.len()..(mint_gov.governance.proposals_count as usize) {
+                // proposals may be created with either the community or council mint
+            match utils::get_proposal_at_index(rpc, mint_gov_key, governing_token_mints, idx as u32) {
+                Ok(proposal) => {
+                    log::info!("fetched new proposal. idx {}, key {}", idx, proposal.key);
+                    if let Err(err) = self.insert_proposal(&proposal) {
+                        log::error!("failed to insert new proposal proposal {}: {:#?}", proposal.key, err)
+                    }
+                }
+                Err(err) => {
+                    log::error!(
+                        "failed to get proposal account {:#?}",
+                        err
+                    );
+                    continue;
+                }
+            }
+            }
+            list_governance_proposals()?
+        } else {
+            proposals
+        };
+        // populate any actively voting proposals that are not in a draft state
+        proposals.iter_mut().for_each(|proposal| {
+            // a proposal which fails to load is synced on the next iteration
+            let fresh_proposal = match utils::get_wrapper(rpc, proposal.key, get_proposal_wrapper) {
+                Ok(fresh_proposal) => fresh_proposal,
+                Err(err) => {
+                    log::error!("failed to get proposal account {}: {:#}", proposal.key, err);
+                    return;
+                }
+            };
+            if let Some(transition) = ProposalStateTransition::from_snapshots(proposal, &fresh_proposal) {
+                log::warn!("on-disk state for {} of {:#?} differs from onchain state of {:#?}, updating...", proposal.key, proposal.proposal.state, fresh_proposal.proposal.state);
+                // queue the result before the new state is stored, as the transition is only
+                // detected once and the announcement may fail
+                if transition.is_conclusion() {
+                    if let Err(err) = self.mark_result_pending(&PendingResult {
+                        proposal_key: proposal.key,
+                        governance_key,
+                        concluded_at: now.timestamp(),
+                    }) {
+                        log::error!("failed to queue result of proposal {}: {:#?}", proposal.key, err);
+                    }
+                }
+                transitions.push(transition);
+                // voting has ended, so preserve the tally as vote records may later be relinquished
+                if proposal
+                    .proposal
+                    .state
+                    .eq(&spl_governance::state::enums::ProposalState::Voting)
+                {
+                    if let Err(err) = self.finalize_vote_tally(proposal.key) {
+                        log::error!(
+                            "failed to finalize vote tally for {}: {:#?}",
+                            proposal.key,
+                            err
+                        );
+                    }
+                }
+                proposal.proposal.state = fresh_proposal.proposal.state;
+                if let Err(err) = self.insert_proposal(proposal) {
+                    log::error!("failed to update proposal {}: {:#?}", proposal.key, err);
+                }
+            }
+
+            let mut notif_cache_contains = false;
+            notif_cache
+                .voting_proposals_last_notification_time
+                .iter()
+                .for_each(|notif_state| {
+                    if notif_state.proposal_key.eq(&proposal.key) {
+                        notif_cache_contains = true;
+                    }
+                });
+            // attempt to finalize vote if possible, as this may not always be done on-chain, even
+            // if a vote has ended. really the only time this will likely be done on-chain is for a vote that is
+            // completed
+            proposal.finalize_vote(&mint_gov.governance.config, now);
+            if !proposal.has_vote_time_ended(&mint_gov.governance.config, now)
+                && !notif_cache_contains
+                && proposal
+                    .proposal
+                    .state
+                    .eq(&spl_governance::state::enums::ProposalState::Voting)
+            {
+                log::info!("updating notif cache with proposal {}", proposal.key);
+                notif_cache
+                    .voting_proposals_last_notification_time
+                    .push(ProposalNotifState::new(proposal.key, 0));
+            }
+        });
+
+        // clear out any proposals from the notification cache that aren't actively voting
+        proposals.iter().for_each(|proposal| {
+            for (idx, notif_state) in notif_cache
+                .clone()
+                .voting_proposals_last_notification_time
+                .iter()
+                .enumerate()
+            {
+                if proposal
+                    .proposal
+                    .state
+                    .ne(&spl_governance::state::enums::ProposalState::Voting)
+                    && proposal.key.eq(&notif_state.proposal_key)
+                {
+                    log::warn!(
+                        "removing non voting proposal {} from notif cache",
+                        notif_state.proposal_key
+                    );
+                    notif_cache
+                        .voting_proposals_last_notification_time
+                        .swap_remove(idx);
+                }
+            }
+        });
+
+        self.insert_notif_cache_entry(&notif_cache)?;
+        self.db.flush()?;
+        Ok(transitions)
+    }
+}
+
+/// the accounts fetched while seeding the database with a realm's governances
+struct RealmSeed {
+    realm: RealmV2Wrapper,
+    governances: Vec<GovernanceV2Wrapper>,
+    proposals: Vec<ProposalV2Wrapper>,
+    notif_caches: Vec<NotifCacheEntry>,
+    summary: SeedSummary,
+}
+
+/// fetches the realm, the given governances, and all of the governances' proposals
+fn fetch_realm_seed(
+    realm_key: Pubkey,
+    governance_keys: &[Pubkey],
+    governing_token_mints: &[Pubkey],
+    now: DateTime<Utc>,
+    rpc: &RpcClient,
+) -> Result<RealmSeed> {
+    let realm_account = rpc
+        .get_account(&realm_key)
+        .with_context(|| format!("failed to get realm account {}", realm_key))?;
+    let mut realm_account_tup = (realm_key, realm_account);
+    let realm_account_info = realm_account_tup.into_account_info();
+    let realm = get_realm_wrapper(&realm_account_info)
+        .with_context(|| format!("failed to deserialize realm account {}", realm_key))?;
+
+    let mut seed = RealmSeed {
+        realm,
+        governances: Vec::with_capacity(governance_keys.len()),
+        proposals: vec![],
+        notif_caches: Vec::with_capacity(governance_keys.len()),
+        summary: SeedSummary::default(),
+    };
+    for governance_key in governance_keys.iter() {
+        let governance_account = rpc
+            .get_account(governance_key)
+            .with_context(|| format!("failed to get governance account {}", governance_key))?;
+        let mut governance_account_tup = (*governance_key, governance_account);
+        let governance_info = governance_account_tup.into_account_info();
+        let governance = get_governance_wrapper(&governance_info).with_context(|| {
+            format!(
+                "failed to deserialize governance account {}",
+                governance_key
+            )
+        })?;
+
+        let mut notif_cache = NotifCacheEntry {
+            governance_key: *governance_key,
+            last_proposals_count: governance.governance.proposals_count,
+            voting_proposals_last_notification_time: Vec::with_capacity(5),
+            proposal_threads: vec![],
+        };
+        seed.summary.expected_proposals += governance.governance.proposals_count;
+
+        // now parse over all existing proposals, collecting them for insertion into the database
+        for idx in 0..governance.governance.proposals_count {
+            // proposal accounts may have been closed, so rather than aborting the seed
+            // record the missing index and continue with the remaining proposals
+            let mut proposal = match utils::get_proposal_at_index(
+                rpc,
+                *governance_key,
+                governing_token_mints,
+                idx,
+            ) {
+                Ok(proposal) => proposal,
+                Err(err) => {
+                    log::warn!(
+                        "failed to load proposal account. governance {}, idx {}: {:#}",
+                        governance_key,
+                        idx,
+                        err
+                    );
+                    seed.summary.missing_indices.push((*governance_key, idx));
+                    continue;
+                }
+            };
+            // attempt to finalize vote if possible, as this may not always be done on-chain, even
+            // if a vote has ended. really the only time this will likely be done on-chain is for a vote that is
+            // completed
+            proposal.finalize_vote(&governance.governance.config, now);
+            if proposal.proposal.voting_at.is_some()
+                && !proposal.has_vote_time_ended(&governance.governance.config, now)
+                && proposal
+                    .proposal
+                    .state
+                    .eq(&spl_governance::state::enums::ProposalState::Voting)
+            {
+                notif_cache
+                    .voting_proposals_last_notification_time
+                    .push(ProposalNotifState::new(proposal.key, 0));
+            }
+
+            seed.proposals.push(proposal);
+            seed.summary.loaded_proposals += 1;
+        }
+        seed.governances.push(governance);
+        seed.notif_caches.push(notif_cache);
+    }
+    if !seed.summary.missing_indices.is_empty() {
+        log::warn!(
+            "loaded {}/{} proposals, missing (governance, index) {:?}",
+            seed.summary.loaded_proposals,
+            seed.summary.expected_proposals,
+            seed.summary.missing_indices
+        );
+    }
+
+    Ok(seed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_client::rpc_client::RpcClient;
+    use solana_program::account_info::IntoAccountInfo;
+    use solana_program::pubkey::Pubkey;
+    use spl_governance::state::proposal::get_proposal_address;
+    use std::str::FromStr;
+
+    pub fn get_tulip_realm_account() -> Pubkey {
+        Pubkey::from_str("413KSeuFUBSWDzfjU9BBqBAWYKmoR8mncrhV84WcGNAk").unwrap()
+    }
+    pub fn get_tulip_council_mint() -> Pubkey {
+        Pubkey::from_str("EzSjCzCPwpchdQVaGJZYpgDNagzasKFVGJ66Dmut26FL").unwrap()
+    }
+    pub fn get_tulip_community_mint() -> Pubkey {
+        Pubkey::from_str("STuLiPmUCUtG1hQcwdc9de9sjYhVsYoucCiWqbApbpM").unwrap()
+    }
+    pub fn get_tulip_governance_account() -> Pubkey {
+        spl_governance::state::governance::get_mint_governance_address(
+            &GOVERNANCE_PROGRAM,
+            &get_tulip_realm_account(),
+            &get_tulip_council_mint(),
+        )
+    }
+    /// returns the tulip mint governance, and its first proposal, for use as the base of test fixtures
+    pub fn get_tulip_fixtures(rpc: &RpcClient) -> (GovernanceV2Wrapper, ProposalV2Wrapper) {
+        let governance_key = get_tulip_governance_account();
+        let mut governance_account_tup =
+            (governance_key, rpc.get_account(&governance_key).unwrap());
+        let governance =
+            get_governance_wrapper(&governance_account_tup.into_account_info()).unwrap();
+        let proposal_key = get_proposal_address(
+            &GOVERNANCE_PROGRAM,
+            &governance_key,
+            &get_tulip_community_mint(),
+            &(0_u32.to_le_bytes()),
+        );
+        let mut proposal_account_tup = (proposal_key, rpc.get_account(&proposal_key).unwrap());
+        let proposal = get_proposal_wrapper(&proposal_account_tup.into_account_info()).unwrap();
+        (governance, proposal)
+    }
+    /// deserializes an account from zeroed bytes, giving an empty value for types
+    /// which do not implement Default
+    pub fn zeroed<T: BorshDeserialize>() -> T {
+        solana_program::borsh::try_from_slice_unchecked(&[0_u8; 1024]).unwrap()
+    }
+    /// accepts a single request, responding with `status` and `response_body`, and returning
+    /// the request body
+    pub async fn mock_server(
+        status: &'static str,
+        response_body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = tokio::task::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0_u8; 1024];
+            // read until the full body, as indicated by content-length, has been received
+            loop {
+                let read = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                    let content_length = headers
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            if name.eq_ignore_ascii_case("content-length") {
+                                value.trim().parse::<usize>().ok()
+                            } else {
+                                None
+                            }
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= content_length {
+                        let response = format!(
+                            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            status,
+                            response_body.len(),
+                            response_body
+                        );
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                        return body.to_string();
+                    }
+                }
+                if read == 0 {
+                    return String::new();
+                }
+            }
+        });
+        (url, handle)
+    }
+    #[test]
+    fn test_check_writable() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_check_writable.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        db.check_writable().unwrap();
+        // the entry is overwritten by subsequent checks
+        db.check_writable().unwrap();
+        std::fs::remove_dir_all("realms_sdk_check_writable.db").unwrap();
+    }
+    #[test]
+    fn test_maintenance() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_maintenance.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        for _ in 0..10 {
+            db.insert_proposal(&ProposalV2Wrapper {
+                proposal: zeroed(),
+                key: Pubkey::new_unique(),
+            })
+            .unwrap();
+        }
+        db.maintenance().unwrap();
+        assert!(db.size_on_disk().unwrap() > 0);
+        assert_eq!(db.list_proposals().unwrap().len(), 10);
+        drop(db);
+        std::fs::remove_dir_all("realms_sdk_maintenance.db").unwrap();
+    }
+    #[test]
+    fn test_error_variants() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_error_variants.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts.clone()).unwrap();
+        let key = Pubkey::new_unique();
+        assert!(matches!(
+            db.get_proposal(key),
+            Err(RealmsError::NotFound { kind: "proposal", key: missing }) if missing == key
+        ));
+        assert_eq!(
+            db.get_realm(key).err().unwrap().to_string(),
+            format!("realm {} not found", key)
+        );
+        assert!(matches!(
+            db.get_governance_notif_cache(key),
+            Err(RealmsError::NotFound { .. })
+        ));
+        assert!(matches!(
+            db.delete_proposal(key),
+            Err(RealmsError::NotFound { .. })
+        ));
+
+        // an entry of a different type stored under the key
+        db.db
+            .open_tree(DbTrees::Custom(PROPOSAL_TREE))
+            .unwrap()
+            .insert(&AnnouncedProposal {
+                proposal_key: key,
+                announced_at: 1,
+            })
+            .unwrap();
+        assert!(matches!(
+            db.get_proposal(key),
+            Err(RealmsError::Deserialize { kind: "proposal", key: corrupt, .. }) if corrupt == key
+        ));
+
+        let rpc = RpcClient::new("http://127.0.0.1:1".to_string());
+        assert!(matches!(
+            db.refresh_proposal(key, &rpc),
+            Err(RealmsError::Rpc(_))
+        ));
+
+        let export = DatabaseExport {
+            schema_version: SCHEMA_VERSION + 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            db.import(&export, false),
+            Err(RealmsError::Other(_))
+        ));
+
+        drop(db);
+        let db = Database::new_read_only(opts).unwrap();
+        assert!(matches!(
+            db.mark_proposal_announced(key, 1),
+            Err(RealmsError::ReadOnly)
+        ));
+        drop(db);
+        std::fs::remove_dir_all("realms_sdk_error_variants.db").unwrap();
+    }
+    #[test]
+    fn test_get_proposal_or_fetch() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_get_proposal_or_fetch.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let mut proposal: ProposalV2 = zeroed();
+        proposal.account_type = spl_governance::state::enums::GovernanceAccountType::ProposalV2;
+        proposal.name = "raise the fee".to_string();
+        let key = Pubkey::new_unique();
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            solana_client::rpc_request::RpcRequest::GetAccountInfo,
+            serde_json::json!({
+                "context": {"slot": 1},
+                "value": {
+                    "lamports": 1_000_000,
+                    "data": [base64::encode(proposal.try_to_vec().unwrap()), "base64"],
+                    "owner": GOVERNANCE_PROGRAM.to_string(),
+                    "executable": false,
+                    "rentEpoch": 0,
+                },
+            }),
+        );
+        let rpc = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        // the proposal isn't cached, so it's fetched and cached
+        assert!(db.get_proposal(key).is_err());
+        let fetched = db.get_proposal_or_fetch(key, &rpc).unwrap();
+        assert_eq!(fetched.key, key);
+        assert_eq!(fetched.proposal.name, "raise the fee");
+        assert_eq!(db.get_proposal(key).unwrap().proposal.name, "raise the fee");
+        // cached proposals are returned without a request, which would fail as the mocked
+        // response is consumed by the first request
+        let cached = db.get_proposal_or_fetch(key, &rpc).unwrap();
+        assert_eq!(cached.proposal.name, "raise the fee");
+        drop(db);
+        std::fs::remove_dir_all("realms_sdk_get_proposal_or_fetch.db").unwrap();
+    }
+    #[test]
+    fn test_read_only() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_read_only.db".to_string(),
+            ..Default::default()
+        };
+        let proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        // the database of a running bot can be read while the bot holds sled's lock
+        let writer = Database::new(opts.clone()).unwrap();
+        assert!(!writer.is_read_only());
+        writer.insert_proposal(&proposal).unwrap();
+        let db = Database::new_read_only(opts).unwrap();
+        assert!(db.is_read_only());
+        assert_eq!(db.get_proposal(proposal.key).unwrap().key, proposal.key);
+        assert_eq!(db.list_proposals().unwrap().len(), 1);
+        let err = db.insert_proposal(&proposal).unwrap_err();
+        assert_eq!(err.to_string(), "database is read-only");
+        assert!(db.update_proposal_if_changed(&proposal).is_err());
+        assert!(db.delete_proposal(proposal.key).is_err());
+        assert!(db.mark_proposal_announced(proposal.key, 1).is_err());
+        assert!(db.check_writable().is_err());
+        assert!(db.migrate(0, SCHEMA_VERSION).is_err());
+        // the failed writes left the stored proposal untouched
+        assert_eq!(db.list_proposals().unwrap().len(), 1);
+        assert!(!db.is_proposal_announced(proposal.key).unwrap());
+        // the snapshot is removed once the last handle is dropped
+        let snapshot_path = db.snapshot.as_ref().unwrap().path.clone();
+        let handle = db.clone();
+        drop(db);
+        assert!(snapshot_path.exists());
+        drop(handle);
+        assert!(!snapshot_path.exists());
+        drop(writer);
+        std::fs::remove_dir_all("realms_sdk_read_only.db").unwrap();
+    }
+    #[test]
+    fn test_stats() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_stats.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        assert_eq!(db.stats().unwrap(), DbStats::default());
+        let realm = RealmV2Wrapper {
+            realm: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        db.insert_realm(&realm).unwrap();
+        let governance_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        for governance_key in governance_keys.iter() {
+            db.insert_governance(&GovernanceV2Wrapper {
+                governance: zeroed(),
+                key: *governance_key,
+            })
+            .unwrap();
+        }
+        for _ in 0..3 {
+            db.insert_proposal(&ProposalV2Wrapper {
+                proposal: zeroed(),
+                key: Pubkey::new_unique(),
+            })
+            .unwrap();
+        }
+        db.insert_notif_cache_entry(&NotifCacheEntry {
+            governance_key: governance_keys[0],
+            last_proposals_count: 3,
+            voting_proposals_last_notification_time: vec![
+                ProposalNotifState::new(Pubkey::new_unique(), 0),
+                ProposalNotifState::new(Pubkey::new_unique(), 0),
+            ],
+            proposal_threads: vec![],
+        })
+        .unwrap();
+        db.get_or_init_notif_cache(governance_keys[1], 0).unwrap();
+        assert_eq!(
+            db.stats().unwrap(),
+            DbStats {
+                governances: 2,
+                proposals: 3,
+                realms: 1,
+                notif_caches: 2,
+                voting_proposals: 2,
+            }
+        );
+        drop(db);
+        std::fs::remove_dir_all("realms_sdk_stats.db").unwrap();
+    }
+    #[test]
+    fn test_export_import() {
+        let seeded_opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_export.db".to_string(),
+            ..Default::default()
+        };
+        let fresh_opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_import.db".to_string(),
+            ..Default::default()
+        };
+        let seeded_db = Database::new(seeded_opts).unwrap();
+        let realm = RealmV2Wrapper {
+            realm: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        let governance = GovernanceV2Wrapper {
+            governance: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        proposal.proposal.name = "raise the fee".to_string();
+        seeded_db.insert_realm(&realm).unwrap();
+        seeded_db.insert_governance(&governance).unwrap();
+        seeded_db.insert_proposal(&proposal).unwrap();
+        seeded_db
+            .get_or_init_notif_cache(governance.key, 3)
+            .unwrap();
+        let export = seeded_db.export().unwrap();
+        assert_eq!(export.schema_version, SCHEMA_VERSION);
+        assert_eq!(export.proposals[0].key, proposal.key.to_string());
+        assert_eq!(export.notif_caches[0].key, governance.key.to_string());
+
+        // round trip the export through json into a fresh database
+        let json = serde_json::to_string(&export).unwrap();
+        let export: DatabaseExport = serde_json::from_str(&json).unwrap();
+        let db = Database::new(fresh_opts).unwrap();
+        let summary = db.import(&export, false).unwrap();
+        assert_eq!((summary.imported, summary.skipped), (4, 0));
+        assert_eq!(db.export().unwrap(), export);
+        let notif_cache = db.get_governance_notif_cache(governance.key).unwrap();
+        assert_eq!(notif_cache.last_proposals_count, 3);
+
+        // existing entries are only replaced when overwriting
+        proposal.proposal.name = "lower the fee".to_string();
+        db.insert_proposal(&proposal).unwrap();
+        let summary = db.import(&export, false).unwrap();
+        assert_eq!((summary.imported, summary.skipped), (0, 4));
+        assert_eq!(
+            db.get_proposal(proposal.key).unwrap().proposal.name,
+            "lower the fee"
+        );
+        let summary = db.import(&export, true).unwrap();
+        assert_eq!((summary.imported, summary.skipped), (4, 0));
+        assert_eq!(
+            db.get_proposal(proposal.key).unwrap().proposal.name,
+            "raise the fee"
+        );
+
+        // malformed entries are rejected before anything is inserted
+        db.insert_proposal(&proposal).unwrap();
+        let mut malformed = export.clone();
+        malformed.governances[0].data = "not base64".to_string();
+        assert!(db.import(&malformed, true).is_err());
+        assert_eq!(
+            db.get_proposal(proposal.key).unwrap().proposal.name,
+            "lower the fee"
+        );
+        // as are exports of other schema versions
+        let mut malformed = export.clone();
+        malformed.schema_version += 1;
+        assert!(db.import(&malformed, true).is_err());
+        // exports written before the cached vote record layout changed are still accepted
+        let mut legacy = export;
+        legacy.schema_version = 1;
+        assert!(db.import(&legacy, true).is_ok());
+        drop(seeded_db);
+        drop(db);
+        std::fs::remove_dir_all("realms_sdk_export.db").unwrap();
+        std::fs::remove_dir_all("realms_sdk_import.db").unwrap();
+    }
+    #[test]
+    fn test_get_or_init_notif_cache() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_get_or_init_notif_cache.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let governance_key = Pubkey::new_unique();
+        assert!(db.get_governance_notif_cache(governance_key).is_err());
+        let notif_cache = db.get_or_init_notif_cache(governance_key, 7).unwrap();
+        assert_eq!(notif_cache.governance_key, governance_key);
+        assert_eq!(notif_cache.last_proposals_count, 7);
+        assert!(notif_cache
+            .voting_proposals_last_notification_time
+            .is_empty());
+        // the entry is persisted, and existing entries are returned as is
+        let notif_cache = db.get_governance_notif_cache(governance_key).unwrap();
+        assert_eq!(notif_cache.last_proposals_count, 7);
+        let notif_cache = db.get_or_init_notif_cache(governance_key, 9).unwrap();
+        assert_eq!(notif_cache.last_proposals_count, 7);
+        std::fs::remove_dir_all("realms_sdk_get_or_init_notif_cache.db").unwrap();
+    }
+    #[test]
+    fn test_list_notif_caches() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_list_notif_caches.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        // other entries in the default tree are ignored
+        db.check_writable().unwrap();
+        assert!(db.list_notif_caches().unwrap().is_empty());
+        let governance_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        for (idx, governance_key) in governance_keys.iter().enumerate() {
+            db.insert_notif_cache_entry(&NotifCacheEntry {
+                governance_key: *governance_key,
+                last_proposals_count: idx as u32,
+                voting_proposals_last_notification_time: vec![],
+                proposal_threads: vec![],
+            })
+            .unwrap();
+        }
+        let notif_caches = db.list_notif_caches().unwrap();
+        assert_eq!(notif_caches.len(), 2);
+        for (idx, governance_key) in governance_keys.iter().enumerate() {
+            let notif_cache = notif_caches
+                .iter()
+                .find(|notif_cache| notif_cache.governance_key.eq(governance_key))
+                .unwrap();
+            assert_eq!(notif_cache.last_proposals_count, idx as u32);
+        }
+        std::fs::remove_dir_all("realms_sdk_list_notif_caches.db").unwrap();
+    }
+    /// the layout of NotifCacheEntry prior to the addition of `ProposalNotifState`
+    #[derive(BorshSerialize)]
+    struct TupleNotifCacheFixture {
+        governance_key: Pubkey,
+        last_proposals_count: u32,
+        voting_proposals_last_notification_time: Vec<(Pubkey, i64)>,
+        proposal_threads: Vec<(Pubkey, u64)>,
+    }
+    /// the layout of NotifCacheEntry prior to the addition of `proposal_threads`
+    #[derive(BorshSerialize)]
+    struct ThreadlessNotifCacheFixture {
+        governance_key: Pubkey,
+        last_proposals_count: u32,
+        voting_proposals_last_notification_time: Vec<(Pubkey, i64)>,
+    }
+    /// the layout of NotifCacheEntry prior to the addition of `result_announced`
+    #[derive(BorshSerialize)]
+    struct ReminderNotifCacheFixture {
+        governance_key: Pubkey,
+        last_proposals_count: u32,
+        voting_proposals_last_notification_time: Vec<(Pubkey, i64, bool)>,
+        proposal_threads: Vec<(Pubkey, u64)>,
+    }
+    impl DbKey for ReminderNotifCacheFixture {
+        fn key(&self) -> Result<Vec<u8>> {
+            Ok(governance_notif_cache_key(self.governance_key)
+                .as_bytes()
+                .to_vec())
+        }
+    }
+    impl DbKey for TupleNotifCacheFixture {
+        fn key(&self) -> Result<Vec<u8>> {
+            Ok(governance_notif_cache_key(self.governance_key)
+                .as_bytes()
+                .to_vec())
+        }
+    }
+    impl DbKey for ThreadlessNotifCacheFixture {
+        fn key(&self) -> Result<Vec<u8>> {
+            Ok(governance_notif_cache_key(self.governance_key)
+                .as_bytes()
+                .to_vec())
+        }
+    }
+    #[test]
+    fn test_legacy_notif_cache_layouts() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_legacy_notif_cache.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let proposal_key = Pubkey::new_unique();
+        let tree = db.db.open_tree(DbTrees::Default).unwrap();
+        let tuple_fixture = TupleNotifCacheFixture {
+            governance_key: Pubkey::new_unique(),
+            last_proposals_count: 3,
+            voting_proposals_last_notification_time: vec![(proposal_key, 5)],
+            proposal_threads: vec![(proposal_key, 7)],
+        };
+        tree.insert(&tuple_fixture).unwrap();
+        let threadless_fixture = ThreadlessNotifCacheFixture {
+            governance_key: Pubkey::new_unique(),
+            last_proposals_count: 4,
+            voting_proposals_last_notification_time: vec![(proposal_key, 6)],
+        };
+        tree.insert(&threadless_fixture).unwrap();
+        let reminder_fixture = ReminderNotifCacheFixture {
+            governance_key: Pubkey::new_unique(),
+            last_proposals_count: 5,
+            voting_proposals_last_notification_time: vec![(proposal_key, 8, true)],
+            proposal_threads: vec![(proposal_key, 9)],
+        };
+        tree.insert(&reminder_fixture).unwrap();
+
+        let notif_cache = db
+            .get_governance_notif_cache(reminder_fixture.governance_key)
+            .unwrap();
+        assert_eq!(notif_cache.last_proposals_count, 5);
+        assert_eq!(
+            notif_cache.voting_proposals_last_notification_time,
+            vec![ProposalNotifState {
+                proposal_key,
+                last_notif_time: 8,
+                final_reminder_sent: true,
+                result_announced: false,
+            }]
+        );
+        assert_eq!(notif_cache.proposal_threads, vec![(proposal_key, 9)]);
+
+        let notif_cache = db
+            .get_governance_notif_cache(tuple_fixture.governance_key)
+            .unwrap();
+        assert_eq!(notif_cache.last_proposals_count, 3);
+        assert_eq!(
+            notif_cache.voting_proposals_last_notification_time,
+            vec![ProposalNotifState::new(proposal_key, 5)]
+        );
+        assert_eq!(notif_cache.proposal_threads, vec![(proposal_key, 7)]);
+
+        let notif_cache = db
+            .get_governance_notif_cache(threadless_fixture.governance_key)
+            .unwrap();
+        assert_eq!(notif_cache.last_proposals_count, 4);
+        assert_eq!(
+            notif_cache.voting_proposals_last_notification_time,
+            vec![ProposalNotifState::new(proposal_key, 6)]
+        );
+        assert!(notif_cache.proposal_threads.is_empty());
+
+        // converted entries are written back using the current layout
+        let mut notif_cache = notif_cache;
+        notif_cache.voting_proposals_last_notification_time[0].final_reminder_sent = true;
+        notif_cache.voting_proposals_last_notification_time[0].result_announced = true;
+        db.insert_notif_cache_entry(&notif_cache).unwrap();
+        let notif_cache = db
+            .get_governance_notif_cache(threadless_fixture.governance_key)
+            .unwrap();
+        assert!(notif_cache.voting_proposals_last_notification_time[0].final_reminder_sent);
+        assert!(notif_cache.voting_proposals_last_notification_time[0].result_announced);
+        std::fs::remove_dir_all("realms_sdk_legacy_notif_cache.db").unwrap();
+    }
+    #[test]
+    fn test_announced_proposals() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_announced_proposals.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let (announced, unannounced) = (Pubkey::new_unique(), Pubkey::new_unique());
+        db.mark_proposal_announced(announced, Utc::now().timestamp())
+            .unwrap();
+        assert!(db.is_proposal_announced(announced).unwrap());
+        assert!(!db.is_proposal_announced(unannounced).unwrap());
+        // results are tracked separately from new proposal notifications
+        assert!(!db.is_result_announced(announced).unwrap());
+        db.mark_result_announced(announced, Utc::now().timestamp())
+            .unwrap();
+        assert!(db.is_result_announced(announced).unwrap());
+        assert!(!db.is_result_announced(unannounced).unwrap());
+        std::fs::remove_dir_all("realms_sdk_announced_proposals.db").unwrap();
+    }
+    #[test]
+    fn test_pending_results() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_pending_results.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let (governance_key, other_governance_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pending = |governance_key: Pubkey, concluded_at: i64| PendingResult {
+            proposal_key: Pubkey::new_unique(),
+            governance_key,
+            concluded_at,
+        };
+        let (newer, older, other) = (
+            pending(governance_key, 200),
+            pending(governance_key, 100),
+            pending(other_governance_key, 100),
+        );
+        for pending in [&newer, &older, &other] {
+            db.mark_result_pending(pending).unwrap();
+        }
+        assert_eq!(
+            db.list_pending_results(governance_key).unwrap(),
+            vec![older.clone(), newer.clone()]
+        );
+        assert_eq!(
+            db.list_pending_results(other_governance_key).unwrap(),
+            vec![other]
+        );
+        // announcing the result removes it from the pending results
+        db.mark_result_announced(older.proposal_key, Utc::now().timestamp())
+            .unwrap();
+        assert_eq!(
+            db.list_pending_results(governance_key).unwrap(),
+            vec![newer.clone()]
+        );
+        // a result which was already announced is not queued again
+        db.mark_result_pending(&older).unwrap();
+        assert_eq!(
+            db.list_pending_results(governance_key).unwrap(),
+            vec![newer]
+        );
+        std::fs::remove_dir_all("realms_sdk_pending_results.db").unwrap();
+    }
+    /// counts the number of times vote records are fetched
+    struct CountingFetcher {
+        calls: std::cell::Cell<u32>,
+    }
+    impl utils::VoteRecordFetcher for CountingFetcher {
+        fn fetch_vote_records(&self, proposal: Pubkey) -> Result<Vec<VoteRecordV2Wrapper>> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(vec![VoteRecordV2Wrapper {
+                vote_record: spl_governance::state::vote_record::VoteRecordV2 {
+                    account_type: spl_governance::state::enums::GovernanceAccountType::VoteRecordV2,
+                    proposal,
+                    governing_token_owner: Pubkey::new_unique(),
+                    is_relinquished: false,
+                    voter_weight: 100,
+                    vote: spl_governance::state::vote_record::Vote::Deny,
+                    reserved_v2: [0; 8],
+                },
+                key: Pubkey::new_unique(),
+            }])
+        }
+    }
+    #[test]
+    fn test_get_cached_vote_records() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_get_cached_vote_records.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let rpc = CountingFetcher {
+            calls: std::cell::Cell::new(0),
+        };
+        let proposal_key = Pubkey::new_unique();
+        let max_age = chrono::Duration::hours(1);
+        let vote_records = db
+            .get_cached_vote_records(proposal_key, max_age, &rpc)
+            .unwrap();
+        assert_eq!(vote_records.len(), 1);
+        assert_eq!(vote_records[0].vote_record.proposal, proposal_key);
+        assert_eq!(rpc.calls.get(), 1);
+        // a second call within the ttl is served from the cache
+        let cached = db
+            .get_cached_vote_records(proposal_key, max_age, &rpc)
+            .unwrap();
+        assert_eq!(cached, vote_records);
+        assert_eq!(rpc.calls.get(), 1);
+        // stale entries are refetched
+        db.get_cached_vote_records(proposal_key, chrono::Duration::zero(), &rpc)
+            .unwrap();
+        assert_eq!(rpc.calls.get(), 2);
+        // entries are cached per proposal
+        db.get_cached_vote_records(Pubkey::new_unique(), max_age, &rpc)
+            .unwrap();
+        assert_eq!(rpc.calls.get(), 3);
+        // proposals are tallied from the cached vote records
+        let proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: proposal_key,
+        };
+        let fetcher = CachedVoteRecordFetcher {
+            db: &db,
+            max_age,
+            rpc: &rpc,
+        };
+        let tally = proposal.vote_tally(&fetcher).unwrap();
+        assert_eq!(tally.deny_votes, 100);
+        assert_eq!(tally.voter_count, 1);
+        assert_eq!(rpc.calls.get(), 3);
+        std::fs::remove_dir_all("realms_sdk_get_cached_vote_records.db").unwrap();
+    }
+    #[test]
+    fn test_update_if_changed() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_update_if_changed.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        assert!(db.update_proposal_if_changed(&proposal).unwrap());
+        // identical data is not rewritten
+        assert!(!db.update_proposal_if_changed(&proposal).unwrap());
+        proposal.proposal.name = "raise the fee".to_string();
+        assert!(db.update_proposal_if_changed(&proposal).unwrap());
+        assert_eq!(
+            db.get_proposal(proposal.key).unwrap().proposal.name,
+            "raise the fee"
+        );
+
+        let mut governance = GovernanceV2Wrapper {
+            governance: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        assert!(db.update_governance_if_changed(&governance).unwrap());
+        assert!(!db.update_governance_if_changed(&governance).unwrap());
+        governance.governance.proposals_count = 1;
+        assert!(db.update_governance_if_changed(&governance).unwrap());
+        std::fs::remove_dir_all("realms_sdk_update_if_changed.db").unwrap();
+    }
+    #[test]
+    fn test_list_tree_entries_failures() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_list_tree_entries_failures.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        db.insert_proposal(&proposal).unwrap();
+        // an entry which is too short to be deserialized as a proposal
+        db.db
+            .open_tree(DbTrees::Custom(PROPOSAL_TREE))
+            .unwrap()
+            .insert(&SelfTestEntry { written_at: 1 })
+            .unwrap();
+        let (proposals, failed) = db
+            .list_tree_entries::<ProposalV2Wrapper>(PROPOSAL_TREE)
+            .unwrap();
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].key, proposal.key);
+        assert_eq!(failed, 1);
+        assert_eq!(db.list_proposals().unwrap().len(), 1);
+        assert!(db.list_proposals_strict().is_err());
+        std::fs::remove_dir_all("realms_sdk_list_tree_entries_failures.db").unwrap();
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_refresh_proposal() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+        let (_, proposal) = get_tulip_fixtures(&rpc);
+        let db = Database::new(tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_refresh_proposal.db".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        // proposals which aren't cached are fetched and inserted
+        let (previous, refreshed) = db.refresh_proposal(proposal.key, &rpc).unwrap();
+        assert!(previous.is_none());
+        assert_eq!(refreshed.key, proposal.key);
+        assert_eq!(
+            db.get_proposal(proposal.key).unwrap().proposal,
+            proposal.proposal
+        );
+
+        // a stale cached entry is replaced by the proposal on chain
+        let mut stale = ProposalV2Wrapper {
+            proposal: proposal.proposal.clone(),
+            key: proposal.key,
+        };
+        stale.proposal.name = "stale".to_string();
+        db.insert_proposal(&stale).unwrap();
+        let (previous, refreshed) = db.refresh_proposal(proposal.key, &rpc).unwrap();
+        assert_eq!(previous.unwrap().proposal.name, "stale");
+        assert_eq!(refreshed.proposal.name, proposal.proposal.name);
+        assert_eq!(
+            db.get_proposal(proposal.key).unwrap().proposal.name,
+            proposal.proposal.name
+        );
+        std::fs::remove_dir_all("realms_sdk_refresh_proposal.db").unwrap();
+    }
+    #[test]
+    fn test_iter_proposals() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_iter_proposals.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        assert_eq!(db.iter_proposals().unwrap().count(), 0);
+        for _ in 0..3 {
+            db.insert_proposal(&ProposalV2Wrapper {
+                proposal: zeroed(),
+                key: Pubkey::new_unique(),
             })
-            .collect();
-        let realms = keys
+            .unwrap();
+        }
+        let iterated = db
+            .iter_proposals()
+            .unwrap()
+            .map(|proposal| proposal.unwrap().key)
+            .collect::<Vec<_>>();
+        let listed = db
+            .list_proposals()
+            .unwrap()
             .iter()
-            .filter_map(|key| {
-                let realm: RealmV2Wrapper = if let Ok(realm) = tree.deserialize(key) {
-                    realm
-                } else {
-                    return None;
-                };
-                Some(realm)
-            })
-            .collect();
-        Ok(realms)
+            .map(|proposal| proposal.key)
+            .collect::<Vec<_>>();
+        assert_eq!(iterated.len(), 3);
+        assert_eq!(iterated, listed);
+        // entries which fail to deserialize are yielded as errors rather than skipped
+        db.db
+            .open_tree(DbTrees::Custom(PROPOSAL_TREE))
+            .unwrap()
+            .insert(&SelfTestEntry { written_at: 1 })
+            .unwrap();
+        assert_eq!(
+            db.iter_proposals()
+                .unwrap()
+                .filter(|proposal| proposal.is_err())
+                .count(),
+            1
+        );
+        assert_eq!(db.list_proposals().unwrap().len(), 3);
+        std::fs::remove_dir_all("realms_sdk_iter_proposals.db").unwrap();
     }
-    /// given a realm key, populate the database with all related mint governance accounts, and proposals
-    ///
-    /// this will not be the most performant as every insert flushes and syncs to disk, so if maximal performance
-    /// is desired you'll want to leverage batch transactions.
-    pub fn populate_database_with_mint_governance(
-        &self,
-        // the realm account key
-        realm_key: Pubkey,
-        council_mint_key: Pubkey,
-        community_mint_key: Pubkey,
-        now: DateTime<Utc>,
-        rpc: &RpcClient,
-    ) -> Result<()> {
-        let realm_account = rpc.get_account(&realm_key).unwrap();
-        let mut realm_account_tup = (realm_key, realm_account);
-        let realm_account_info = realm_account_tup.into_account_info();
-        let realm = get_realm_wrapper(&realm_account_info).unwrap();
-        self.insert_realm(&realm)?;
+    #[test]
+    fn test_finalize_vote_tally() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_finalize_tally.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let proposal_key = Pubkey::new_unique();
+        // nothing to finalize if a tally was never recorded
+        assert_eq!(db.finalize_vote_tally(proposal_key).unwrap(), None);
 
-        let mint_gov_key = spl_governance::state::governance::get_mint_governance_address(
-            &GOVERNANCE_PROGRAM,
-            &realm_key,
-            &council_mint_key,
+        let tally = VoteTally {
+            approval_votes: 100,
+            deny_votes: 50,
+            voter_count: 3,
+            abstain_votes: 10,
+        };
+        db.record_vote_tally(proposal_key, tally.clone()).unwrap();
+        assert_eq!(
+            db.finalize_vote_tally(proposal_key).unwrap(),
+            Some(tally.clone())
         );
-        let main_gov_account = rpc.get_account(&mint_gov_key).unwrap();
-        let mut main_gov_account_tup = (mint_gov_key, main_gov_account);
-        let main_gov_info = main_gov_account_tup.into_account_info();
-        let mint_gov = get_governance_wrapper(&main_gov_info).unwrap();
-        self.insert_governance(&mint_gov)?;
 
-        let mut notif_cache = NotifCacheEntry {
-            governance_key: mint_gov_key,
-            last_proposals_count: mint_gov.governance.proposals_count,
-            voting_proposals_last_notification_time: Vec::with_capacity(5),
+        // tallies recorded after voting ended do not change the final tally
+        db.record_vote_tally(proposal_key, VoteTally::default())
+            .unwrap();
+        let tally_entry = db.get_proposal_tally(proposal_key).unwrap();
+        assert_eq!(tally_entry.last_tally, tally);
+        assert_eq!(tally_entry.final_tally, Some(tally));
+
+        std::fs::remove_dir_all("realms_sdk_finalize_tally.db").unwrap();
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_populate_database_with_governances_batched() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+
+        let dbs: Vec<Database> = ["realms_sdk_populate.db", "realms_sdk_populate_batched.db"]
+            .iter()
+            .map(|path| {
+                Database::new(tulip_sled_util::config::DbOpts {
+                    path: path.to_string(),
+                    ..Default::default()
+                })
+                .unwrap()
+            })
+            .collect();
+        // use a fixed point in time so that both seeds finalize votes identically
+        let now = Utc::now();
+        let summary = dbs[0]
+            .populate_database_with_governances(
+                get_tulip_realm_account(),
+                &[get_tulip_governance_account()],
+                &[get_tulip_community_mint(), get_tulip_council_mint()],
+                now,
+                &rpc,
+            )
+            .unwrap();
+        let batched_summary = dbs[1]
+            .populate_database_with_governances_batched(
+                get_tulip_realm_account(),
+                &[get_tulip_governance_account()],
+                &[get_tulip_community_mint(), get_tulip_council_mint()],
+                now,
+                &rpc,
+            )
+            .unwrap();
+        assert_eq!(summary.loaded_proposals, batched_summary.loaded_proposals);
+
+        let serialize = |db: &Database| -> Vec<Vec<u8>> {
+            db.list_proposals()
+                .unwrap()
+                .iter()
+                .map(|proposal| proposal.try_to_vec().unwrap())
+                .collect()
         };
+        assert_eq!(serialize(&dbs[0]), serialize(&dbs[1]));
 
-        // now parse over all existing proposals, inserting them into the database
-        for idx in 0..mint_gov.governance.proposals_count {
-            let proposal_key = spl_governance::state::proposal::get_proposal_address(
-                &GOVERNANCE_PROGRAM,
-                &mint_gov_key,
-                &community_mint_key,
-                &idx.to_le_bytes()[..],
-            );
-            let proposal_account = rpc.get_account(&proposal_key).unwrap();
-            let mut proposal_account_tup = (proposal_key, proposal_account);
-            let proposal_account_info = proposal_account_tup.into_account_info();
-            let mut proposal = get_proposal_wrapper(&proposal_account_info).unwrap();
-            // attempt to finalize vote if possible, as this may not always be done on-chain, even
-            // if a vote has ended. really the only time this will likely be done on-chain is for a vote that is
-            // completed
-            proposal.finalize_vote(&mint_gov.governance.config, now);
-            if proposal.proposal.voting_at.is_some()
-                && !proposal.has_vote_time_ended(&mint_gov.governance.config, now)
-                && proposal
-                    .proposal
-                    .state
-                    .eq(&spl_governance::state::enums::ProposalState::Voting)
-            {
-                notif_cache
-                    .voting_proposals_last_notification_time
-                    .push((proposal.key, 0));
-            }
+        drop(dbs);
+        std::fs::remove_dir_all("realms_sdk_populate.db").unwrap();
+        std::fs::remove_dir_all("realms_sdk_populate_batched.db").unwrap();
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_populate_database_with_bogus_realm() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
 
-            self.insert_proposal(&proposal)?;
-        }
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_populate_bogus.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
 
-        // insert the notif cache entry
-        self.insert_notif_cache_entry(&notif_cache)?;
+        let realm_key = Pubkey::new_unique();
+        let err = db
+            .populate_database_with_governances(
+                realm_key,
+                &[get_tulip_governance_account()],
+                &[get_tulip_community_mint(), get_tulip_council_mint()],
+                Utc::now(),
+                &rpc,
+            )
+            .err()
+            .unwrap();
+        assert!(format!("{:#}", err).contains(&realm_key.to_string()));
+        assert!(db.list_realms().unwrap().is_empty());
 
-        Ok(())
+        std::fs::remove_dir_all("realms_sdk_populate_bogus.db").unwrap();
     }
-    /// used to check existing proposals, filter for actively voting ones,
-    /// and updating the notification cache if they are missing from the cache
-    ///
-    /// additionally it performs a proposal state sync, ensuring that if a proposal has a state on-disk
-    /// which does match its on-chain state, that the on-disk state is updated to reflect the on-chain state
-    pub fn sync_notif_cache_with_proposals(
-        &self,
-        realm_key: Pubkey,
-        community_mint_key: Pubkey,
-        council_mint_key: Pubkey,
-        now: DateTime<Utc>,
-        rpc: &RpcClient,
-    ) -> Result<()> {
-        let mint_gov_key = spl_governance::state::governance::get_mint_governance_address(
-            &GOVERNANCE_PROGRAM,
-            &realm_key,
-            &council_mint_key,
-        );
-        let main_gov_account = rpc.get_account(&mint_gov_key).unwrap();
-        let mut main_gov_account_tup = (mint_gov_key, main_gov_account);
-        let main_gov_info = main_gov_account_tup.into_account_info();
-        let mint_gov = get_governance_wrapper(&main_gov_info).unwrap();
-        self.insert_governance(&mint_gov)?;
-        let mut notif_cache = self.get_governance_notif_cache(mint_gov_key)?;
-        log::info!("notif_cache {:#?}", notif_cache);
-        let proposals = self.list_proposals()?;
-        log::info!("proposals count {}", proposals.len());
-        let mut proposals = if proposals.len().lt(&(mint_gov.governance.proposals_count as usize)) {
-            log::warn!("proposal count of {} less than governance count {}, backfilling", proposals.len(), mint_gov.governance.proposals_count);
-            for idx in proposals.len()..(mint_gov.governance.proposals_count as usize) {
-                let proposal_key =
-                spl_governance::state::proposal::get_proposal_address(
-                    &GOVERNANCE_PROGRAM,
-                    &mint_gov_key,
-                    &community_mint_key,
-                    &(idx as u32).to_le_bytes()[..],
-                );
-                log::info!("calculated new proposal. idx {}, key {}", idx, proposal_key);
-            match rpc.get_account(&proposal_key) {
-                Ok(account) => {
-                    let mut account_tup = (proposal_key, account);
-                    let account_info = account_tup.into_account_info();
-                    match crate::types::get_proposal_wrapper(
-                        &account_info,
-                    ) {
-                        Ok(proposal) => {
-                            if let Err(err) = self.insert_proposal(&proposal) {
-                                log::error!("failed to insert new proposal proposal {}: {:#?}", proposal.key, err)
-                            }
-                        }
-                        Err(err) => {
-                            log::error!(
-                                "failed to get proposal account {:#?}",
-                                err
-                            );
-                        }
-                    }
-                }
-                Err(err) => {
-                    log::error!(
-                        "failed to get proposal account {:#?}",
-                        err
-                    );
-                    continue;
-                }
-            }
-            }
-            self.list_proposals()?
-        } else {
-            proposals
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_realm_and_governance() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_get_realm_governance.db".to_string(),
+            ..Default::default()
         };
-        // populate any actively voting proposals that are not in a draft state
-        proposals.iter_mut().for_each(|proposal| {
-            let proposal_account = rpc.get_account(&proposal.key).unwrap();
-            let mut proposal_account_tup = (proposal.key, proposal_account);
-            let proposal_account_info = proposal_account_tup.into_account_info();
-            let fresh_proposal = get_proposal_wrapper(&proposal_account_info).unwrap();
-            if proposal.proposal.state.ne(&fresh_proposal.proposal.state) {
-                log::warn!("on-disk state for {} of {:#?} differs from onchain state of {:#?}, updating...", proposal.key, proposal.proposal.state, fresh_proposal.proposal.state);
-                proposal.proposal.state = fresh_proposal.proposal.state;
-                self.insert_proposal(proposal).unwrap();
-            }
+        let db = Database::new(opts).unwrap();
 
-            let mut notif_cache_contains = false;
-            notif_cache
-                .voting_proposals_last_notification_time
-                .iter()
-                .for_each(|(proposal_key, _)| {
-                    if proposal_key.eq(&proposal.key) {
-                        notif_cache_contains = true;
-                    }
-                });
-            // attempt to finalize vote if possible, as this may not always be done on-chain, even
-            // if a vote has ended. really the only time this will likely be done on-chain is for a vote that is
-            // completed
-            proposal.finalize_vote(&mint_gov.governance.config, now);
-            if !proposal.has_vote_time_ended(&mint_gov.governance.config, now)
-                && !notif_cache_contains
-                && proposal
-                    .proposal
-                    .state
-                    .eq(&spl_governance::state::enums::ProposalState::Voting)
-            {
-                log::info!("updating notif cache with proposal {}", proposal.key);
-                notif_cache
-                    .voting_proposals_last_notification_time
-                    .push((proposal.key, 0));
-            }
-        });
+        let realm_key = get_tulip_realm_account();
+        let mut realm_account_tup = (realm_key, rpc.get_account(&realm_key).unwrap());
+        let realm = get_realm_wrapper(&realm_account_tup.into_account_info()).unwrap();
+        let (governance, _) = get_tulip_fixtures(&rpc);
+        db.insert_realm(&realm).unwrap();
+        db.insert_governance(&governance).unwrap();
 
-        // clear out any proposals from the notification cache that aren't actively voting
-        proposals.iter().for_each(|proposal| {
-            for (idx, (key, _)) in notif_cache
-                .clone()
-                .voting_proposals_last_notification_time
-                .iter()
-                .enumerate()
-            {
-                if proposal
-                    .proposal
-                    .state
-                    .ne(&spl_governance::state::enums::ProposalState::Voting)
-                    && proposal.key.eq(key)
-                {
-                    log::warn!("removing non voting proposal {} from notif cache", key);
-                    notif_cache
-                        .voting_proposals_last_notification_time
-                        .swap_remove(idx);
-                }
-            }
-        });
+        let got_realm = db.get_realm(realm_key).unwrap();
+        assert_eq!(got_realm.key, realm_key);
+        assert_eq!(got_realm.realm.name, realm.realm.name);
+        assert_eq!(got_realm.display_name(), realm.realm.name);
+        assert!(!got_realm.display_name().is_empty());
+        assert_eq!(got_realm.community_mint(), get_tulip_community_mint());
+        let got_governance = db.get_governance(governance.key).unwrap();
+        assert_eq!(got_governance.key, governance.key);
+        assert_eq!(
+            got_governance.governance.proposals_count,
+            governance.governance.proposals_count
+        );
 
-        self.insert_notif_cache_entry(&notif_cache)?;
-        self.db.flush()?;
-        Ok(())
+        let missing = Pubkey::new_unique();
+        let err = db.get_realm(missing).err().unwrap();
+        assert!(format!("{:#}", err).contains(&format!("realm {} not found", missing)));
+        let err = db.get_governance(missing).err().unwrap();
+        assert!(format!("{:#}", err).contains(&format!("governance {} not found", missing)));
+
+        std::fs::remove_dir_all("realms_sdk_get_realm_governance.db").unwrap();
     }
-}
+    #[test]
+    fn test_get_proposal() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_get_proposal.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use solana_client::rpc_client::RpcClient;
-    use solana_program::account_info::IntoAccountInfo;
-    use solana_program::pubkey::Pubkey;
-    use spl_governance::state::proposal::get_proposal_address;
-    use std::str::FromStr;
+        let names = ["first", "second"];
+        let proposals: Vec<ProposalV2Wrapper> = names
+            .iter()
+            .map(|name| {
+                let mut fixture = ProposalV2Wrapper {
+                    proposal: zeroed(),
+                    key: Pubkey::new_unique(),
+                };
+                fixture.proposal.name = name.to_string();
+                fixture
+            })
+            .collect();
+        for proposal in proposals.iter() {
+            db.insert_proposal(proposal).unwrap();
+        }
+        for proposal in proposals.iter() {
+            let got = db.get_proposal(proposal.key).unwrap();
+            assert_eq!(got.key, proposal.key);
+            assert_eq!(got.proposal.name, proposal.proposal.name);
+        }
+        let missing = Pubkey::new_unique();
+        let err = db.get_proposal(missing).err().unwrap();
+        assert!(format!("{:#}", err).contains(&format!("proposal {} not found", missing)));
 
-    pub fn get_tulip_realm_account() -> Pubkey {
-        Pubkey::from_str("413KSeuFUBSWDzfjU9BBqBAWYKmoR8mncrhV84WcGNAk").unwrap()
+        std::fs::remove_dir_all("realms_sdk_get_proposal.db").unwrap();
     }
-    pub fn get_tulip_council_mint() -> Pubkey {
-        Pubkey::from_str("EzSjCzCPwpchdQVaGJZYpgDNagzasKFVGJ66Dmut26FL").unwrap()
-    }
-    pub fn get_tulip_community_mint() -> Pubkey {
-        Pubkey::from_str("STuLiPmUCUtG1hQcwdc9de9sjYhVsYoucCiWqbApbpM").unwrap()
+    #[test]
+    fn test_list_proposals_paginated() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_list_paginated.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+
+        for _ in 0..5 {
+            db.insert_proposal(&ProposalV2Wrapper {
+                proposal: zeroed(),
+                key: Pubkey::new_unique(),
+            })
+            .unwrap();
+        }
+        let first_page = db.list_proposals_paginated(0, 3).unwrap();
+        let second_page = db.list_proposals_paginated(3, 3).unwrap();
+        assert_eq!(first_page.len(), 3);
+        assert_eq!(second_page.len(), 2);
+        assert!(db.list_proposals_paginated(5, 3).unwrap().is_empty());
+
+        // pages follow the same lexicographic ordering as list_proposals
+        let paged_keys: Vec<Pubkey> = first_page
+            .iter()
+            .chain(second_page.iter())
+            .map(|proposal| proposal.key)
+            .collect();
+        let keys: Vec<Pubkey> = db
+            .list_proposals()
+            .unwrap()
+            .iter()
+            .map(|proposal| proposal.key)
+            .collect();
+        assert_eq!(paged_keys, keys);
+
+        std::fs::remove_dir_all("realms_sdk_list_paginated.db").unwrap();
     }
-    pub fn get_tulip_governance_account() -> Pubkey {
-        spl_governance::state::governance::get_mint_governance_address(
-            &GOVERNANCE_PROGRAM,
-            &get_tulip_realm_account(),
-            &get_tulip_council_mint(),
-        )
+    #[test]
+    fn test_delete_proposal() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_delete_proposal.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        for key in keys.iter() {
+            db.insert_proposal(&ProposalV2Wrapper {
+                proposal: zeroed(),
+                key: *key,
+            })
+            .unwrap();
+        }
+        assert_eq!(db.list_proposals().unwrap().len(), 2);
+
+        db.delete_proposal(keys[0]).unwrap();
+        let proposals = db.list_proposals().unwrap();
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].key, keys[1]);
+        assert!(db.get_proposal(keys[0]).is_err());
+        // deleting a proposal which isn't stored is an error
+        assert!(db.delete_proposal(keys[0]).is_err());
+
+        std::fs::remove_dir_all("realms_sdk_delete_proposal.db").unwrap();
     }
     #[tokio::test(flavor = "multi_thread")]
     async fn test_database_simple() {
@@ -453,7 +2521,7 @@ mod test {
         std::fs::remove_dir_all("realms_sdk_list_voting.db").unwrap();
     }
     #[tokio::test(flavor = "multi_thread")]
-    async fn test_populate_database_with_mint() {
+    async fn test_populate_database_with_governances() {
         let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
 
         let opts = tulip_sled_util::config::DbOpts {
@@ -463,10 +2531,10 @@ mod test {
 
         let db = Database::new(opts).unwrap();
 
-        db.populate_database_with_mint_governance(
+        db.populate_database_with_governances(
             get_tulip_realm_account(),
-            get_tulip_council_mint(),
-            get_tulip_community_mint(),
+            &[get_tulip_governance_account()],
+            &[get_tulip_community_mint(), get_tulip_council_mint()],
             Utc::now(),
             &rpc,
         )
@@ -502,9 +2570,8 @@ mod test {
             notif_cache.voting_proposals_last_notification_time = vec![];
             db.insert_notif_cache_entry(&notif_cache).unwrap();
             db.sync_notif_cache_with_proposals(
-                get_tulip_realm_account(),
-                get_tulip_community_mint(),
-                get_tulip_council_mint(),
+                get_tulip_governance_account(),
+                &[get_tulip_community_mint(), get_tulip_council_mint()],
                 Utc::now(),
                 &rpc,
             )
@@ -513,4 +2580,83 @@ mod test {
         }
         std::fs::remove_dir_all("realms_sdk_populate_mint.db").unwrap();
     }
+    #[test]
+    fn test_schema_version() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_schema_version.db".to_string(),
+            ..Default::default()
+        };
+        // new databases are stamped with the current version
+        let db = Database::new(opts.clone()).unwrap();
+        assert_eq!(db.schema_version().unwrap(), Some(SCHEMA_VERSION));
+
+        // simulate a database written before the schema version was tracked
+        db.set_schema_version(0).unwrap();
+        db.db
+            .open_tree(DbTrees::Custom(VOTE_RECORD_TREE))
+            .unwrap()
+            .insert(&CachedVoteRecords {
+                proposal_key: Pubkey::new_unique(),
+                fetched_at: 0,
+                vote_records: vec![],
+            })
+            .unwrap();
+        drop(db);
+        let db = Database::new(opts.clone()).unwrap();
+        assert_eq!(db.schema_version().unwrap(), Some(SCHEMA_VERSION));
+        assert!(db
+            .db
+            .open_tree(DbTrees::Custom(VOTE_RECORD_TREE))
+            .unwrap()
+            .iter()
+            .next()
+            .is_none());
+
+        // vote records cached without their account keys are dropped as well
+        db.set_schema_version(1).unwrap();
+        db.db
+            .open_tree(DbTrees::Custom(VOTE_RECORD_TREE))
+            .unwrap()
+            .insert(&CachedVoteRecords {
+                proposal_key: Pubkey::new_unique(),
+                fetched_at: 0,
+                vote_records: vec![],
+            })
+            .unwrap();
+        drop(db);
+        let db = Database::new(opts.clone()).unwrap();
+        assert_eq!(db.schema_version().unwrap(), Some(SCHEMA_VERSION));
+        assert!(db
+            .db
+            .open_tree(DbTrees::Custom(VOTE_RECORD_TREE))
+            .unwrap()
+            .iter()
+            .next()
+            .is_none());
+
+        // databases written by a newer version of the bot are rejected
+        db.set_schema_version(SCHEMA_VERSION + 1).unwrap();
+        drop(db);
+        let err = Database::new(opts).err().unwrap();
+        assert!(format!("{:#}", err).contains(&format!(
+            "database schema version {} is newer than the supported version {}",
+            SCHEMA_VERSION + 1,
+            SCHEMA_VERSION
+        )));
+        std::fs::remove_dir_all("realms_sdk_schema_version.db").unwrap();
+    }
+    #[test]
+    fn test_database_already_in_use() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_already_in_use.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts.clone()).unwrap();
+        let err = Database::new(opts).err().unwrap();
+        assert!(err.to_string().contains(
+            "another instance is already using the database at realms_sdk_already_in_use.db"
+        ));
+        drop(db);
+        std::fs::remove_dir_all("realms_sdk_already_in_use.db").unwrap();
+    }
 }
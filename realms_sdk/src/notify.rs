@@ -0,0 +1,243 @@
+//! notification sinks which proposal events can be delivered to in addition to the discord bot,
+//! such as slack or a generic webhook
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// the kind of proposal event being delivered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalEventKind {
+    /// a proposal was created
+    NewProposal,
+    /// a periodic update of the votes cast on a proposal which is accepting votes
+    VotingStats,
+    /// voting on a proposal concluded
+    Concluded,
+}
+
+/// a proposal event, with vote weights expressed as ui amounts of the governing token mint
+#[derive(Clone, Debug, Serialize)]
+pub struct ProposalEvent {
+    pub kind: ProposalEventKind,
+    pub realm_name: String,
+    pub governance_key: String,
+    pub proposal_key: String,
+    pub name: String,
+    pub description: String,
+    /// link to the proposal in the realms ui
+    pub url: String,
+    pub approval_votes: f64,
+    pub deny_votes: f64,
+    pub abstain_votes: f64,
+    /// the number of voters, only known for voting stats
+    pub voter_count: Option<u64>,
+    /// unix timestamp at which voting ends, only known for voting stats
+    pub vote_ends_at: Option<i64>,
+    /// the state voting concluded in, only known for concluded proposals
+    pub result: Option<String>,
+}
+
+impl ProposalEvent {
+    /// returns the title used when rendering the event for humans
+    pub fn title(&self) -> &'static str {
+        match self.kind {
+            ProposalEventKind::NewProposal => "New Proposal Detected",
+            ProposalEventKind::VotingStats => "Proposal Voting Stats",
+            ProposalEventKind::Concluded => "Proposal Concluded",
+        }
+    }
+}
+
+/// how long sinks wait for a response before giving up on delivering an event. sinks are
+/// awaited by the worker loop, so a webhook which hangs must not stall it
+const SINK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// returns the http client used to deliver events, which times out after `SINK_TIMEOUT`
+fn sink_client() -> reqwest::Client {
+    // Client::new panics under the same conditions
+    reqwest::Client::builder()
+        .timeout(SINK_TIMEOUT)
+        .build()
+        .expect("failed to build http client")
+}
+
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    async fn send_proposal_event(&self, event: &ProposalEvent) -> Result<()>;
+}
+
+/// posts proposal events as json to a webhook
+pub struct WebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: sink_client(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for WebhookSink {
+    async fn send_proposal_event(&self, event: &ProposalEvent) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(event)
+            .send()
+            .await
+            .context("failed to send webhook request")?
+            .error_for_status()
+            .context("webhook returned an error")?;
+        Ok(())
+    }
+}
+
+/// posts proposal events as embeds to a discord webhook, allowing events to be delivered
+/// to servers the bot has not been added to
+pub struct DiscordWebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl DiscordWebhookSink {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: sink_client(),
+        }
+    }
+    /// returns the discord webhook payload for the event
+    pub fn payload(event: &ProposalEvent) -> serde_json::Value {
+        let mut fields = vec![json!({"name": "name", "value": event.name, "inline": false})];
+        if let Some(result) = event.result.as_ref() {
+            fields.push(json!({"name": "result", "value": result, "inline": false}));
+        }
+        fields.extend([
+            json!({
+                "name": "approval vote count",
+                "value": event.approval_votes.to_string(),
+                "inline": false,
+            }),
+            json!({
+                "name": "deny vote count",
+                "value": event.deny_votes.to_string(),
+                "inline": false,
+            }),
+            json!({
+                "name": "abstain vote count",
+                "value": event.abstain_votes.to_string(),
+                "inline": false,
+            }),
+        ]);
+        if let Some(voter_count) = event.voter_count {
+            fields.push(json!({
+                "name": "voters",
+                "value": format!("{} voters", voter_count),
+                "inline": false,
+            }));
+        }
+        json!({
+            "embeds": [{
+                "title": event.title(),
+                "url": event.url,
+                "author": {"name": event.realm_name},
+                "fields": fields,
+            }]
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationSink for DiscordWebhookSink {
+    async fn send_proposal_event(&self, event: &ProposalEvent) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(&DiscordWebhookSink::payload(event))
+            .send()
+            .await
+            .context("failed to send discord webhook request")?
+            .error_for_status()
+            .context("discord webhook returned an error")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::mock_server;
+
+    fn event() -> ProposalEvent {
+        ProposalEvent {
+            kind: ProposalEventKind::NewProposal,
+            realm_name: "Tulip Protocol".to_string(),
+            governance_key: "governance".to_string(),
+            proposal_key: "proposal".to_string(),
+            name: "raise the fee".to_string(),
+            description: "https://example.com".to_string(),
+            url: "https://realms.today/dao/tulip/proposal/proposal".to_string(),
+            approval_votes: 1.5,
+            deny_votes: 0.0,
+            abstain_votes: 0.0,
+            voter_count: None,
+            vote_ends_at: None,
+            result: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_sink() {
+        let (url, handle) = mock_server("200 OK", "").await;
+        WebhookSink::new(url)
+            .send_proposal_event(&event())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&handle.await.unwrap()).unwrap();
+        assert_eq!(body["kind"], "new_proposal");
+        assert_eq!(body["proposal_key"], "proposal");
+        assert_eq!(body["approval_votes"], 1.5);
+
+        // error responses are surfaced
+        let (url, handle) = mock_server("500 Internal Server Error", "").await;
+        assert!(WebhookSink::new(url)
+            .send_proposal_event(&event())
+            .await
+            .is_err());
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_discord_webhook_sink() {
+        let (url, handle) = mock_server("204 No Content", "").await;
+        DiscordWebhookSink::new(url)
+            .send_proposal_event(&event())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&handle.await.unwrap()).unwrap();
+        assert_eq!(body["embeds"][0]["title"], "New Proposal Detected");
+        assert_eq!(body["embeds"][0]["author"]["name"], "Tulip Protocol");
+        assert_eq!(body["embeds"][0]["fields"][1]["value"], "1.5");
+    }
+
+    #[test]
+    fn test_concluded_payload() {
+        let event = ProposalEvent {
+            kind: ProposalEventKind::Concluded,
+            result: Some("Succeeded".to_string()),
+            ..event()
+        };
+        assert_eq!(serde_json::to_value(&event).unwrap()["kind"], "concluded");
+        let payload = DiscordWebhookSink::payload(&event);
+        assert_eq!(payload["embeds"][0]["title"], "Proposal Concluded");
+        assert_eq!(payload["embeds"][0]["fields"][1]["name"], "result");
+        assert_eq!(payload["embeds"][0]["fields"][1]["value"], "Succeeded");
+    }
+}
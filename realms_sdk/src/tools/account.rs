@@ -0,0 +1,44 @@
+//! fetches governance program accounts and deserializes them with `get_account_data`, without
+//! callers having to build an `AccountInfo` from the fetched account
+
+use crate::utils::AccountFetcher;
+use crate::GOVERNANCE_PROGRAM;
+use anyhow::{anyhow, Context, Result};
+use borsh::BorshDeserialize;
+use solana_program::account_info::IntoAccountInfo;
+use solana_program::program_pack::IsInitialized;
+use solana_program::pubkey::Pubkey;
+use spl_governance_tools::account::get_account_data;
+
+/// fetches the account and deserializes it as `T`, checking that it is owned by the
+/// governance program and initialized. unlike the wrapper getters such as
+/// `get_governance_wrapper`, accounts of older versions are not converted
+pub fn fetch_and_deserialize<T: BorshDeserialize + IsInitialized>(
+    rpc: &impl AccountFetcher,
+    key: Pubkey,
+) -> Result<T> {
+    let account = rpc
+        .fetch_account(&key)
+        .with_context(|| format!("failed to get account {}", key))?;
+    let mut account_tup = (key, account);
+    get_account_data::<T>(&GOVERNANCE_PROGRAM, &account_tup.into_account_info())
+        .map_err(|err| anyhow!("failed to deserialize account {}: {:#?}", key, err))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::{get_tulip_governance_account, get_tulip_realm_account};
+    use solana_client::rpc_client::RpcClient;
+    use spl_governance::state::governance::GovernanceV2;
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_and_deserialize() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+        let governance: GovernanceV2 =
+            fetch_and_deserialize(&rpc, get_tulip_governance_account()).unwrap();
+        assert_eq!(governance.realm, get_tulip_realm_account());
+        assert!(governance.proposals_count > 0);
+        // accounts which don't exist can't be fetched
+        assert!(fetch_and_deserialize::<GovernanceV2>(&rpc, Pubkey::new_unique()).is_err());
+    }
+}
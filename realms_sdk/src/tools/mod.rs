@@ -0,0 +1,2 @@
+//! helpers built on top of spl-governance-tools
+pub mod account;
@@ -1,5 +1,11 @@
+use anyhow::anyhow;
 use chrono::prelude::*;
-use spl_governance::state::governance::GovernanceConfig;
+use spl_governance::state::{
+    enums::{MintMaxVoteWeightSource, ProposalState, VoteThresholdPercentage},
+    governance::GovernanceConfig,
+    vote_record::{Vote, VoteRecordV2},
+};
+use std::collections::HashSet;
 
 use crate::utils::governance_notif_cache_key;
 
@@ -11,11 +17,11 @@ pub struct NotifCacheEntry {
     /// the total number of proposals tracked by the governance account the last time
     /// a sample was taken
     pub last_proposals_count: u32,
-    /// a vector at which the time a proposal which is actively voting
-    /// had a notification sent out, each element contains the values of (proposal_key, notif_time)
-    ///
-    /// if notif_time is 0, then it means no notification was sent out
-    pub voting_proposals_last_notification_time: Vec<(Pubkey, i64)>,
+    /// the notification state of each proposal which is actively voting
+    pub voting_proposals_last_notification_time: Vec<ProposalNotifState>,
+    /// the discord threads created for proposals when notifications are posted as threads,
+    /// each element contains the values of (proposal_key, thread_id)
+    pub proposal_threads: Vec<(Pubkey, u64)>,
 }
 
 impl DbKey for NotifCacheEntry {
@@ -26,6 +32,414 @@ impl DbKey for NotifCacheEntry {
     }
 }
 
+/// the notification state of a proposal which is actively voting
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct ProposalNotifState {
+    pub proposal_key: Pubkey,
+    /// the time at which a voting stats notification was last sent out, 0 if none was sent
+    pub last_notif_time: i64,
+    /// set once the final reminder that voting is about to end was sent out
+    pub final_reminder_sent: bool,
+    /// set once the result of the proposal was announced. concluded proposals are dropped from
+    /// the cache once synced, so `Database::is_result_announced` remains the lasting record
+    pub result_announced: bool,
+}
+
+impl ProposalNotifState {
+    pub fn new(proposal_key: Pubkey, last_notif_time: i64) -> Self {
+        Self {
+            proposal_key,
+            last_notif_time,
+            final_reminder_sent: false,
+            result_announced: false,
+        }
+    }
+    /// returns true if the final reminder should be sent, that is voting ends within
+    /// `final_reminder_hours` of `now` and no reminder was sent yet. a `final_reminder_hours`
+    /// of 0 disables the reminder
+    pub fn needs_final_reminder(
+        &self,
+        ends_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+        final_reminder_hours: i64,
+    ) -> bool {
+        final_reminder_hours > 0
+            && !self.final_reminder_sent
+            && now < ends_at
+            && ends_at.signed_duration_since(now) <= chrono::Duration::hours(final_reminder_hours)
+    }
+}
+
+/// converts the (proposal_key, notif_time) tuples used by legacy layouts of NotifCacheEntry
+fn notif_states_from_tuples(tuples: Vec<(Pubkey, i64)>) -> Vec<ProposalNotifState> {
+    tuples
+        .into_iter()
+        .map(|(proposal_key, last_notif_time)| {
+            ProposalNotifState::new(proposal_key, last_notif_time)
+        })
+        .collect()
+}
+
+/// the on-disk layout of NotifCacheEntry prior to the addition of `proposal_threads`
+#[derive(BorshDeserialize)]
+pub(crate) struct LegacyNotifCacheEntry {
+    governance_key: Pubkey,
+    last_proposals_count: u32,
+    voting_proposals_last_notification_time: Vec<(Pubkey, i64)>,
+}
+
+impl From<LegacyNotifCacheEntry> for NotifCacheEntry {
+    fn from(legacy: LegacyNotifCacheEntry) -> Self {
+        Self {
+            governance_key: legacy.governance_key,
+            last_proposals_count: legacy.last_proposals_count,
+            voting_proposals_last_notification_time: notif_states_from_tuples(
+                legacy.voting_proposals_last_notification_time,
+            ),
+            proposal_threads: vec![],
+        }
+    }
+}
+
+/// the on-disk layout of ProposalNotifState prior to the addition of `result_announced`
+#[derive(BorshDeserialize)]
+pub(crate) struct LegacyProposalNotifState {
+    proposal_key: Pubkey,
+    last_notif_time: i64,
+    final_reminder_sent: bool,
+}
+
+/// the on-disk layout of NotifCacheEntry prior to the addition of
+/// `ProposalNotifState::result_announced`
+#[derive(BorshDeserialize)]
+pub(crate) struct LegacyReminderNotifCacheEntry {
+    governance_key: Pubkey,
+    last_proposals_count: u32,
+    voting_proposals_last_notification_time: Vec<LegacyProposalNotifState>,
+    proposal_threads: Vec<(Pubkey, u64)>,
+}
+
+impl From<LegacyReminderNotifCacheEntry> for NotifCacheEntry {
+    fn from(legacy: LegacyReminderNotifCacheEntry) -> Self {
+        Self {
+            governance_key: legacy.governance_key,
+            last_proposals_count: legacy.last_proposals_count,
+            voting_proposals_last_notification_time: legacy
+                .voting_proposals_last_notification_time
+                .into_iter()
+                .map(|state| ProposalNotifState {
+                    proposal_key: state.proposal_key,
+                    last_notif_time: state.last_notif_time,
+                    final_reminder_sent: state.final_reminder_sent,
+                    result_announced: false,
+                })
+                .collect(),
+            proposal_threads: legacy.proposal_threads,
+        }
+    }
+}
+
+/// the on-disk layout of NotifCacheEntry prior to the addition of `ProposalNotifState`, which
+/// tracked proposals as (proposal_key, notif_time) tuples
+#[derive(BorshDeserialize)]
+pub(crate) struct LegacyTupleNotifCacheEntry {
+    governance_key: Pubkey,
+    last_proposals_count: u32,
+    voting_proposals_last_notification_time: Vec<(Pubkey, i64)>,
+    proposal_threads: Vec<(Pubkey, u64)>,
+}
+
+impl From<LegacyTupleNotifCacheEntry> for NotifCacheEntry {
+    fn from(legacy: LegacyTupleNotifCacheEntry) -> Self {
+        Self {
+            governance_key: legacy.governance_key,
+            last_proposals_count: legacy.last_proposals_count,
+            voting_proposals_last_notification_time: notif_states_from_tuples(
+                legacy.voting_proposals_last_notification_time,
+            ),
+            proposal_threads: legacy.proposal_threads,
+        }
+    }
+}
+
+/// records that a "New Proposal Detected" notification, or the result of a proposal, was sent
+/// for a proposal, so that proposals are not re-announced when the bot restarts
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct AnnouncedProposal {
+    pub proposal_key: Pubkey,
+    /// the unix timestamp at which the notification was sent
+    pub announced_at: i64,
+}
+
+impl DbKey for AnnouncedProposal {
+    fn key(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.proposal_key.to_bytes().to_vec())
+    }
+}
+
+/// a proposal whose voting concluded, and whose result has yet to be announced. stored when the
+/// conclusion is detected, so that an announcement which fails is retried
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PendingResult {
+    pub proposal_key: Pubkey,
+    pub governance_key: Pubkey,
+    /// the unix timestamp at which the conclusion was detected
+    pub concluded_at: i64,
+}
+
+impl DbKey for PendingResult {
+    fn key(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.proposal_key.to_bytes().to_vec())
+    }
+}
+
+/// the vote records of a proposal as of `fetched_at`, used to avoid scanning program
+/// accounts on every iteration of the worker loop
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct CachedVoteRecords {
+    pub proposal_key: Pubkey,
+    /// the unix timestamp at which the vote records were fetched
+    pub fetched_at: i64,
+    pub vote_records: Vec<VoteRecordV2Wrapper>,
+}
+
+impl DbKey for CachedVoteRecords {
+    fn key(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.proposal_key.to_bytes().to_vec())
+    }
+}
+
+/// entry written, and read back to verify the database is writable
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq, Eq)]
+pub(crate) struct SelfTestEntry {
+    pub(crate) written_at: i64,
+}
+
+impl DbKey for SelfTestEntry {
+    fn key(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(b"self_test".to_vec())
+    }
+}
+
+/// the version of the on-disk layout of the database
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq, Eq)]
+pub(crate) struct SchemaVersionEntry {
+    pub(crate) schema_version: u32,
+}
+
+impl DbKey for SchemaVersionEntry {
+    fn key(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(b"schema_version".to_vec())
+    }
+}
+
+/// summary of seeding the database, reporting how many proposals were loaded
+/// out of the proposals count tracked by the governance accounts
+#[derive(Clone, Debug, Default)]
+pub struct SeedSummary {
+    pub expected_proposals: u32,
+    pub loaded_proposals: u32,
+    /// the (governance_key, index) of the proposals which could not be loaded
+    pub missing_indices: Vec<(Pubkey, u32)>,
+}
+
+/// a snapshot of the cached accounts and notification caches, as returned by `Database::export`
+/// and restored by `Database::import`. the spl-governance types don't implement serde, so entries hold their borsh encoding
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DatabaseExport {
+    pub schema_version: u32,
+    pub realms: Vec<ExportedEntry>,
+    pub governances: Vec<ExportedEntry>,
+    pub proposals: Vec<ExportedEntry>,
+    pub notif_caches: Vec<ExportedEntry>,
+}
+
+/// the number of entries inserted and skipped by `Database::import`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// entries which were already stored, and were not overwritten
+    pub skipped: usize,
+}
+
+/// the number of entries cached by the database, returned by `Database::stats`
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct DbStats {
+    pub governances: usize,
+    pub proposals: usize,
+    pub realms: usize,
+    pub notif_caches: usize,
+    /// the number of voting proposals tracked by the notification caches
+    pub voting_proposals: usize,
+}
+
+/// an exported database entry, keyed by the account it describes
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExportedEntry {
+    pub key: String,
+    /// the base64 encoding of the borsh serialized entry
+    pub data: String,
+}
+
+impl ExportedEntry {
+    pub fn new<T: BorshSerialize>(key: Pubkey, value: &T) -> Result<Self> {
+        Ok(Self {
+            key: key.to_string(),
+            data: base64::encode(value.try_to_vec()?),
+        })
+    }
+    /// decodes and deserializes the entry
+    pub fn decode<T: BorshDeserialize>(&self) -> Result<T> {
+        let data = base64::decode(&self.data)
+            .with_context(|| format!("failed to decode entry {}", self.key))?;
+        T::try_from_slice(&data)
+            .with_context(|| format!("failed to deserialize entry {}", self.key))
+    }
+}
+
+/// a change in a proposal's state, detected while syncing cached proposals with their on-chain state
+#[derive(Clone, Debug)]
+pub struct ProposalStateTransition {
+    pub proposal_key: Pubkey,
+    pub previous_state: ProposalState,
+    pub state: ProposalState,
+}
+
+impl ProposalStateTransition {
+    /// compares two snapshots of the same proposal, returning the transition between them
+    /// if the proposal's state changed
+    pub fn from_snapshots(
+        previous: &ProposalV2Wrapper,
+        current: &ProposalV2Wrapper,
+    ) -> Option<Self> {
+        if previous.proposal.state.eq(&current.proposal.state) {
+            return None;
+        }
+        Some(Self {
+            proposal_key: current.key,
+            previous_state: previous.proposal.state.clone(),
+            state: current.proposal.state.clone(),
+        })
+    }
+    /// returns true if the transition moved the proposal out of the Voting state,
+    /// meaning the outcome of the vote is known
+    pub fn is_conclusion(&self) -> bool {
+        self.previous_state.eq(&ProposalState::Voting) && self.state.ne(&ProposalState::Voting)
+    }
+}
+
+/// tally of the non-relinquished vote records of a proposal, with vote weights
+/// expressed in the raw amount of the governing token mint
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VoteTally {
+    /// the weight of approval votes, summed across the approved options of multi choice proposals
+    pub approval_votes: u64,
+    pub deny_votes: u64,
+    /// the number of distinct voters
+    pub voter_count: u64,
+    pub abstain_votes: u64,
+}
+
+impl VoteTally {
+    pub fn from_vote_records(vote_records: &[VoteRecordV2]) -> Self {
+        let mut tally = VoteTally::default();
+        let mut voters = HashSet::with_capacity(vote_records.len());
+        // do not track relinquished votes
+        for vote_record in vote_records
+            .iter()
+            .filter(|vote_record| !vote_record.is_relinquished)
+        {
+            match &vote_record.vote {
+                Vote::Approve(choices) => {
+                    // each approved option receives the voter's weight scaled by the choice's
+                    // weight percentage, matching how the governance program tallies options
+                    tally.approval_votes += choices
+                        .iter()
+                        .map(|choice| {
+                            (vote_record.voter_weight as u128 * choice.weight_percentage as u128
+                                / 100) as u64
+                        })
+                        .sum::<u64>()
+                }
+                Vote::Deny => tally.deny_votes += vote_record.voter_weight,
+                Vote::Abstain => tally.abstain_votes += vote_record.voter_weight,
+                _ => {
+                    log::warn!("unsupported vote type {:#?}", vote_record.vote);
+                    continue;
+                }
+            }
+            voters.insert(vote_record.governing_token_owner);
+        }
+        tally.voter_count = voters.len() as u64;
+        tally
+    }
+    /// returns the approval and deny votes as ui amounts of the governing token mint
+    pub fn ui_amounts(&self, voter_mint_decimals: u8) -> (f64, f64) {
+        (
+            crate::utils::ui_amount(self.approval_votes, voter_mint_decimals),
+            crate::utils::ui_amount(self.deny_votes, voter_mint_decimals),
+        )
+    }
+    /// returns the abstain votes as a ui amount of the governing token mint
+    pub fn ui_abstain_votes(&self, voter_mint_decimals: u8) -> f64 {
+        crate::utils::ui_amount(self.abstain_votes, voter_mint_decimals)
+    }
+}
+
+/// the on-disk layout of VoteTally prior to the addition of `abstain_votes`
+#[derive(BorshDeserialize)]
+pub(crate) struct LegacyVoteTally {
+    approval_votes: u64,
+    deny_votes: u64,
+    voter_count: u64,
+}
+
+impl From<LegacyVoteTally> for VoteTally {
+    fn from(legacy: LegacyVoteTally) -> Self {
+        Self {
+            approval_votes: legacy.approval_votes,
+            deny_votes: legacy.deny_votes,
+            voter_count: legacy.voter_count,
+            abstain_votes: 0,
+        }
+    }
+}
+
+/// vote tallies recorded for a proposal, so that historical results do not require
+/// re-scanning vote records which may since have been relinquished, or closed
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default)]
+pub struct ProposalTallyEntry {
+    pub proposal_key: Pubkey,
+    /// the tally computed from the most recently fetched vote records
+    pub last_tally: VoteTally,
+    /// the tally at the time voting ended, set once the proposal leaves the Voting state
+    pub final_tally: Option<VoteTally>,
+}
+
+impl DbKey for ProposalTallyEntry {
+    fn key(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.proposal_key.to_bytes().to_vec())
+    }
+}
+
+/// the on-disk layout of ProposalTallyEntry prior to the addition of `VoteTally::abstain_votes`
+#[derive(BorshDeserialize)]
+pub(crate) struct LegacyProposalTallyEntry {
+    proposal_key: Pubkey,
+    last_tally: LegacyVoteTally,
+    final_tally: Option<LegacyVoteTally>,
+}
+
+impl From<LegacyProposalTallyEntry> for ProposalTallyEntry {
+    fn from(legacy: LegacyProposalTallyEntry) -> Self {
+        Self {
+            proposal_key: legacy.proposal_key,
+            last_tally: legacy.last_tally.into(),
+            final_tally: legacy.final_tally.map(Into::into),
+        }
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
 pub struct GovernanceV2Wrapper {
     pub governance: GovernanceV2,
@@ -49,6 +463,18 @@ impl DbKey for ProposalV2Wrapper {
     }
 }
 
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq)]
+pub struct VoteRecordV2Wrapper {
+    pub vote_record: VoteRecordV2,
+    pub key: Pubkey,
+}
+
+impl DbKey for VoteRecordV2Wrapper {
+    fn key(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.key.to_bytes().to_vec())
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
 pub struct RealmV2Wrapper {
     pub realm: RealmV2,
@@ -81,6 +507,18 @@ pub fn get_proposal_wrapper(proposal_account: &AccountInfo) -> Result<ProposalV2
     })
 }
 
+/// returns a VoteRecordV2Wrapper if the account can be deserialized into a VoteRecordV2 account
+pub fn get_vote_record_wrapper(vote_record_account: &AccountInfo) -> Result<VoteRecordV2Wrapper> {
+    let vote_record_data = spl_governance::state::vote_record::get_vote_record_data(
+        &GOVERNANCE_PROGRAM,
+        vote_record_account,
+    )?;
+    Ok(VoteRecordV2Wrapper {
+        vote_record: vote_record_data,
+        key: *vote_record_account.key,
+    })
+}
+
 /// returns a GovernanceV2Wrapper if the account can be deserialized into a ProposalV2 account
 pub fn get_governance_wrapper(governance_account: &AccountInfo) -> Result<GovernanceV2Wrapper> {
     let gov_data = spl_governance::state::governance::get_governance_data(
@@ -93,7 +531,27 @@ pub fn get_governance_wrapper(governance_account: &AccountInfo) -> Result<Govern
     })
 }
 
+impl RealmV2Wrapper {
+    /// returns the name of the realm, as displayed by the realms ui
+    pub fn display_name(&self) -> &str {
+        &self.realm.name
+    }
+    /// returns the community token mint, whose decimals vote weights are displayed with
+    pub fn community_mint(&self) -> Pubkey {
+        self.realm.community_mint
+    }
+}
+
 impl ProposalV2Wrapper {
+    /// fetches the proposal's vote records, returning their tally
+    pub fn vote_tally(&self, rpc: &impl crate::utils::VoteRecordFetcher) -> Result<VoteTally> {
+        let vote_records: Vec<VoteRecordV2> = rpc
+            .fetch_vote_records(self.key)?
+            .into_iter()
+            .map(|wrapper| wrapper.vote_record)
+            .collect();
+        Ok(VoteTally::from_vote_records(&vote_records))
+    }
     /// similar to ProposalV2::has_vote_time_ended except makes comparisons using timestampts coerced
     /// to utc timezone
     pub fn has_vote_time_ended(
@@ -114,6 +572,20 @@ impl ProposalV2Wrapper {
             false
         }
     }
+    /// returns true if the outcome of the proposal has been decided. proposals can be decided
+    /// before `max_voting_time` elapses when the vote is tipped, which `has_vote_time_ended`
+    /// does not detect
+    pub fn is_decided(&self) -> bool {
+        matches!(
+            self.proposal.state,
+            ProposalState::Succeeded
+                | ProposalState::Defeated
+                | ProposalState::Completed
+                | ProposalState::Cancelled
+                | ProposalState::Executing
+                | ProposalState::ExecutingWithErrors
+        )
+    }
     /// this is a very basic version of ProposalV2::finalize_vote and simply sets `voting_compled_at` if the current
     /// timestamp is past the end at time.
     ///
@@ -138,6 +610,25 @@ impl ProposalV2Wrapper {
             }
         }
     }
+    /// returns the proposal's description truncated to `max_len` characters, or a placeholder
+    /// if the proposal has no description
+    pub fn description_or_default(&self, max_len: usize) -> String {
+        let description = self.proposal.description_link.trim();
+        if description.is_empty() {
+            return "no description provided".to_string();
+        }
+        crate::utils::truncate_chars(description, max_len).to_string()
+    }
+    /// returns the time at which the proposal was created
+    pub fn created_at(&self) -> DateTime<Utc> {
+        crate::utils::date_time_from_timestamp(self.proposal.draft_at)
+    }
+    /// returns the time at which voting started, if the proposal has entered voting
+    pub fn vote_started_at(&self) -> Option<DateTime<Utc>> {
+        self.proposal
+            .voting_at
+            .map(crate::utils::date_time_from_timestamp)
+    }
     pub fn vote_ends_at(&self, governance_config: &GovernanceConfig) -> Option<DateTime<Utc>> {
         if let Some(voting_at) = self.proposal.voting_at {
             crate::utils::date_time_from_timestamp(voting_at).checked_add_signed(
@@ -147,4 +638,492 @@ impl ProposalV2Wrapper {
             None
         }
     }
+    /// mirrors the private `ProposalV2::get_max_vote_weight`, returning the max vote weight of the
+    /// proposal given its realm, and the supply of the proposal's governing token mint. unlike
+    /// the program, absolute max vote weight sources are supported
+    pub fn max_vote_weight(&self, realm: &RealmV2, mint_supply: u64) -> Result<u64> {
+        // max vote weight fraction is only used for the community mint
+        if Some(self.proposal.governing_token_mint) == realm.config.council_mint {
+            return Ok(mint_supply);
+        }
+        match realm.config.community_mint_max_vote_weight_source {
+            MintMaxVoteWeightSource::SupplyFraction(fraction) => {
+                if fraction == MintMaxVoteWeightSource::SUPPLY_FRACTION_BASE {
+                    return Ok(mint_supply);
+                }
+                let max_vote_weight = (mint_supply as u128)
+                    .checked_mul(fraction as u128)
+                    .ok_or_else(|| anyhow!("max vote weight overflow"))?
+                    .checked_div(MintMaxVoteWeightSource::SUPPLY_FRACTION_BASE as u128)
+                    .ok_or_else(|| anyhow!("max vote weight overflow"))?
+                    as u64;
+                // when a fraction is used it's possible more votes were cast than the
+                // calculated max vote weight, in which case the cast weight is used instead
+                Ok(max_vote_weight.max(self.cast_vote_weight()))
+            }
+            // the program doesn't support absolute values yet, so treat them as a fixed max
+            // vote weight, clamped to the cast weight in the same way as fractions
+            MintMaxVoteWeightSource::Absolute(value) => Ok(value.max(self.cast_vote_weight())),
+        }
+    }
+    /// returns the fraction of the governing mint's supply which voted on the proposal, counting
+    /// the approval and deny votes. returns 0 when the supply is 0
+    pub fn participation_rate(&self, mint_supply: u64) -> f64 {
+        if mint_supply == 0 {
+            return 0.0;
+        }
+        let approval_votes: u128 = self
+            .proposal
+            .options
+            .iter()
+            .map(|option| option.vote_weight as u128)
+            .sum();
+        let votes = approval_votes + self.proposal.deny_vote_weight.unwrap_or(0) as u128;
+        votes as f64 / mint_supply as f64
+    }
+    /// returns the approval vote weight as a percentage of the weight required to pass the
+    /// proposal, capped at 100. the required weight mirrors `get_min_vote_threshold_weight`,
+    /// rounding up the threshold percentage of `max_vote_weight`
+    pub fn approval_progress(&self, config: &GovernanceConfig, max_vote_weight: u64) -> f64 {
+        let threshold_percentage = match config.vote_threshold_percentage {
+            VoteThresholdPercentage::YesVote(percentage) => percentage,
+            VoteThresholdPercentage::Quorum(percentage) => percentage,
+        };
+        let numerator = (threshold_percentage as u128) * (max_vote_weight as u128);
+        let mut threshold = numerator / 100;
+        if threshold * 100 < numerator {
+            threshold += 1;
+        }
+        if threshold == 0 {
+            return 100.0;
+        }
+        let approval_votes = self
+            .proposal
+            .options
+            .iter()
+            .map(|option| option.vote_weight)
+            .max()
+            .unwrap_or(0);
+        (approval_votes as f64 / threshold as f64 * 100.0).min(100.0)
+    }
+    /// returns the state the proposal would conclude with given its current votes, without
+    /// modifying the proposal. proposals which are not voting return their current state.
+    ///
+    /// the projection runs the program's own vote resolution against a copy of the proposal,
+    /// first checking whether the vote has been tipped, and otherwise resolving the vote as
+    /// if voting ended now. ties between the approve and deny votes resolve as Defeated
+    pub fn projected_state(
+        &self,
+        config: &GovernanceConfig,
+        max_vote_weight: u64,
+    ) -> ProposalState {
+        if self.proposal.state != ProposalState::Voting {
+            return self.proposal.state.clone();
+        }
+        // vote resolution requires a yes vote threshold, and the time at which voting started
+        let voting_at = match (&config.vote_threshold_percentage, self.proposal.voting_at) {
+            (VoteThresholdPercentage::YesVote(_), Some(voting_at)) => voting_at,
+            _ => return ProposalState::Voting,
+        };
+        let mut proposal = self.proposal.clone();
+        if let Ok(true) = proposal.try_tip_vote(max_vote_weight, config, voting_at) {
+            return proposal.state;
+        }
+        let ends_at = voting_at.saturating_add(config.max_voting_time as i64);
+        match proposal.finalize_vote(max_vote_weight, config, ends_at.saturating_add(1)) {
+            Ok(()) => proposal.state,
+            Err(_) => ProposalState::Voting,
+        }
+    }
+    /// returns the weight of the most voted option plus the deny vote weight
+    fn cast_vote_weight(&self) -> u64 {
+        let max_option_vote_weight = self
+            .proposal
+            .options
+            .iter()
+            .map(|option| option.vote_weight)
+            .max()
+            .unwrap_or(0);
+        max_option_vote_weight.saturating_add(self.proposal.deny_vote_weight.unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::zeroed;
+    use spl_governance::state::{
+        enums::GovernanceAccountType, proposal::ProposalOption, vote_record::VoteChoice,
+    };
+
+    pub fn vote_record(
+        owner: Pubkey,
+        voter_weight: u64,
+        vote: Vote,
+        relinquished: bool,
+    ) -> VoteRecordV2 {
+        VoteRecordV2 {
+            account_type: GovernanceAccountType::VoteRecordV2,
+            proposal: Pubkey::default(),
+            governing_token_owner: owner,
+            is_relinquished: relinquished,
+            voter_weight,
+            vote,
+            reserved_v2: [0; 8],
+        }
+    }
+    #[test]
+    fn test_vote_tally_voter_count() {
+        let whale = Pubkey::new_unique();
+        let approve = || {
+            Vote::Approve(vec![VoteChoice {
+                rank: 0,
+                weight_percentage: 100,
+            }])
+        };
+        let vote_records = vec![
+            vote_record(whale, 1_000_000, approve(), false),
+            vote_record(Pubkey::new_unique(), 10, approve(), false),
+            vote_record(Pubkey::new_unique(), 20, Vote::Deny, false),
+            // relinquished votes are neither counted as weight, or as a voter
+            vote_record(Pubkey::new_unique(), 30, Vote::Deny, true),
+        ];
+        let tally = VoteTally::from_vote_records(&vote_records);
+        assert_eq!(tally.approval_votes, 1_000_010);
+        assert_eq!(tally.deny_votes, 20);
+        assert_eq!(tally.voter_count, 3);
+        assert_eq!(tally.ui_amounts(6), (1.00001, 0.00002));
+    }
+    #[test]
+    fn test_vote_tally_vote_variants() {
+        let choice = |weight_percentage: u8| VoteChoice {
+            rank: 0,
+            weight_percentage,
+        };
+        // single choice approval
+        let tally = VoteTally::from_vote_records(&[vote_record(
+            Pubkey::new_unique(),
+            100,
+            Vote::Approve(vec![choice(100)]),
+            false,
+        )]);
+        assert_eq!(tally.approval_votes, 100);
+        assert_eq!((tally.deny_votes, tally.abstain_votes), (0, 0));
+        assert_eq!(tally.voter_count, 1);
+        // multi choice approval sums the weight given to each approved option
+        let tally = VoteTally::from_vote_records(&[vote_record(
+            Pubkey::new_unique(),
+            100,
+            Vote::Approve(vec![choice(100), choice(0), choice(100)]),
+            false,
+        )]);
+        assert_eq!(tally.approval_votes, 200);
+        assert_eq!(tally.voter_count, 1);
+        // deny
+        let tally = VoteTally::from_vote_records(&[vote_record(
+            Pubkey::new_unique(),
+            100,
+            Vote::Deny,
+            false,
+        )]);
+        assert_eq!(tally.deny_votes, 100);
+        assert_eq!((tally.approval_votes, tally.abstain_votes), (0, 0));
+        assert_eq!(tally.voter_count, 1);
+        // abstain is tallied separately, and counted as a voter
+        let tally = VoteTally::from_vote_records(&[
+            vote_record(Pubkey::new_unique(), 2_500_000, Vote::Abstain, false),
+            vote_record(Pubkey::new_unique(), 100, Vote::Abstain, true),
+        ]);
+        assert_eq!(tally.abstain_votes, 2_500_000);
+        assert_eq!((tally.approval_votes, tally.deny_votes), (0, 0));
+        assert_eq!(tally.voter_count, 1);
+        assert_eq!(tally.ui_abstain_votes(6), 2.5);
+    }
+    #[test]
+    fn test_approval_progress() {
+        let mut config: GovernanceConfig = zeroed();
+        config.vote_threshold_percentage = VoteThresholdPercentage::YesVote(60);
+        let mut option: ProposalOption = zeroed();
+        option.vote_weight = 300;
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        // no options have been voted on
+        assert_eq!(proposal.approval_progress(&config, 1_000), 0.0);
+        proposal.proposal.options.push(option);
+        // 300 of the 600 votes required
+        assert_eq!(proposal.approval_progress(&config, 1_000), 50.0);
+        // the threshold is rounded up, requiring 7 votes
+        assert_eq!(proposal.approval_progress(&config, 11), 100.0);
+        proposal.proposal.options[0].vote_weight = 6;
+        assert!((proposal.approval_progress(&config, 11) - 600.0 / 7.0).abs() < 1e-9);
+        // a proposal with no voting weight has nothing left to reach
+        assert_eq!(proposal.approval_progress(&config, 0), 100.0);
+    }
+    #[test]
+    fn test_participation_rate() {
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        assert_eq!(proposal.participation_rate(1_000), 0.0);
+        let mut option: ProposalOption = zeroed();
+        option.vote_weight = 150;
+        proposal.proposal.options.push(option);
+        proposal.proposal.deny_vote_weight = Some(100);
+        assert_eq!(proposal.participation_rate(1_000), 0.25);
+        assert_eq!(proposal.participation_rate(250), 1.0);
+        // a mint without supply doesn't divide by zero
+        assert_eq!(proposal.participation_rate(0), 0.0);
+        // weights summing beyond u64 don't overflow
+        proposal.proposal.options[0].vote_weight = u64::MAX;
+        proposal.proposal.deny_vote_weight = Some(u64::MAX);
+        assert_eq!(proposal.participation_rate(u64::MAX), 2.0);
+    }
+    #[test]
+    fn test_max_vote_weight() {
+        let mut realm: RealmV2 = zeroed();
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        proposal.proposal.governing_token_mint = Pubkey::new_unique();
+        let mut option: ProposalOption = zeroed();
+        option.vote_weight = 400;
+        proposal.proposal.options.push(option);
+        // the full supply is used without any fraction math
+        realm.config.community_mint_max_vote_weight_source =
+            MintMaxVoteWeightSource::SupplyFraction(MintMaxVoteWeightSource::SUPPLY_FRACTION_BASE);
+        assert_eq!(proposal.max_vote_weight(&realm, 1_000).unwrap(), 1_000);
+        // half of the supply
+        realm.config.community_mint_max_vote_weight_source =
+            MintMaxVoteWeightSource::SupplyFraction(
+                MintMaxVoteWeightSource::SUPPLY_FRACTION_BASE / 2,
+            );
+        assert_eq!(proposal.max_vote_weight(&realm, 1_000).unwrap(), 500);
+        // the cast vote weight is used when it exceeds the fraction of the supply
+        proposal.proposal.deny_vote_weight = Some(200);
+        assert_eq!(proposal.max_vote_weight(&realm, 1_000).unwrap(), 600);
+        // an absolute max vote weight above the cast vote weight is used as is
+        realm.config.community_mint_max_vote_weight_source = MintMaxVoteWeightSource::Absolute(800);
+        assert_eq!(proposal.max_vote_weight(&realm, 1_000).unwrap(), 800);
+        // and the cast vote weight is used when it exceeds the absolute max vote weight
+        realm.config.community_mint_max_vote_weight_source = MintMaxVoteWeightSource::Absolute(300);
+        assert_eq!(proposal.max_vote_weight(&realm, 1_000).unwrap(), 600);
+        // the max vote weight source doesn't apply to proposals voted on by the council
+        realm.config.council_mint = Some(proposal.proposal.governing_token_mint);
+        assert_eq!(proposal.max_vote_weight(&realm, 1_000).unwrap(), 1_000);
+    }
+    #[test]
+    fn test_projected_state() {
+        let mut config: GovernanceConfig = zeroed();
+        config.vote_threshold_percentage = VoteThresholdPercentage::YesVote(10);
+        config.max_voting_time = 86400;
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        proposal.proposal.state = ProposalState::Voting;
+        proposal.proposal.voting_at = Some(1_655_842_130);
+        proposal.proposal.options.push(zeroed());
+        let mut projected = |approval_votes: u64, deny_votes: u64| {
+            proposal.proposal.options[0].vote_weight = approval_votes;
+            proposal.proposal.deny_vote_weight = Some(deny_votes);
+            let state = proposal.projected_state(&config, 1_000);
+            // the proposal itself is left untouched
+            assert_eq!(proposal.proposal.state, ProposalState::Voting);
+            state
+        };
+        // an exact tie resolves as Defeated, while a single vote breaks the tie
+        assert_eq!(projected(300, 300), ProposalState::Defeated);
+        assert_eq!(projected(301, 300), ProposalState::Succeeded);
+        // the approve votes must reach the 100 vote threshold
+        assert_eq!(projected(99, 0), ProposalState::Defeated);
+        assert_eq!(projected(100, 0), ProposalState::Succeeded);
+        // tipped votes, where the remaining weight can't change the outcome
+        assert_eq!(projected(501, 0), ProposalState::Succeeded);
+        assert_eq!(projected(0, 901), ProposalState::Defeated);
+
+        // proposals which aren't voting keep their state
+        proposal.proposal.state = ProposalState::Completed;
+        assert_eq!(
+            proposal.projected_state(&config, 1_000),
+            ProposalState::Completed
+        );
+        // proposals which never started voting can't be projected
+        proposal.proposal.state = ProposalState::Voting;
+        proposal.proposal.voting_at = None;
+        assert_eq!(
+            proposal.projected_state(&config, 1_000),
+            ProposalState::Voting
+        );
+    }
+    #[test]
+    fn test_state_transition_from_snapshots() {
+        let mut previous = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        previous.proposal.state = ProposalState::Voting;
+        let mut current = ProposalV2Wrapper {
+            proposal: previous.proposal.clone(),
+            key: previous.key,
+        };
+        // the state is unchanged
+        assert!(ProposalStateTransition::from_snapshots(&previous, &current).is_none());
+        for (state, concluded) in [
+            (ProposalState::Succeeded, true),
+            (ProposalState::Defeated, true),
+            (ProposalState::Cancelled, true),
+        ] {
+            current.proposal.state = state.clone();
+            let transition = ProposalStateTransition::from_snapshots(&previous, &current).unwrap();
+            assert_eq!(transition.proposal_key, previous.key);
+            assert_eq!(transition.previous_state, ProposalState::Voting);
+            assert_eq!(transition.state, state);
+            assert_eq!(transition.is_conclusion(), concluded);
+        }
+        // transitions which do not leave the voting state are not conclusions
+        previous.proposal.state = ProposalState::Succeeded;
+        current.proposal.state = ProposalState::Executing;
+        let transition = ProposalStateTransition::from_snapshots(&previous, &current).unwrap();
+        assert!(!transition.is_conclusion());
+        previous.proposal.state = ProposalState::SigningOff;
+        current.proposal.state = ProposalState::Voting;
+        let transition = ProposalStateTransition::from_snapshots(&previous, &current).unwrap();
+        assert!(!transition.is_conclusion());
+    }
+    #[test]
+    fn test_description_or_default() {
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        assert_eq!(
+            proposal.description_or_default(512),
+            "no description provided"
+        );
+        proposal.proposal.description_link = "  \n".to_string();
+        assert_eq!(
+            proposal.description_or_default(512),
+            "no description provided"
+        );
+        proposal.proposal.description_link = "https://example.com/proposal".to_string();
+        assert_eq!(
+            proposal.description_or_default(512),
+            "https://example.com/proposal"
+        );
+        assert_eq!(proposal.description_or_default(5), "https");
+        // truncating "héllo" to 2 bytes would split the multibyte character
+        proposal.proposal.description_link = "héllo wörld".to_string();
+        assert_eq!(proposal.description_or_default(2), "hé");
+        assert_eq!(proposal.description_or_default(7), "héllo w");
+        // 600 four byte characters, where the 512th byte falls inside a character
+        proposal.proposal.description_link = "🗳".repeat(600);
+        let description = proposal.description_or_default(512);
+        assert_eq!(description.chars().count(), 512);
+        assert_eq!(description.len(), 512 * 4);
+    }
+    #[test]
+    fn test_needs_final_reminder() {
+        let now = crate::utils::date_time_from_timestamp(1_655_842_130);
+        let hours = chrono::Duration::hours;
+        let mut notif_state = ProposalNotifState::new(Pubkey::new_unique(), 0);
+        // voting ends within the final reminder window
+        assert!(notif_state.needs_final_reminder(now + hours(2), now, 3));
+        assert!(notif_state.needs_final_reminder(now + hours(3), now, 3));
+        // voting ends after the window, or has already ended
+        assert!(!notif_state.needs_final_reminder(now + hours(4), now, 3));
+        assert!(!notif_state.needs_final_reminder(now, now, 3));
+        assert!(!notif_state.needs_final_reminder(now - hours(1), now, 3));
+        // the reminder is disabled
+        assert!(!notif_state.needs_final_reminder(now + hours(2), now, 0));
+        // the reminder is only sent once
+        notif_state.final_reminder_sent = true;
+        assert!(!notif_state.needs_final_reminder(now + hours(2), now, 3));
+    }
+    #[test]
+    fn test_is_decided() {
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        for (state, decided) in [
+            (ProposalState::Draft, false),
+            (ProposalState::SigningOff, false),
+            (ProposalState::Voting, false),
+            (ProposalState::Succeeded, true),
+            (ProposalState::Defeated, true),
+            (ProposalState::Completed, true),
+            (ProposalState::Cancelled, true),
+            (ProposalState::Executing, true),
+            (ProposalState::ExecutingWithErrors, true),
+        ] {
+            proposal.proposal.state = state.clone();
+            assert_eq!(proposal.is_decided(), decided, "{:?}", state);
+        }
+    }
+    #[test]
+    fn test_vote_started_at() {
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        assert_eq!(proposal.vote_started_at(), None);
+        proposal.proposal.voting_at = Some(1_650_000_000);
+        assert_eq!(
+            proposal.vote_started_at(),
+            Some(Utc.ymd(2022, 4, 15).and_hms(5, 20, 0))
+        );
+    }
+    #[test]
+    fn test_created_at() {
+        let mut proposal = ProposalV2Wrapper {
+            proposal: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        assert_eq!(proposal.created_at(), Utc.timestamp(0, 0));
+        proposal.proposal.draft_at = 1_650_000_000;
+        assert_eq!(
+            proposal.created_at(),
+            Utc.ymd(2022, 4, 15).and_hms(5, 20, 0)
+        );
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_vote_record_wrapper() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+        let (_, proposal) = crate::test::get_tulip_fixtures(&rpc);
+        let vote_records = crate::utils::get_vote_records_for_proposal(&rpc, proposal.key).unwrap();
+        let vote_record = vote_records.first().unwrap();
+        let token_owner_record =
+            spl_governance::state::token_owner_record::get_token_owner_record_address(
+                &GOVERNANCE_PROGRAM,
+                &crate::test::get_tulip_realm_account(),
+                &proposal.proposal.governing_token_mint,
+                &vote_record.governing_token_owner,
+            );
+        let vote_record_key = spl_governance::state::vote_record::get_vote_record_address(
+            &GOVERNANCE_PROGRAM,
+            &proposal.key,
+            &token_owner_record,
+        );
+        let mut account_tup = (vote_record_key, rpc.get_account(&vote_record_key).unwrap());
+        let wrapper = get_vote_record_wrapper(&account_tup.into_account_info()).unwrap();
+        assert_eq!(wrapper.key, vote_record_key);
+        assert_eq!(wrapper.key().unwrap(), vote_record_key.to_bytes().to_vec());
+        assert_eq!(wrapper.vote_record, *vote_record);
+        // accounts of other types are rejected
+        let mut account_tup = (proposal.key, rpc.get_account(&proposal.key).unwrap());
+        assert!(get_vote_record_wrapper(&account_tup.into_account_info()).is_err());
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tally_votes() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+        let (_, proposal) = crate::test::get_tulip_fixtures(&rpc);
+        let vote_records = crate::utils::get_vote_records_for_proposal(&rpc, proposal.key).unwrap();
+        let tally = VoteTally::from_vote_records(&vote_records);
+        assert_eq!(proposal.vote_tally(&rpc).unwrap(), tally);
+        assert!(tally.approval_votes > 0);
+    }
 }
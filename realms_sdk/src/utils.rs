@@ -1,56 +1,172 @@
-use borsh::BorshDeserialize;
 use chrono::prelude::*;
 use solana_client::rpc_client::RpcClient;
 use solana_client::rpc_filter::RpcFilterType;
+use solana_program::account_info::{AccountInfo, IntoAccountInfo};
+use solana_program::borsh::try_from_slice_unchecked;
 use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use spl_governance::state::enums::{GovernanceAccountType, ProposalState};
+use spl_governance::state::token_owner_record::TokenOwnerRecordV2;
 use spl_governance::state::vote_record::VoteRecordV2;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
-use crate::GOVERNANCE_TREE;
+use crate::GOVERNANCE_PROGRAM;
 use crate::{
-    types::{GovernanceV2Wrapper, ProposalV2Wrapper},
-    Database,
+    types::{GovernanceV2Wrapper, ProposalV2Wrapper, VoteRecordV2Wrapper},
+    Database, RealmsError,
 };
-use anyhow::{anyhow, Result};
-use tulip_sled_util::types::DbTrees;
+use anyhow::{anyhow, Context, Result};
 impl Database {
-    /// returns a vector of all proposals that are undergoing activte voting
-    pub fn list_voting_proposals(&self, now: DateTime<Utc>) -> Result<Vec<ProposalV2Wrapper>> {
-        let mut governance_wrapper = None;
-        let voting_proposals = self
+    /// returns a vector of all proposals that are undergoing activte voting, de-duplicated by key
+    /// and sorted by the time at which voting ends, with the soonest deadline first
+    pub fn list_voting_proposals(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ProposalV2Wrapper>, RealmsError> {
+        // proposals are filtered and sorted using the max voting time of their own governance
+        let mut governances: HashMap<Pubkey, Option<GovernanceV2Wrapper>> = HashMap::new();
+        let mut voting_proposals: Vec<(Option<DateTime<Utc>>, ProposalV2Wrapper)> = self
             .list_proposals()?
             .into_iter()
-            .filter(|proposal| {
-                if proposal.proposal.voting_at.is_none() {
-                    return false;
+            .filter_map(|proposal| {
+                if proposal.proposal.voting_at.is_none()
+                    || proposal.proposal.state.ne(&ProposalState::Voting)
+                {
+                    return None;
                 }
-                if governance_wrapper.is_none() {
-                    if let Ok(gov_tree) = self.db.open_tree(DbTrees::Custom(GOVERNANCE_TREE)) {
-                        let gov_wrapper: GovernanceV2Wrapper = if let Ok(gov_wrap) =
-                            gov_tree.deserialize(proposal.proposal.governance)
-                        {
-                            gov_wrap
-                        } else {
-                            log::warn!("failed to deserialize governance account");
-                            return false;
-                        };
-                        governance_wrapper = Some(gov_wrapper);
-                    }
+                let governance_key = proposal.proposal.governance;
+                let governance = governances
+                    .entry(governance_key)
+                    .or_insert_with(|| match self.get_governance(governance_key) {
+                        Ok(governance) => Some(governance),
+                        Err(err) => {
+                            log::warn!("failed to load governance {}: {:#}", governance_key, err);
+                            None
+                        }
+                    })
+                    .as_ref()?;
+                let config = &governance.governance.config;
+                if proposal.has_vote_time_ended(config, now) {
+                    return None;
                 }
-                if let Some(governance_wrapper) = governance_wrapper.as_ref() {
-                    //return !proposal.has_vote_time_ended(&governance_wrapper.governance.config, now);
-                    !proposal.has_vote_time_ended(&governance_wrapper.governance.config, now)
-                        && proposal
-                            .proposal
-                            .state
-                            .eq(&spl_governance::state::enums::ProposalState::Voting)
+                Some((proposal.vote_ends_at(config), proposal))
+            })
+            .collect();
+        let mut seen = HashSet::with_capacity(voting_proposals.len());
+        voting_proposals.retain(|(_, proposal)| seen.insert(proposal.key));
+        voting_proposals.sort_by(|(a_ends_at, a), (b_ends_at, b)| {
+            a_ends_at.cmp(b_ends_at).then_with(|| a.key.cmp(&b.key))
+        });
+        let voting_proposals = voting_proposals
+            .into_iter()
+            .map(|(_, proposal)| proposal)
+            .collect();
+        Ok(voting_proposals)
+    }
+    /// returns the voting proposals whose voting ends between `now` and `now + within`, using
+    /// the `max_voting_time` of each proposal's governance, sorted by the time voting ends
+    pub fn list_proposals_ending_within(
+        &self,
+        within: chrono::Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ProposalV2Wrapper>, RealmsError> {
+        let deadline = now + within;
+        let mut governances: HashMap<Pubkey, Option<GovernanceV2Wrapper>> = HashMap::new();
+        let mut ending: Vec<(DateTime<Utc>, ProposalV2Wrapper)> = self
+            .list_proposals_by_state(ProposalState::Voting)?
+            .into_iter()
+            .filter_map(|proposal| {
+                let governance = governances
+                    .entry(proposal.proposal.governance)
+                    .or_insert_with(|| self.get_governance(proposal.proposal.governance).ok())
+                    .as_ref()?;
+                let ends_at = proposal.vote_ends_at(&governance.governance.config)?;
+                if ends_at >= now && ends_at <= deadline {
+                    Some((ends_at, proposal))
                 } else {
-                    log::warn!("governance wrapper is None");
-                    false
+                    None
                 }
             })
             .collect();
-        Ok(voting_proposals)
+        ending.sort_by(|(a_ends_at, a), (b_ends_at, b)| {
+            a_ends_at.cmp(b_ends_at).then_with(|| a.key.cmp(&b.key))
+        });
+        Ok(ending.into_iter().map(|(_, proposal)| proposal).collect())
+    }
+    /// returns all proposals in the given state. sled has no secondary indices, so this
+    /// iterates over every stored proposal, filtering on its state
+    pub fn list_proposals_by_state(
+        &self,
+        state: ProposalState,
+    ) -> Result<Vec<ProposalV2Wrapper>, RealmsError> {
+        Ok(self
+            .list_proposals()?
+            .into_iter()
+            .filter(|proposal| proposal.proposal.state.eq(&state))
+            .collect())
     }
+    /// returns up to `limit` of the most recently decided proposals, that is proposals which
+    /// are Succeeded, Defeated, or Completed, sorted by `voting_completed_at` descending
+    pub fn list_decided_proposals(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<ProposalV2Wrapper>, RealmsError> {
+        let mut decided_proposals: Vec<ProposalV2Wrapper> = self
+            .list_proposals()?
+            .into_iter()
+            .filter(|proposal| {
+                matches!(
+                    proposal.proposal.state,
+                    ProposalState::Succeeded | ProposalState::Defeated | ProposalState::Completed
+                )
+            })
+            .collect();
+        decided_proposals.sort_by(|a, b| {
+            b.proposal
+                .voting_completed_at
+                .cmp(&a.proposal.voting_completed_at)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        decided_proposals.truncate(limit);
+        Ok(decided_proposals)
+    }
+    /// returns the index of the proposal within its governance, as listed by the realms ui.
+    ///
+    /// the index is not stored on the proposal account, so it is found by deriving the proposal
+    /// addresses for indices `0..max_count`. resolved indices are cached as they never change
+    pub fn proposal_index(
+        &self,
+        governance: Pubkey,
+        governing_mint: Pubkey,
+        key: Pubkey,
+        max_count: u32,
+    ) -> Option<u32> {
+        if let Ok(proposal_indices) = self.proposal_indices.read() {
+            if let Some(index) = proposal_indices.get(&key) {
+                return Some(*index);
+            }
+        }
+        let index = (0..max_count).find(|idx| {
+            spl_governance::state::proposal::get_proposal_address(
+                &GOVERNANCE_PROGRAM,
+                &governance,
+                &governing_mint,
+                &idx.to_le_bytes()[..],
+            )
+            .eq(&key)
+        })?;
+        if let Ok(mut proposal_indices) = self.proposal_indices.write() {
+            proposal_indices.insert(key, index);
+        }
+        Some(index)
+    }
+}
+
+/// converts a raw amount of a token with the given decimals into a ui amount, ie: vote weights
+/// into an amount of the community mint
+pub fn ui_amount(raw: u64, decimals: u8) -> f64 {
+    spl_token::amount_to_ui_amount(raw, decimals)
 }
 
 /// given a timestamp, return a DateTime<Utc> object using a utc timezone
@@ -58,29 +174,317 @@ pub fn date_time_from_timestamp(timestamp: i64) -> DateTime<Utc> {
     DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(timestamp, 0), Utc)
 }
 
+/// renders a duration using its two most significant units, ie: "2d 3h", "1h 5m", "45m".
+/// durations under a minute render as "<1m", and negative durations as "ended"
+pub fn humanize_duration(d: chrono::Duration) -> String {
+    if d < chrono::Duration::zero() {
+        return "ended".to_string();
+    }
+    let days = d.num_days();
+    let hours = d.num_hours() % 24;
+    let minutes = d.num_minutes() % 60;
+    match (days, hours, minutes) {
+        (0, 0, 0) => "<1m".to_string(),
+        (0, 0, minutes) => format!("{}m", minutes),
+        (0, hours, 0) => format!("{}h", hours),
+        (0, hours, minutes) => format!("{}h {}m", hours, minutes),
+        (days, 0, _) => format!("{}d", days),
+        (days, hours, _) => format!("{}d {}h", days, hours),
+    }
+}
+
+/// returns at most the first `max_chars` characters of the text
+pub fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    // slice on a char boundary, as byte offsets may fall inside a multibyte character
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// maximum number of characters of a resolved description
+pub const DESCRIPTION_EXCERPT_LEN: usize = 400;
+
+/// fetches the content linked to by a proposal's `description_link`, returning an excerpt
+/// of at most `DESCRIPTION_EXCERPT_LEN` characters. links which aren't http(s) urls are rejected
+pub async fn resolve_description(link: &str) -> Result<String> {
+    let url = reqwest::Url::parse(link.trim())
+        .with_context(|| format!("description link {} is not a url", link))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(anyhow!("description link {} is not a http url", link));
+    }
+    let content = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?
+        .get(url)
+        .send()
+        .await
+        .context("failed to fetch description")?
+        .error_for_status()
+        .context("description link returned an error")?
+        .text()
+        .await
+        .context("failed to read description")?;
+    let content = content.trim();
+    if content.is_empty() {
+        return Err(anyhow!("description link {} is empty", link));
+    }
+    let mut excerpt: String = content.chars().take(DESCRIPTION_EXCERPT_LEN).collect();
+    if excerpt.len() < content.len() {
+        excerpt.push_str("...");
+    }
+    Ok(excerpt)
+}
+
+/// approximate duration of a slot in milliseconds
+pub const SLOT_DURATION_MS: i64 = 400;
+
+/// the progression of an rpc node's slot between two observations
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotProgress {
+    Healthy,
+    /// the slot advanced by less than half of the slots expected for the elapsed time
+    Behind,
+    /// the slot has not advanced at all
+    Stuck,
+}
+
+/// classifies the progression of an rpc node's reported slot, given the slot observed previously
+/// and the time elapsed since that observation
+pub fn slot_progress(
+    previous_slot: u64,
+    current_slot: u64,
+    elapsed: chrono::Duration,
+) -> SlotProgress {
+    if current_slot <= previous_slot {
+        return SlotProgress::Stuck;
+    }
+    let expected_slots = elapsed.num_milliseconds() / SLOT_DURATION_MS;
+    if ((current_slot - previous_slot) as i64) < expected_slots / 2 {
+        SlotProgress::Behind
+    } else {
+        SlotProgress::Healthy
+    }
+}
+
+/// fetches accounts, allowing rpc access to be mocked
+pub trait AccountFetcher {
+    fn fetch_account(&self, key: &Pubkey) -> Result<Account>;
+}
+
+impl AccountFetcher for RpcClient {
+    fn fetch_account(&self, key: &Pubkey) -> Result<Account> {
+        Ok(self.get_account(key)?)
+    }
+}
+
+/// fetches the account, making up to `attempts` attempts and doubling `backoff` after each
+/// failed attempt, so that a transient rpc error doesn't abort an entire worker loop iteration.
+/// the backoff is awaited rather than slept, so retries don't block the runtime's threads
+pub async fn get_account_with_retry(
+    rpc: &impl AccountFetcher,
+    key: Pubkey,
+    attempts: u32,
+    backoff: Duration,
+) -> Result<Account> {
+    with_retry(&format!("account {}", key), attempts, backoff, || {
+        rpc.fetch_account(&key)
+    })
+    .await
+}
+
+/// calls `f` until it succeeds, making up to `attempts` attempts and doubling `backoff` after
+/// each failed attempt. `what` describes the fetched value in errors and logs
+async fn with_retry<T>(
+    what: &str,
+    attempts: u32,
+    backoff: Duration,
+    f: impl Fn() -> Result<T>,
+) -> Result<T> {
+    let mut backoff = backoff;
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= attempts => {
+                return Err(err.context(format!(
+                    "failed to fetch {} after {} attempts",
+                    what, attempt
+                )));
+            }
+            Err(err) => {
+                log::warn!(
+                    "failed to fetch {} (attempt {}/{}) {:#}",
+                    what,
+                    attempt,
+                    attempts,
+                    err
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// returns the addresses the proposal at `idx` of the governance may be stored at, one for
+/// each of the given governing token mints. proposals are derived from the mint whose holders
+/// created them, so only one of the addresses exists
+pub fn proposal_addresses(
+    governance_key: &Pubkey,
+    governing_token_mints: &[Pubkey],
+    idx: u32,
+) -> Vec<Pubkey> {
+    governing_token_mints
+        .iter()
+        .map(|governing_token_mint| {
+            spl_governance::state::proposal::get_proposal_address(
+                &GOVERNANCE_PROGRAM,
+                governance_key,
+                governing_token_mint,
+                &idx.to_le_bytes()[..],
+            )
+        })
+        .collect()
+}
+
+/// fetches the proposal at `idx` of the governance, which may have been created with any of
+/// the governing token mints. the address derived from each mint is fetched in a single request
+pub fn get_proposal_at_index(
+    rpc: &RpcClient,
+    governance_key: Pubkey,
+    governing_token_mints: &[Pubkey],
+    idx: u32,
+) -> Result<ProposalV2Wrapper> {
+    let keys = proposal_addresses(&governance_key, governing_token_mints, idx);
+    let accounts = rpc.get_multiple_accounts(&keys).with_context(|| {
+        format!(
+            "failed to fetch proposal {} of governance {}",
+            idx, governance_key
+        )
+    })?;
+    let (key, account) = keys
+        .into_iter()
+        .zip(accounts)
+        .find_map(|(key, account)| Some((key, account?)))
+        .ok_or_else(|| {
+            anyhow!(
+                "proposal {} of governance {} does not exist",
+                idx,
+                governance_key
+            )
+        })?;
+    parse_wrapper(key, account, crate::types::get_proposal_wrapper)
+}
+
+/// same as `get_proposal_at_index`, except failed fetches are retried as
+/// `get_account_with_retry` does
+pub async fn get_proposal_at_index_with_retry(
+    rpc: &RpcClient,
+    governance_key: Pubkey,
+    governing_token_mints: &[Pubkey],
+    idx: u32,
+    attempts: u32,
+    backoff: Duration,
+) -> Result<ProposalV2Wrapper> {
+    with_retry(
+        &format!("proposal {} of governance {}", idx, governance_key),
+        attempts,
+        backoff,
+        || get_proposal_at_index(rpc, governance_key, governing_token_mints, idx),
+    )
+    .await
+}
+
+/// fetches the account once, and parses it using one of the wrapper getters such as
+/// `get_governance_wrapper` or `get_proposal_wrapper`, for callers outside of the async worker
+/// loop which can't await `get_wrapper_with_retry`
+pub fn get_wrapper<T>(
+    rpc: &impl AccountFetcher,
+    key: Pubkey,
+    get_wrapper: impl Fn(&AccountInfo) -> Result<T>,
+) -> Result<T> {
+    let account = rpc
+        .fetch_account(&key)
+        .with_context(|| format!("failed to fetch account {}", key))?;
+    parse_wrapper(key, account, get_wrapper)
+}
+
+/// parses the fetched account using one of the wrapper getters
+fn parse_wrapper<T>(
+    key: Pubkey,
+    account: Account,
+    get_wrapper: impl Fn(&AccountInfo) -> Result<T>,
+) -> Result<T> {
+    let mut account_tup = (key, account);
+    get_wrapper(&account_tup.into_account_info())
+        .with_context(|| format!("failed to deserialize account {}", key))
+}
+
+/// fetches the account with `get_account_with_retry`, and parses it using one of the wrapper
+/// getters such as `get_governance_wrapper` or `get_proposal_wrapper`
+pub async fn get_wrapper_with_retry<T>(
+    rpc: &impl AccountFetcher,
+    key: Pubkey,
+    attempts: u32,
+    backoff: Duration,
+    get_wrapper: impl Fn(&AccountInfo) -> Result<T>,
+) -> Result<T> {
+    let account = get_account_with_retry(rpc, key, attempts, backoff).await?;
+    parse_wrapper(key, account, get_wrapper)
+}
+
+/// prefix of the keys notification cache entries are stored under in the default tree
+pub const NOTIF_CACHE_KEY_PREFIX: &str = "notif_cache_entry-";
+
 pub fn governance_notif_cache_key(gov_key: Pubkey) -> String {
-    format!("notif_cache_entry-{}", gov_key)
+    format!("{}{}", NOTIF_CACHE_KEY_PREFIX, gov_key)
 }
 
+/// returns all vote records for the given proposal using get_program_accounts, as this is
+/// a heavy call the given rpc client should point to a node which permits program account scans
+///
+/// VoteRecordV2 accounts are laid out as the account type (1 byte), followed by the proposal
+/// key (32 bytes). other accounts such as signatory records also store the proposal key at
+/// offset 1, so the account type is matched as well
 pub fn get_vote_records_for_proposal(
     rpc: &RpcClient,
     proposal: Pubkey,
 ) -> Result<Vec<VoteRecordV2>> {
-    use crate::GOVERNANCE_PROGRAM;
+    Ok(get_vote_record_wrappers_for_proposal(rpc, proposal)?
+        .into_iter()
+        .map(|wrapper| wrapper.vote_record)
+        .collect())
+}
+
+/// similar to get_vote_records_for_proposal, except the vote records are returned along with
+/// the keys of their accounts
+pub fn get_vote_record_wrappers_for_proposal(
+    rpc: &RpcClient,
+    proposal: Pubkey,
+) -> Result<Vec<VoteRecordV2Wrapper>> {
     use solana_account_decoder::UiAccountEncoding;
     use solana_client::rpc_config::RpcAccountInfoConfig;
     use solana_client::rpc_config::RpcProgramAccountsConfig;
-    use solana_client::rpc_filter::Memcmp;
+    use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes};
     match rpc.get_program_accounts_with_config(
         &GOVERNANCE_PROGRAM,
         RpcProgramAccountsConfig {
-            filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
-                offset: 1,
-                bytes: solana_client::rpc_filter::MemcmpEncodedBytes::Bytes(
-                    proposal.to_bytes().to_vec(),
-                ),
-                encoding: None,
-            })]),
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp {
+                    offset: 0,
+                    bytes: MemcmpEncodedBytes::Bytes(vec![
+                        GovernanceAccountType::VoteRecordV2 as u8,
+                    ]),
+                    encoding: None,
+                }),
+                RpcFilterType::Memcmp(Memcmp {
+                    offset: 1,
+                    bytes: MemcmpEncodedBytes::Bytes(proposal.to_bytes().to_vec()),
+                    encoding: None,
+                }),
+            ]),
             with_context: None,
             account_config: RpcAccountInfoConfig {
                 min_context_slot: None,
@@ -90,41 +494,501 @@ pub fn get_vote_records_for_proposal(
             },
         },
     ) {
-        Ok(mut accounts) => {
-            let mut voter_records = Vec::with_capacity(accounts.len());
-            for (key, voter_account) in accounts.iter_mut() {
-                let key = std::mem::take(key);
-                let voter_account = std::mem::take(voter_account);
-                match spl_governance::state::vote_record::VoteRecordV2::deserialize(
-                    &mut &voter_account.data[..],
-                ) {
-                    Ok(voter_record) => {
-                        voter_records.push(voter_record);
-                    }
-                    Err(err) => {
-                        log::error!("failed to deserialize voter record {}: {:#?}", key, err)
-                    }
-                }
+        Ok(accounts) => Ok(decode_vote_records(
+            accounts
+                .iter()
+                .map(|(key, account)| (key, &account.data[..])),
+        )),
+        Err(err) => Err(anyhow!("failed to find voter records {:#?}", err)),
+    }
+}
+
+/// fetches the vote records of a proposal, allowing rpc access to be mocked
+pub trait VoteRecordFetcher {
+    fn fetch_vote_records(&self, proposal: Pubkey) -> Result<Vec<VoteRecordV2Wrapper>>;
+}
+
+impl VoteRecordFetcher for RpcClient {
+    fn fetch_vote_records(&self, proposal: Pubkey) -> Result<Vec<VoteRecordV2Wrapper>> {
+        get_vote_record_wrappers_for_proposal(self, proposal)
+    }
+}
+
+/// returns the vote record cast by the token owner record on the proposal, or None if
+/// the token owner has not voted. this avoids scanning all vote records for a single lookup
+pub fn get_vote_record_for_voter(
+    rpc: &RpcClient,
+    proposal: Pubkey,
+    token_owner_record: Pubkey,
+) -> Result<Option<VoteRecordV2>> {
+    let vote_record_key = spl_governance::state::vote_record::get_vote_record_address(
+        &GOVERNANCE_PROGRAM,
+        &proposal,
+        &token_owner_record,
+    );
+    let account = match rpc
+        .get_account_with_commitment(&vote_record_key, rpc.commitment())
+        .with_context(|| format!("failed to fetch vote record {}", vote_record_key))?
+        .value
+    {
+        Some(account) => account,
+        None => return Ok(None),
+    };
+    let mut account_tup = (vote_record_key, account);
+    let vote_record = spl_governance::state::vote_record::get_vote_record_data(
+        &GOVERNANCE_PROGRAM,
+        &account_tup.into_account_info(),
+    )
+    .map_err(|err| {
+        anyhow!(
+            "failed to decode vote record {}: {:#?}",
+            vote_record_key,
+            err
+        )
+    })?;
+    Ok(Some(vote_record))
+}
+
+/// returns the token owner record of the owner's deposits of the governing mint into the realm,
+/// or None if the owner has never deposited, allowing a wallet to be mapped to its votes
+pub fn get_token_owner_record(
+    rpc: &RpcClient,
+    realm: Pubkey,
+    governing_mint: Pubkey,
+    owner: Pubkey,
+) -> Result<Option<TokenOwnerRecordV2>> {
+    let token_owner_record_key =
+        spl_governance::state::token_owner_record::get_token_owner_record_address(
+            &GOVERNANCE_PROGRAM,
+            &realm,
+            &governing_mint,
+            &owner,
+        );
+    let account = match rpc
+        .get_account_with_commitment(&token_owner_record_key, rpc.commitment())
+        .with_context(|| {
+            format!(
+                "failed to fetch token owner record {}",
+                token_owner_record_key
+            )
+        })?
+        .value
+    {
+        Some(account) => account,
+        None => return Ok(None),
+    };
+    let mut account_tup = (token_owner_record_key, account);
+    let token_owner_record =
+        spl_governance::state::token_owner_record::get_token_owner_record_data(
+            &GOVERNANCE_PROGRAM,
+            &account_tup.into_account_info(),
+        )
+        .map_err(|err| {
+            anyhow!(
+                "failed to decode token owner record {}: {:#?}",
+                token_owner_record_key,
+                err
+            )
+        })?;
+    Ok(Some(token_owner_record))
+}
+
+/// the account types of governance accounts, both v1 and v2 accounts are deserialized
+/// into GovernanceV2 by `get_governance_data`
+const GOVERNANCE_ACCOUNT_TYPES: [GovernanceAccountType; 8] = [
+    GovernanceAccountType::AccountGovernanceV1,
+    GovernanceAccountType::ProgramGovernanceV1,
+    GovernanceAccountType::MintGovernanceV1,
+    GovernanceAccountType::TokenGovernanceV1,
+    GovernanceAccountType::AccountGovernanceV2,
+    GovernanceAccountType::ProgramGovernanceV2,
+    GovernanceAccountType::MintGovernanceV2,
+    GovernanceAccountType::TokenGovernanceV2,
+];
+
+/// returns all governance accounts belonging to the given realm using get_program_accounts, as
+/// this is a heavy call the given rpc client should point to a node which permits program account scans
+///
+/// governance accounts are laid out as the account type (1 byte), followed by the realm key
+/// (32 bytes). token owner records also store the realm key at offset 1, so a scan is issued
+/// for each governance account type
+pub fn get_governances_for_realm(
+    rpc: &RpcClient,
+    realm_key: Pubkey,
+) -> Result<Vec<GovernanceV2Wrapper>> {
+    use solana_account_decoder::UiAccountEncoding;
+    use solana_client::rpc_config::RpcAccountInfoConfig;
+    use solana_client::rpc_config::RpcProgramAccountsConfig;
+    use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes};
+    use solana_program::account_info::IntoAccountInfo;
+    let mut governances = vec![];
+    for account_type in GOVERNANCE_ACCOUNT_TYPES.iter() {
+        let accounts = rpc
+            .get_program_accounts_with_config(
+                &GOVERNANCE_PROGRAM,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::Memcmp(Memcmp {
+                            offset: 0,
+                            bytes: MemcmpEncodedBytes::Bytes(vec![account_type.clone() as u8]),
+                            encoding: None,
+                        }),
+                        RpcFilterType::Memcmp(Memcmp {
+                            offset: 1,
+                            bytes: MemcmpEncodedBytes::Bytes(realm_key.to_bytes().to_vec()),
+                            encoding: None,
+                        }),
+                    ]),
+                    with_context: None,
+                    account_config: RpcAccountInfoConfig {
+                        min_context_slot: None,
+                        encoding: Some(UiAccountEncoding::Base64),
+                        data_slice: None,
+                        commitment: None,
+                    },
+                },
+            )
+            .map_err(|err| anyhow!("failed to find {:?} accounts {:#?}", account_type, err))?;
+        for mut account_tup in accounts.into_iter() {
+            let key = account_tup.0;
+            match crate::types::get_governance_wrapper(&account_tup.into_account_info()) {
+                Ok(governance) => governances.push(governance),
+                Err(err) => log::warn!("failed to deserialize governance {}: {:#?}", key, err),
             }
-            Ok(voter_records)
         }
-        Err(err) => Err(anyhow!("failed to find voter records {:#?}", err)),
     }
+    Ok(governances)
+}
+
+/// decodes the given (key, account data) pairs into vote record wrappers. accounts which fail to
+/// deserialize are logged and skipped, so that a single bad account doesn't fail the entire fetch
+fn decode_vote_records<'a>(
+    accounts: impl IntoIterator<Item = (&'a Pubkey, &'a [u8])>,
+) -> Vec<VoteRecordV2Wrapper> {
+    let mut skipped = 0;
+    let voter_records: Vec<VoteRecordV2Wrapper> = accounts
+        .into_iter()
+        .filter_map(|(key, data)| {
+            // vote records may be allocated with trailing space, so don't require
+            // the entire account data to be consumed
+            match try_from_slice_unchecked::<VoteRecordV2>(data) {
+                Ok(vote_record) => Some(VoteRecordV2Wrapper {
+                    vote_record,
+                    key: *key,
+                }),
+                Err(err) => {
+                    log::warn!("failed to deserialize voter record {}: {:#?}", key, err);
+                    skipped += 1;
+                    None
+                }
+            }
+        })
+        .collect();
+    if skipped > 0 {
+        log::warn!(
+            "skipped {} of {} voter records which failed to deserialize",
+            skipped,
+            skipped + voter_records.len()
+        );
+    }
+    voter_records
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::test::{get_tulip_community_mint, get_tulip_council_mint, get_tulip_realm_account};
+    use crate::test::{
+        get_tulip_community_mint, get_tulip_council_mint, get_tulip_fixtures,
+        get_tulip_governance_account, get_tulip_realm_account, zeroed,
+    };
     use solana_client::rpc_client::RpcClient;
     use static_pubkey::static_pubkey;
+    #[tokio::test]
+    async fn test_resolve_description() {
+        let (url, handle) = crate::test::mock_server("200 OK", "  raise the fee to 1%\n").await;
+        assert_eq!(
+            resolve_description(&url).await.unwrap(),
+            "raise the fee to 1%"
+        );
+        handle.await.unwrap();
+
+        // long descriptions are truncated
+        let body: &'static str =
+            Box::leak("a".repeat(DESCRIPTION_EXCERPT_LEN + 1).into_boxed_str());
+        let (url, handle) = crate::test::mock_server("200 OK", body).await;
+        assert_eq!(
+            resolve_description(&url).await.unwrap(),
+            format!("{}...", "a".repeat(DESCRIPTION_EXCERPT_LEN))
+        );
+        handle.await.unwrap();
+
+        let (url, handle) = crate::test::mock_server("404 Not Found", "").await;
+        assert!(resolve_description(&url).await.is_err());
+        handle.await.unwrap();
+
+        assert!(resolve_description("raise the fee to 1%").await.is_err());
+        assert!(resolve_description("ftp://example.com/proposal")
+            .await
+            .is_err());
+    }
+    struct FlakyFetcher {
+        failures: u32,
+        calls: std::cell::Cell<u32>,
+    }
+    impl AccountFetcher for FlakyFetcher {
+        fn fetch_account(&self, _key: &Pubkey) -> Result<Account> {
+            let calls = self.calls.get();
+            self.calls.set(calls + 1);
+            if calls < self.failures {
+                return Err(anyhow!("connection reset"));
+            }
+            Ok(Account {
+                lamports: 42,
+                ..Default::default()
+            })
+        }
+    }
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_get_account_with_retry() {
+        let key = Pubkey::new_unique();
+        let fetcher = FlakyFetcher {
+            failures: 2,
+            calls: Default::default(),
+        };
+        let account = get_account_with_retry(&fetcher, key, 3, Duration::from_millis(1))
+            .await
+            .unwrap();
+        assert_eq!(account.lamports, 42);
+        assert_eq!(fetcher.calls.get(), 3);
+
+        let fetcher = FlakyFetcher {
+            failures: 2,
+            calls: Default::default(),
+        };
+        let err = get_account_with_retry(&fetcher, key, 2, Duration::from_millis(1))
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("after 2 attempts"));
+        assert_eq!(fetcher.calls.get(), 2);
+
+        // the backoff yields to the runtime, so other tasks keep running on its only thread
+        let ticked = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let ticker = {
+            let ticked = ticked.clone();
+            tokio::task::spawn(async move {
+                ticked.store(true, std::sync::atomic::Ordering::SeqCst);
+            })
+        };
+        let fetcher = FlakyFetcher {
+            failures: 1,
+            calls: Default::default(),
+        };
+        get_account_with_retry(&fetcher, key, 2, Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert!(ticked.load(std::sync::atomic::Ordering::SeqCst));
+        ticker.await.unwrap();
+    }
+    #[test]
+    fn test_ui_amount() {
+        assert_eq!(ui_amount(0, 0), 0.0);
+        assert_eq!(ui_amount(0, 9), 0.0);
+        assert_eq!(ui_amount(1_500_000, 6), 1.5);
+        assert_eq!(ui_amount(1_500_000, 0), 1_500_000.0);
+        // large weights lose precision, but remain in the right magnitude
+        assert_eq!(ui_amount(u64::MAX, 0), u64::MAX as f64);
+        assert!((ui_amount(u64::MAX, 9) - 18_446_744_073.709_553).abs() < 1e-3);
+    }
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("hello", 10), "hello");
+        assert_eq!(truncate_chars("hello", 5), "hello");
+        assert_eq!(truncate_chars("hello", 2), "he");
+        assert_eq!(truncate_chars("héllo", 2), "hé");
+        assert_eq!(truncate_chars("hello", 0), "");
+    }
+    #[test]
+    fn test_humanize_duration() {
+        use chrono::Duration;
+        let cases = [
+            (Duration::days(2) + Duration::hours(3), "2d 3h"),
+            (Duration::days(2) + Duration::minutes(59), "2d"),
+            (Duration::hours(48), "2d"),
+            (Duration::hours(1) + Duration::minutes(5), "1h 5m"),
+            (Duration::hours(23), "23h"),
+            (Duration::minutes(45), "45m"),
+            (Duration::seconds(59), "<1m"),
+            (Duration::zero(), "<1m"),
+            (Duration::seconds(-1), "ended"),
+            (Duration::hours(-5), "ended"),
+        ];
+        for (duration, expected) in cases {
+            assert_eq!(humanize_duration(duration), expected);
+        }
+    }
     #[tokio::test(flavor = "multi_thread")]
 
     async fn test_get_vote_records_for_proposal() {
         let proposal = static_pubkey!("9z4TmXcvSUksTB1LiUSHYFxoodH67Fi2Wt5riCo7i61U");
-        let rpc = RpcClient::new("http://51.222.241.93:8899".to_string());
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
         let voter_records = get_vote_records_for_proposal(&rpc, proposal).unwrap();
         assert_eq!(voter_records.len(), 8);
+        assert!(voter_records
+            .iter()
+            .all(|voter_record| voter_record.proposal.eq(&proposal)));
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_vote_record_for_voter() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+        let (_, proposal) = get_tulip_fixtures(&rpc);
+        let vote_records = get_vote_records_for_proposal(&rpc, proposal.key).unwrap();
+        let vote_record = vote_records.first().unwrap();
+        let token_owner_record =
+            spl_governance::state::token_owner_record::get_token_owner_record_address(
+                &GOVERNANCE_PROGRAM,
+                &get_tulip_realm_account(),
+                &proposal.proposal.governing_token_mint,
+                &vote_record.governing_token_owner,
+            );
+        let got = get_vote_record_for_voter(&rpc, proposal.key, token_owner_record)
+            .unwrap()
+            .unwrap();
+        assert_eq!(got.proposal, proposal.key);
+        assert_eq!(got.governing_token_owner, vote_record.governing_token_owner);
+        assert_eq!(got.voter_weight, vote_record.voter_weight);
+        // token owners which have not voted have no vote record
+        assert!(
+            get_vote_record_for_voter(&rpc, proposal.key, Pubkey::new_unique())
+                .unwrap()
+                .is_none()
+        );
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_token_owner_record() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+        let (_, proposal) = get_tulip_fixtures(&rpc);
+        // the proposal's voters are known token owners of the tulip realm
+        let vote_records = get_vote_records_for_proposal(&rpc, proposal.key).unwrap();
+        let owner = vote_records.first().unwrap().governing_token_owner;
+        let token_owner_record = get_token_owner_record(
+            &rpc,
+            get_tulip_realm_account(),
+            proposal.proposal.governing_token_mint,
+            owner,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(token_owner_record.realm, get_tulip_realm_account());
+        assert_eq!(
+            token_owner_record.governing_token_mint,
+            proposal.proposal.governing_token_mint
+        );
+        assert_eq!(token_owner_record.governing_token_owner, owner);
+        // wallets which never deposited have no token owner record
+        assert!(get_token_owner_record(
+            &rpc,
+            get_tulip_realm_account(),
+            proposal.proposal.governing_token_mint,
+            Pubkey::new_unique(),
+        )
+        .unwrap()
+        .is_none());
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_governances_for_realm() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+        let governances = get_governances_for_realm(&rpc, get_tulip_realm_account()).unwrap();
+        assert!(!governances.is_empty());
+        assert!(governances
+            .iter()
+            .any(|governance| governance.key.eq(&get_tulip_governance_account())));
+        assert!(governances
+            .iter()
+            .all(|governance| governance.governance.realm.eq(&get_tulip_realm_account())));
+    }
+    #[test]
+    fn test_decode_vote_records_skips_invalid() {
+        use borsh::BorshSerialize;
+        use spl_governance::state::vote_record::Vote;
+        let voter_record = VoteRecordV2 {
+            account_type: GovernanceAccountType::VoteRecordV2,
+            proposal: Pubkey::new_unique(),
+            governing_token_owner: Pubkey::new_unique(),
+            is_relinquished: false,
+            voter_weight: 100,
+            vote: Vote::Deny,
+            reserved_v2: [0; 8],
+        };
+        let data = voter_record.try_to_vec().unwrap();
+        let (valid_key, truncated_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let voter_records = decode_vote_records(vec![
+            (&valid_key, &data[..]),
+            (&truncated_key, &data[..data.len() / 2]),
+        ]);
+        assert_eq!(voter_records.len(), 1);
+        assert_eq!(voter_records[0].key, valid_key);
+        assert_eq!(voter_records[0].vote_record.voter_weight, 100);
+        assert_eq!(voter_records[0].vote_record.proposal, voter_record.proposal);
+    }
+    #[test]
+    fn test_slot_progress() {
+        let elapsed = chrono::Duration::seconds(60);
+        // 60 seconds is roughly 150 slots
+        assert_eq!(slot_progress(1000, 1150, elapsed), SlotProgress::Healthy);
+        assert_eq!(slot_progress(1000, 1080, elapsed), SlotProgress::Healthy);
+        assert_eq!(slot_progress(1000, 1050, elapsed), SlotProgress::Behind);
+        assert_eq!(slot_progress(1000, 1000, elapsed), SlotProgress::Stuck);
+        assert_eq!(slot_progress(1000, 999, elapsed), SlotProgress::Stuck);
+    }
+    #[test]
+    fn test_proposal_index() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_proposal_index.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let governance = Pubkey::new_unique();
+        let governing_mint = Pubkey::new_unique();
+        let key = spl_governance::state::proposal::get_proposal_address(
+            &GOVERNANCE_PROGRAM,
+            &governance,
+            &governing_mint,
+            &7_u32.to_le_bytes()[..],
+        );
+        assert_eq!(db.proposal_index(governance, governing_mint, key, 5), None);
+        assert_eq!(
+            db.proposal_index(governance, governing_mint, key, 10),
+            Some(7)
+        );
+        // served from the cache regardless of max_count
+        assert_eq!(
+            db.proposal_index(governance, governing_mint, key, 0),
+            Some(7)
+        );
+        assert_eq!(
+            db.proposal_index(governance, governing_mint, Pubkey::new_unique(), 10),
+            None
+        );
+        std::fs::remove_dir_all("realms_sdk_proposal_index.db").unwrap();
+    }
+    #[test]
+    fn test_proposal_addresses() {
+        let governance = Pubkey::new_unique();
+        let (community_mint, council_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let addresses = proposal_addresses(&governance, &[community_mint, council_mint], 3);
+        assert_eq!(addresses.len(), 2);
+        for (address, governing_mint) in addresses.iter().zip([community_mint, council_mint]) {
+            assert_eq!(
+                *address,
+                spl_governance::state::proposal::get_proposal_address(
+                    &GOVERNANCE_PROGRAM,
+                    &governance,
+                    &governing_mint,
+                    &3_u32.to_le_bytes()[..],
+                )
+            );
+        }
+        assert_ne!(addresses[0], addresses[1]);
     }
     #[test]
     fn test_timestamp() {
@@ -148,10 +1012,10 @@ mod test {
 
         let db = Database::new(opts).unwrap();
 
-        db.populate_database_with_mint_governance(
+        db.populate_database_with_governances(
             get_tulip_realm_account(),
-            get_tulip_council_mint(),
-            get_tulip_community_mint(),
+            &[get_tulip_governance_account()],
+            &[get_tulip_community_mint(), get_tulip_council_mint()],
             Utc::now(),
             &rpc,
         )
@@ -181,4 +1045,205 @@ mod test {
 
         std::fs::remove_dir_all("realms_sdk_list_voting2.db").unwrap();
     }
+    #[test]
+    fn test_list_proposals_ending_within() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_list_ending_within.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        let mut governance = GovernanceV2Wrapper {
+            governance: zeroed(),
+            key: Pubkey::new_unique(),
+        };
+        governance.governance.config.max_voting_time = 86400;
+        db.insert_governance(&governance).unwrap();
+
+        let now = date_time_from_timestamp(1655842130);
+        // (hours since voting started, state, governance). voting ends 24 hours after it starts
+        let fixtures = [
+            (23_i64, ProposalState::Voting, governance.key),
+            // ended an hour ago
+            (25, ProposalState::Voting, governance.key),
+            (20, ProposalState::Voting, governance.key),
+            // ends in 10 hours
+            (14, ProposalState::Voting, governance.key),
+            (22, ProposalState::Succeeded, governance.key),
+            // the governance isn't stored, so the end time is unknown
+            (23, ProposalState::Voting, Pubkey::new_unique()),
+        ];
+        let proposals: Vec<ProposalV2Wrapper> = fixtures
+            .iter()
+            .map(|(hours, state, governance_key)| {
+                let mut fixture = ProposalV2Wrapper {
+                    proposal: zeroed(),
+                    key: Pubkey::new_unique(),
+                };
+                fixture.proposal.governance = *governance_key;
+                fixture.proposal.state = state.clone();
+                fixture.proposal.voting_at = Some(now.timestamp() - hours * 3600);
+                fixture
+            })
+            .collect();
+        for proposal in proposals.iter() {
+            db.insert_proposal(proposal).unwrap();
+        }
+
+        let keys = |within: chrono::Duration| -> Vec<Pubkey> {
+            db.list_proposals_ending_within(within, now)
+                .unwrap()
+                .into_iter()
+                .map(|proposal| proposal.key)
+                .collect()
+        };
+        assert_eq!(
+            keys(chrono::Duration::hours(6)),
+            vec![proposals[0].key, proposals[2].key]
+        );
+        assert_eq!(
+            keys(chrono::Duration::hours(12)),
+            vec![proposals[0].key, proposals[2].key, proposals[3].key]
+        );
+        assert!(keys(chrono::Duration::minutes(30)).is_empty());
+
+        std::fs::remove_dir_all("realms_sdk_list_ending_within.db").unwrap();
+    }
+    #[test]
+    fn test_list_proposals_by_state() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_list_by_state.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+
+        let states = [
+            ProposalState::Voting,
+            ProposalState::Voting,
+            ProposalState::Succeeded,
+            ProposalState::Defeated,
+            ProposalState::Defeated,
+            ProposalState::Defeated,
+        ];
+        for state in states.iter() {
+            let mut fixture = ProposalV2Wrapper {
+                proposal: zeroed(),
+                key: Pubkey::new_unique(),
+            };
+            fixture.proposal.state = state.clone();
+            db.insert_proposal(&fixture).unwrap();
+        }
+        let count = |state: ProposalState| db.list_proposals_by_state(state).unwrap().len();
+        assert_eq!(count(ProposalState::Voting), 2);
+        assert_eq!(count(ProposalState::Succeeded), 1);
+        assert_eq!(count(ProposalState::Defeated), 3);
+        assert_eq!(count(ProposalState::Draft), 0);
+
+        std::fs::remove_dir_all("realms_sdk_list_by_state.db").unwrap();
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_voting_proposals_sorted() {
+        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
+
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_list_voting_sorted.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+
+        let (governance, proposal) = get_tulip_fixtures(&rpc);
+        let mut governance = governance;
+        governance.governance.config.max_voting_time = 86400;
+        db.insert_governance(&governance).unwrap();
+
+        let now = date_time_from_timestamp(1655842130);
+        // the proposal which started voting first has the soonest deadline
+        let hours_ago = [1_i64, 3, 2];
+        let proposals: Vec<ProposalV2Wrapper> = hours_ago
+            .iter()
+            .map(|hours| {
+                let mut fixture = ProposalV2Wrapper {
+                    proposal: proposal.proposal.clone(),
+                    key: Pubkey::new_unique(),
+                };
+                fixture.proposal.governance = governance.key;
+                fixture.proposal.state = spl_governance::state::enums::ProposalState::Voting;
+                fixture.proposal.voting_at = Some(now.timestamp() - hours * 3600);
+                fixture
+            })
+            .collect();
+        for proposal in proposals.iter() {
+            db.insert_proposal(proposal).unwrap();
+        }
+
+        let voting_proposals = db.list_voting_proposals(now).unwrap();
+        assert_eq!(voting_proposals.len(), 3);
+        assert_eq!(voting_proposals[0].key, proposals[1].key);
+        assert_eq!(voting_proposals[1].key, proposals[2].key);
+        assert_eq!(voting_proposals[2].key, proposals[0].key);
+
+        std::fs::remove_dir_all("realms_sdk_list_voting_sorted.db").unwrap();
+    }
+    #[test]
+    fn test_list_voting_proposals_multiple_governances() {
+        let opts = tulip_sled_util::config::DbOpts {
+            path: "realms_sdk_list_voting_governances.db".to_string(),
+            ..Default::default()
+        };
+        let db = Database::new(opts).unwrap();
+        // voting lasts a day in the first governance, and three days in the second
+        let governances: Vec<GovernanceV2Wrapper> = [86400_u32, 3 * 86400]
+            .iter()
+            .map(|max_voting_time| {
+                let mut governance = GovernanceV2Wrapper {
+                    governance: zeroed(),
+                    key: Pubkey::new_unique(),
+                };
+                governance.governance.config.max_voting_time = *max_voting_time;
+                db.insert_governance(&governance).unwrap();
+                governance
+            })
+            .collect();
+
+        let now = date_time_from_timestamp(1655842130);
+        // (hours since voting started, governance)
+        let fixtures = [
+            // ends in 4 hours
+            (20_i64, &governances[0]),
+            // ended 24 hours ago, had it been in the second governance it would still be voting
+            (48, &governances[0]),
+            // ends in 24 hours
+            (48, &governances[1]),
+            // ends in 2 hours
+            (70, &governances[1]),
+        ];
+        let proposals: Vec<ProposalV2Wrapper> = fixtures
+            .iter()
+            .map(|(hours, governance)| {
+                let mut fixture = ProposalV2Wrapper {
+                    proposal: zeroed(),
+                    key: Pubkey::new_unique(),
+                };
+                fixture.proposal.governance = governance.key;
+                fixture.proposal.state = ProposalState::Voting;
+                fixture.proposal.voting_at = Some(now.timestamp() - hours * 3600);
+                fixture
+            })
+            .collect();
+        for proposal in proposals.iter() {
+            db.insert_proposal(proposal).unwrap();
+        }
+
+        let keys: Vec<Pubkey> = db
+            .list_voting_proposals(now)
+            .unwrap()
+            .into_iter()
+            .map(|proposal| proposal.key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![proposals[3].key, proposals[0].key, proposals[2].key]
+        );
+
+        std::fs::remove_dir_all("realms_sdk_list_voting_governances.db").unwrap();
+    }
 }